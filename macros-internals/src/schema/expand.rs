@@ -336,11 +336,36 @@ macro_rules! derive_newtype {
         let lib = &$meta.lib;
         let name = &$meta.name;
         quote::quote! {
-            #lib::dev::macros::impl_selector_seed_serde! { @selector_seed_codec_deseed_newtype {} {} #name as #$inner_ty
-            }
             #lib::dev::macros::impl_selector_seed_serde! {
-                @selector_seed_select {} {} #name
+                @selector_seed_codec_deseed_newtype {} {
+                    #lib::dev::SelectorSeed<'a, Ctx, #$inner_ty>: #lib::dev::CodecDeserializeSeed<'de>,
+                } #name as #$inner_ty
             }
+            #lib::dev::macros::impl_selector_seed_serde! {
+                @selector_seed_select {} {
+                    #$inner_ty: #lib::dev::Select<Ctx>,
+                    for<'b, 'de> #lib::dev::SelectorSeed<'b, Ctx, #$inner_ty>:
+                        #lib::dev::CodecDeserializeSeed<'de>,
+                } #name
+            {
+                /// Delegates to the inner type, selecting the newtype itself
+                /// if the current selector is a matcher.
+                #[doc(hidden)]
+                fn select_in_seed(
+                    &self,
+                    seed: #lib::dev::SelectorSeed<'_, Ctx, Self>,
+                ) -> Result<(), #lib::dev::Error> {
+                    if seed.selector.is_matcher() {
+                        let seed = #lib::dev::macros::impl_selector_seed_serde! {
+                            @selector_seed_wrap
+                            seed { #name => #$inner_ty }
+                        };
+                        seed.select_in_dag(&self.0)
+                    } else {
+                        seed.select_in_inner(&self.0)
+                    }
+                }
+            }}
         }
     }};
     (@conv @has_constructor $def:ident, $meta:ident =>
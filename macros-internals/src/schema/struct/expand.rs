@@ -96,7 +96,180 @@ impl ExpandBasicRepresentation for BasicStructReprDefinition {
         impl_repr(self.iter(), meta, SchemaKind::Map.data_model_kind())
     }
     fn derive_select(&self, meta: &SchemaMeta) -> TokenStream {
-        TokenStream::default()
+        // TODO: support generic structs
+        if meta
+            .generics
+            .as_ref()
+            .is_some_and(|generics| !generics.params.is_empty())
+        {
+            return TokenStream::default();
+        }
+
+        let lib = &meta.lib;
+        let name = &meta.name;
+        let field_tys: Vec<&Type> = self.iter().map(|field| &field.value).collect();
+        let visit_branches = self.iter().map(visit_field_branch);
+        let select_in_fields = self.iter().map(select_in_field);
+        let patch_fields = self.iter().map(patch_field);
+
+        quote! {
+            #lib::dev::macros::impl_selector_seed_serde! {
+                @codec_seed_visitor {} {
+                    #(for<'b> #lib::dev::CodedSeed<'b, C, Ctx, #field_tys>:
+                        #lib::dev::DeserializeSeed<'de, Value = ()>,)*
+                } #name
+            {
+                #[inline]
+                fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "A `{}`", <#name as #lib::dev::Representation>::NAME)
+                }
+
+                #[inline]
+                fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: #lib::dev::MapAccess<'de>,
+                {
+                    use #lib::dev::*;
+
+                    match self.0.selector {
+                        Selector::Matcher(matcher) if self.0.is_dag() => {
+                            let dag = <#name as Representation>::deserialize::<C, _>(
+                                serde::de::value::MapAccessDeserializer::new(map),
+                            )?;
+                            return self
+                                .0
+                                .select_matched_dag(dag, matcher.label.as_deref())
+                                .map_err(A::Error::custom);
+                        }
+                        Selector::Matcher(matcher) => self
+                            .0
+                            .select_matched_node(SelectedNode::Map, matcher.label.as_deref())
+                            .map_err(A::Error::custom)?,
+                        Selector::ExploreFields(_)
+                        | Selector::ExploreAll(_)
+                        | Selector::ExploreUnion(_)
+                            if self.0.is_node() =>
+                        {
+                            self.0
+                                .select_node(SelectedNode::Map)
+                                .map_err(A::Error::custom)?
+                        }
+                        Selector::ExploreFields(_)
+                        | Selector::ExploreAll(_)
+                        | Selector::ExploreUnion(_) => {}
+                        selector => {
+                            return Err(A::Error::custom(
+                                Error::unsupported_selector::<#name>(selector),
+                            ));
+                        }
+                    };
+
+                    let (selector, state, mut callback, ctx) = self.0.into_parts();
+                    while let Some(key) = map.next_key::<IpldString>()? {
+                        match key.as_str() {
+                            #(#visit_branches,)*
+                            _ => {
+                                map.next_value::<IgnoredAny>()?;
+                            }
+                        }
+                    }
+
+                    Ok(())
+                }
+            }}
+            #lib::dev::macros::impl_selector_seed_serde! {
+                @codec_seed_visitor_ext {} {
+                    #(for<'b> #lib::dev::CodedSeed<'b, C, Ctx, #field_tys>:
+                        #lib::dev::DeserializeSeed<'de, Value = ()>,)*
+                } #name {}
+            }
+            #lib::dev::macros::impl_selector_seed_serde! {
+                @selector_seed_codec_deseed {} {
+                    #(for<'b> #lib::dev::SelectorSeed<'b, Ctx, #field_tys>:
+                        #lib::dev::CodecDeserializeSeed<'de>,)*
+                } #name
+            {
+                #[inline]
+                fn deserialize<const C: u64, D>(self, deserializer: D) -> Result<(), D::Error>
+                where
+                    D: #lib::dev::Deserializer<'de>,
+                {
+                    deserializer.deserialize_map(#lib::dev::CodecSeed::<C, _>(self))
+                }
+            }}
+            #lib::dev::macros::impl_selector_seed_serde! {
+                @selector_seed_select {} {
+                    Self: Clone,
+                    #(#field_tys: #lib::dev::Select<Ctx> + 'static,)*
+                    #(for<'b, 'de> #lib::dev::SelectorSeed<'b, Ctx, #field_tys>:
+                        #lib::dev::CodecDeserializeSeed<'de>,)*
+                } #name
+            {
+                /// Selects the struct itself if the current selector is a
+                /// matcher, descending into each explored field.
+                #[doc(hidden)]
+                fn select_in_seed(
+                    &self,
+                    mut seed: #lib::dev::SelectorSeed<'_, Ctx, Self>,
+                ) -> Result<(), #lib::dev::Error> {
+                    use #lib::dev::*;
+
+                    match seed.selector {
+                        Selector::Matcher(matcher) if seed.is_dag() => {
+                            return seed.select_matched_dag(self.clone(), matcher.label.as_deref());
+                        }
+                        Selector::Matcher(matcher) => {
+                            seed.select_matched_node(SelectedNode::Map, matcher.label.as_deref())?;
+                        }
+                        Selector::ExploreFields(_)
+                        | Selector::ExploreAll(_)
+                        | Selector::ExploreUnion(_)
+                            if seed.is_node() =>
+                        {
+                            seed.select_node(SelectedNode::Map)?;
+                        }
+                        selector @ Selector::ExploreUnion(_) => {
+                            if let Some(matcher) = selector.union_matcher() {
+                                seed.select_matched_dag(self.clone(), matcher.label.as_deref())?;
+                            }
+                        }
+                        Selector::ExploreFields(_) | Selector::ExploreAll(_) => {}
+                        selector => return Err(Error::unsupported_selector::<#name>(selector)),
+                    };
+
+                    let (selector, state, mut callback, ctx) = seed.into_parts();
+                    #(#select_in_fields)*
+
+                    Ok(())
+                }
+
+                /// Patches the struct itself if the current selector is a
+                /// matcher, otherwise patches each explored field.
+                #[doc(hidden)]
+                fn patch_seed(
+                    &mut self,
+                    mut seed: #lib::dev::SelectorSeed<'_, Ctx, Self>,
+                ) -> Result<bool, #lib::dev::Error> {
+                    use #lib::dev::*;
+
+                    let mut patched = match seed.selector {
+                        Selector::Matcher(_) => return seed.patch_matched(self),
+                        selector if selector.union_matcher().is_some() => {
+                            seed.patch_matched(self)?
+                        }
+                        Selector::ExploreFields(_)
+                        | Selector::ExploreAll(_)
+                        | Selector::ExploreUnion(_) => false,
+                        selector => return Err(Error::unsupported_selector::<#name>(selector)),
+                    };
+
+                    let (selector, state, mut callback, ctx) = seed.into_parts();
+                    #(#patch_fields)*
+
+                    Ok(patched)
+                }
+            }}
+        }
     }
     fn derive_conv(&self, meta: &SchemaMeta) -> TokenStream {
         quote!()
@@ -140,6 +313,77 @@ pub(super) fn impl_repr<'a>(
     repr_body
 }
 
+/// The field's name, as found in the serialized struct.
+fn field_name(field: &StructField) -> String {
+    field
+        .rename
+        .as_ref()
+        .map(|s| s.value())
+        .unwrap_or_else(|| field.key.to_string())
+}
+
+/// Selects against a field's serialized value, if the field is explored.
+fn visit_field_branch(field: &StructField) -> TokenStream {
+    let name = field_name(field);
+    let ty = &field.value;
+    quote! {
+        #name => {
+            let field = Field::from(#name);
+            let Some(next) = selector.next_selector(&field) else {
+                map.next_value::<IgnoredAny>()?;
+                continue;
+            };
+            SelectorSeed::field_select_seed::<#ty>(&next, state, &mut callback, ctx, field, None)
+                .map_err(A::Error::custom)
+                .and_then(|seed| map.next_value_seed(CodecSeed::<C, _>(seed)))?;
+            state.ascend::<#ty>().map_err(A::Error::custom)?;
+        }
+    }
+}
+
+/// Selects against a field's in-memory value, if present and explored.
+fn select_in_field(field: &StructField) -> TokenStream {
+    let name = field_name(field);
+    let key = &field.key;
+    let ty = &field.value;
+    let dag = if field.optional || field.nullable {
+        quote!(self.#key.as_ref())
+    } else {
+        quote!(Some(&self.#key))
+    };
+
+    quote! {
+        let field = Field::from(#name);
+        if let (Some(dag), Some(next)) = (#dag, selector.next_selector(&field)) {
+            SelectorSeed::field_select_seed::<#ty>(&next, state, &mut callback, ctx, field, None)?
+                .select_in_dag(dag)?;
+            state.ascend::<#ty>()?;
+        }
+    }
+}
+
+/// Patches a field's in-memory value, if present and explored.
+fn patch_field(field: &StructField) -> TokenStream {
+    let name = field_name(field);
+    let key = &field.key;
+    let ty = &field.value;
+    let dag = if field.optional || field.nullable {
+        quote!(self.#key.as_mut())
+    } else {
+        quote!(Some(&mut self.#key))
+    };
+
+    quote! {
+        let field = Field::from(#name);
+        if let (Some(dag), Some(next)) = (#dag, selector.next_selector(&field)) {
+            let seed =
+                SelectorSeed::field_select_seed::<#ty>(&next, state, &mut callback, ctx, field, None)?;
+            patched |= seed.patch_dag(dag)?;
+            state.ascend::<#ty>()?;
+        }
+    }
+}
+
 pub(crate) fn default_field_def(field: &StructField) -> TokenStream {
     let attrs = &field.attrs;
     let vis = &field.vis;
@@ -160,6 +404,10 @@ fn field_def(field: &StructField) -> TokenStream {
     let generics = field.generics.as_ref().map(|g| quote!(#g));
 
     let implicit_attr = field.implicit.as_ref().map(|_| quote!(#[serde(default)]));
+    // optional fields are absent, rather than null, when missing
+    let optional_attr = field
+        .optional
+        .then(|| quote!(#[serde(default, skip_serializing_if = "Option::is_none")]));
     let rename_attr = field
         .rename
         .as_ref()
@@ -168,6 +416,7 @@ fn field_def(field: &StructField) -> TokenStream {
     quote! {
         #(#attrs)*
         #implicit_attr
+        #optional_attr
         #rename_attr
         #vis #key: #value #generics
     }
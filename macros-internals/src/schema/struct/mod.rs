@@ -9,7 +9,7 @@ use crate::dev::{OuterAttributes, SchemaMeta};
 use proc_macro2::TokenStream;
 use std::ops::Deref;
 use syn::{
-    punctuated::Punctuated, Attribute, Expr, Generics, Ident, LitStr, Path, Token, Visibility,
+    punctuated::Punctuated, Attribute, Expr, Generics, Ident, LitStr, Path, Token, Type, Visibility,
 };
 
 #[derive(Debug)]
@@ -104,7 +104,7 @@ pub struct StructField {
     pub attrs: OuterAttributes,
     pub vis: Visibility,
     pub key: Ident,
-    pub value: Ident,
+    pub value: Type,
     pub generics: Option<Generics>,
     pub nullable: bool,
     pub optional: bool,
@@ -8,9 +8,9 @@ use crate::dev::{
 };
 use quote::quote;
 use syn::{
-    braced, parenthesized,
+    braced, bracketed, parenthesized,
     parse::{Parse, ParseStream, Result as ParseResult},
-    token, Expr, Generics, Ident, LitStr, Path, Visibility,
+    token, Expr, Generics, Ident, LitStr, Path, Type, Visibility,
 };
 
 impl Parse for StructReprDefinition {
@@ -105,7 +105,7 @@ impl Parse for StructField {
         try_parse_schema_directives(input, &mut optional, &mut nullable)?;
         try_parse_schema_directives(input, &mut optional, &mut nullable)?;
 
-        let value = input.parse::<Ident>()?;
+        let value = parse_field_type(input)?;
         let generics = input.parse::<Generics>().map_or(None, Some);
 
         if input.peek(token::Paren) {
@@ -133,6 +133,59 @@ impl Parse for StructField {
     }
 }
 
+/// Parses a field's type, which is either a named type or an inline map
+/// type (e.g. `{String:Selector}`).
+fn parse_field_type(input: ParseStream) -> ParseResult<Type> {
+    if input.peek(token::Bracket) {
+        let typedef_stream;
+        bracketed!(typedef_stream in input);
+        let nullable = typedef_stream.peek(kw::nullable);
+        if nullable {
+            typedef_stream.parse::<kw::nullable>()?;
+        }
+        let elem = schema_type(typedef_stream.parse::<Ident>()?);
+        if !typedef_stream.is_empty() {
+            return Err(input.error("invalid IPLD struct field list type definition"));
+        }
+
+        return Ok(match nullable {
+            true => Type::Verbatim(quote!(List<Option<#elem>>)),
+            false => Type::Verbatim(quote!(List<#elem>)),
+        });
+    }
+    if !input.peek(token::Brace) {
+        return Ok(schema_type(input.parse::<Ident>()?));
+    }
+
+    let typedef_stream;
+    braced!(typedef_stream in input);
+    let key = schema_type(typedef_stream.parse::<Ident>()?);
+    typedef_stream.parse::<Token![:]>()?;
+    let nullable = typedef_stream.peek(kw::nullable);
+    if nullable {
+        typedef_stream.parse::<kw::nullable>()?;
+    }
+    let value = schema_type(typedef_stream.parse::<Ident>()?);
+    if !typedef_stream.is_empty() {
+        return Err(input.error("invalid IPLD struct field map type definition"));
+    }
+
+    Ok(match nullable {
+        true => Type::Verbatim(quote!(Map<#key, Option<#value>>)),
+        false => Type::Verbatim(quote!(Map<#key, #value>)),
+    })
+}
+
+/// Maps a schema type name to its type, substituting `IpldString` for the
+/// schema's `String`.
+fn schema_type(name: Ident) -> Type {
+    if name == "String" {
+        Type::Verbatim(quote!(IpldString))
+    } else {
+        Type::Verbatim(quote!(#name))
+    }
+}
+
 fn try_parse_schema_directives(
     input: ParseStream,
     optional: &mut bool,
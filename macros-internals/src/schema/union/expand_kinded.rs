@@ -72,6 +72,8 @@ impl ExpandBasicRepresentation for KindedUnionReprDefinition {
             .filter(|f| f.key == SchemaKind::Link)
             .map(|f| f.visit_fn(meta))
             .flatten();
+        let select_in_branches = self.iter().map(|f| f.select_in_branch(meta));
+        let patch_branches = self.iter().map(UnionField::<SchemaKind>::patch_branch);
 
         quote! {
             // TODO: add a method that does the delegation to the variant, rather than codegen each
@@ -121,7 +123,35 @@ impl ExpandBasicRepresentation for KindedUnionReprDefinition {
             }
             #lib::dev::macros::impl_selector_seed_serde! {
                 @selector_seed_select {} {} #name
-            }
+            {
+                /// Selects the union itself if the current selector is a
+                /// matcher, otherwise selects against the current variant.
+                #[doc(hidden)]
+                fn select_in_seed(
+                    &self,
+                    seed: #lib::dev::SelectorSeed<'_, Ctx, Self>,
+                ) -> Result<(), #lib::dev::Error> {
+                    match self {
+                        #(#select_in_branches,)*
+                    }
+                }
+
+                /// Patches the union itself if the current selector is a
+                /// matcher, otherwise patches the current variant.
+                #[doc(hidden)]
+                fn patch_seed(
+                    &mut self,
+                    mut seed: #lib::dev::SelectorSeed<'_, Ctx, Self>,
+                ) -> Result<bool, #lib::dev::Error> {
+                    if seed.selector.is_matcher() {
+                        return seed.patch_matched(self);
+                    }
+
+                    match self {
+                        #(#patch_branches,)*
+                    }
+                }
+            }}
         }
     }
 
@@ -248,6 +278,38 @@ impl UnionField<SchemaKind> {
         quote!(Self::#name(ty) => Representation::name(ty))
     }
 
+    fn select_in_branch(&self, meta: &SchemaMeta) -> TokenStream {
+        let lib = &meta.lib;
+        let name = &meta.name;
+        let field_name = self.name();
+        let ty = self.ty(false);
+        let inner = match &self.wrapper {
+            Some(_) => quote!(&**inner),
+            None => quote!(inner),
+        };
+
+        quote! {
+            Self::#field_name(inner) if seed.selector.is_matcher() => {
+                let seed = #lib::dev::macros::impl_selector_seed_serde! {
+                    @selector_seed_wrap
+                    seed { #name::#field_name => #ty }
+                };
+                seed.select_in_dag(#inner)
+            }
+            Self::#field_name(inner) => seed.select_in_inner(#inner)
+        }
+    }
+
+    fn patch_branch(&self) -> TokenStream {
+        let name = self.name();
+        match &self.wrapper {
+            Some(wrapper) => {
+                quote!(Self::#name(inner) => seed.patch_inner(#wrapper::make_mut(inner)))
+            }
+            None => quote!(Self::#name(inner) => seed.patch_inner(inner)),
+        }
+    }
+
     fn dm_kind_branch(&self) -> TokenStream {
         let name = self.name();
         quote!(Self::#name(ty) => Representation::data_model_kind(ty))
@@ -0,0 +1,344 @@
+//! A SQLite-backed blockstore [`Context`] for `ipld`.
+//!
+//! Blocks are kept in a single `blocks` table keyed by their [`Cid`]'s bytes,
+//! alongside their multicodec code and size. An optional `links` table tracks
+//! which blocks reference which others.
+
+use ipld::dev::*;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{io::Cursor, path::Path};
+
+const BLOCKS_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS blocks (
+        cid BLOB PRIMARY KEY NOT NULL,
+        codec INTEGER NOT NULL,
+        size INTEGER NOT NULL,
+        data BLOB NOT NULL
+    ) WITHOUT ROWID;
+";
+
+const LINKS_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS links (
+        parent BLOB NOT NULL REFERENCES blocks (cid) ON DELETE CASCADE,
+        child BLOB NOT NULL,
+        PRIMARY KEY (parent, child)
+    ) WITHOUT ROWID;
+    CREATE INDEX IF NOT EXISTS links_child ON links (child);
+";
+
+const INSERT_BLOCK: &str =
+    "INSERT OR IGNORE INTO blocks (cid, codec, size, data) VALUES (?1, ?2, ?3, ?4)";
+
+/// Parameters for [`INSERT_BLOCK`].
+macro_rules! block_params {
+    ($cid:expr, $block:expr) => {
+        params![
+            $cid.to_bytes(),
+            $cid.multicodec_code() as i64,
+            $block.len() as i64,
+            $block
+        ]
+    };
+}
+
+/// A persistent [`Context`] of blocks stored in a SQLite database.
+#[derive(Debug)]
+pub struct SqliteContext {
+    conn: Connection,
+    track_links: bool,
+}
+
+impl SqliteContext {
+    /// Opens the blockstore in the database file at the given path, creating
+    /// it if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path).map_err(sql_err)?)
+    }
+
+    /// Opens a transient blockstore in memory.
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory().map_err(sql_err)?)
+    }
+
+    /// Creates a blockstore from an existing connection, creating the
+    /// `blocks` table if it doesn't exist.
+    ///
+    /// Link tracking is enabled if the database already has a `links` table.
+    pub fn from_connection(conn: Connection) -> Result<Self, Error> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")
+            .map_err(sql_err)?;
+        conn.execute_batch(BLOCKS_TABLE).map_err(sql_err)?;
+        let track_links = conn
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'links'",
+                [],
+                |_| Ok(()),
+            )
+            .optional()
+            .map_err(sql_err)?
+            .is_some();
+        Ok(Self { conn, track_links })
+    }
+
+    /// Enables tracking of links between blocks, creating the `links` table
+    /// if it doesn't exist.
+    pub fn with_links(mut self) -> Result<Self, Error> {
+        self.conn.execute_batch(LINKS_TABLE).map_err(sql_err)?;
+        self.track_links = true;
+        Ok(self)
+    }
+
+    /// Returns `true` if links between blocks are being tracked.
+    pub fn tracks_links(&self) -> bool {
+        self.track_links
+    }
+
+    /// Returns `true` if the blockstore contains the block.
+    pub fn has(&self, cid: &Cid) -> Result<bool, Error> {
+        Ok(self.size(cid)?.is_some())
+    }
+
+    /// Returns the size of the block in bytes, if present.
+    pub fn size(&self, cid: &Cid) -> Result<Option<u64>, Error> {
+        self.conn
+            .query_row(
+                "SELECT size FROM blocks WHERE cid = ?1",
+                [cid.to_bytes()],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()
+            .map(|size| size.map(|size| size as u64))
+            .map_err(sql_err)
+    }
+
+    /// Adds a block, after verifying that it matches its [`Cid`].
+    pub fn put_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        verify(cid, block)?;
+        insert(&self.conn, cid, block)
+    }
+
+    /// Adds many blocks within a single transaction, returning the number of
+    /// blocks not already present.
+    ///
+    /// If any block fails to match its [`Cid`], none of the blocks are added.
+    pub fn put_blocks<I, B>(&mut self, blocks: I) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = (Cid, B)>,
+        B: AsRef<[u8]>,
+    {
+        let tx = self.conn.transaction().map_err(sql_err)?;
+        let mut count = 0;
+        {
+            let mut stmt = tx.prepare_cached(INSERT_BLOCK).map_err(sql_err)?;
+            for (cid, block) in blocks {
+                let block = block.as_ref();
+                verify(&cid, block)?;
+                count += stmt.execute(block_params!(cid, block)).map_err(sql_err)?;
+            }
+        }
+        tx.commit().map_err(sql_err)?;
+        Ok(count)
+    }
+
+    /// Removes a block (and any links from it), returning `false` if it was
+    /// absent.
+    pub fn delete(&mut self, cid: &Cid) -> Result<bool, Error> {
+        let count = self
+            .conn
+            .execute("DELETE FROM blocks WHERE cid = ?1", [cid.to_bytes()])
+            .map_err(sql_err)?;
+        Ok(count > 0)
+    }
+
+    /// Returns the [`Cid`]s of every block in the blockstore, ordered by
+    /// their bytes.
+    pub fn cids(&self) -> Result<Vec<Cid>, Error> {
+        self.query_cids("SELECT cid FROM blocks ORDER BY cid", [])
+    }
+
+    /// Records the links from a stored block to other blocks, which need not
+    /// be present.
+    ///
+    /// Fails if link tracking isn't enabled, or if the parent block is absent.
+    pub fn put_links<'a, I>(&mut self, parent: &Cid, children: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = &'a Cid>,
+    {
+        self.ensure_links()?;
+        let parent = parent.to_bytes();
+        let tx = self.conn.transaction().map_err(sql_err)?;
+        {
+            let mut stmt = tx
+                .prepare_cached("INSERT OR IGNORE INTO links (parent, child) VALUES (?1, ?2)")
+                .map_err(sql_err)?;
+            for child in children {
+                stmt.execute(params![parent, child.to_bytes()])
+                    .map_err(sql_err)?;
+            }
+        }
+        tx.commit().map_err(sql_err)
+    }
+
+    /// Returns the [`Cid`]s of the blocks linked to by a block.
+    pub fn links(&self, parent: &Cid) -> Result<Vec<Cid>, Error> {
+        self.ensure_links()?;
+        self.query_cids(
+            "SELECT child FROM links WHERE parent = ?1 ORDER BY child",
+            [parent.to_bytes()],
+        )
+    }
+
+    /// Returns the [`Cid`]s of the blocks that link to a block.
+    pub fn referrers(&self, child: &Cid) -> Result<Vec<Cid>, Error> {
+        self.ensure_links()?;
+        self.query_cids(
+            "SELECT parent FROM links WHERE child = ?1 ORDER BY parent",
+            [child.to_bytes()],
+        )
+    }
+
+    fn ensure_links(&self) -> Result<(), Error> {
+        if self.track_links {
+            Ok(())
+        } else {
+            Err(Error::Context(anyhow::anyhow!(
+                "link tracking is not enabled"
+            )))
+        }
+    }
+
+    fn query_cids<P: rusqlite::Params>(&self, sql: &str, params: P) -> Result<Vec<Cid>, Error> {
+        let mut stmt = self.conn.prepare_cached(sql).map_err(sql_err)?;
+        let rows = stmt
+            .query_map(params, |row| row.get::<_, Vec<u8>>(0))
+            .map_err(sql_err)?;
+        rows.map(|bytes| Cid::try_from(bytes.map_err(sql_err)?.as_slice()))
+            .collect()
+    }
+}
+
+impl Context for SqliteContext {
+    type Reader = Cursor<Vec<u8>>;
+    type Writer = BlockWriter;
+
+    fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
+        self.conn
+            .query_row(
+                "SELECT data FROM blocks WHERE cid = ?1",
+                [cid.to_bytes()],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()
+            .map_err(sql_err)?
+            .map(Cursor::new)
+            .ok_or(Error::BlockNotFound(*cid))
+    }
+
+    fn block_writer(
+        &mut self,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Self::Writer, Error> {
+        BlockWriter::new(Vec::new(), multicodec_code, multihash_code)
+    }
+
+    fn flush_block(&mut self, writer: Self::Writer) -> Result<Cid, Error> {
+        let (cid, block) = writer.finish()?;
+        insert(&self.conn, &cid, &block)?;
+        Ok(cid)
+    }
+
+    fn insert_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        insert(&self.conn, cid, block)
+    }
+}
+
+fn insert(conn: &Connection, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+    conn.prepare_cached(INSERT_BLOCK)
+        .and_then(|mut stmt| stmt.execute(block_params!(cid, block)))
+        .map_err(sql_err)?;
+    Ok(())
+}
+
+fn verify(cid: &Cid, block: &[u8]) -> Result<(), Error> {
+    if &cid.derive_new(block)? != cid {
+        return Err(Error::Context(anyhow::anyhow!(
+            "block does not match cid: {:?}",
+            cid
+        )));
+    }
+    Ok(())
+}
+
+fn sql_err(err: rusqlite::Error) -> Error {
+    Error::Context(err.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn raw(block: &[u8]) -> Cid {
+        Cid::new(Version::V1, Raw::CODE, Multihash::SHA2_256, block).unwrap()
+    }
+
+    #[test]
+    fn test_sqlite_context() {
+        let path = std::env::temp_dir().join(format!("ipld-sqlite-{}.db", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let mut ctx = SqliteContext::open(&path).unwrap();
+        assert!(!ctx.tracks_links());
+        assert!(ctx.links(&raw(b"leaf")).is_err());
+
+        // writing blocks
+        let mut writer = ctx.block_writer(Raw::CODE, Multihash::SHA2_256).unwrap();
+        writer.write_all(b"leaf").unwrap();
+        let leaf = ctx.flush_block(writer).unwrap();
+        assert_eq!(leaf, raw(b"leaf"));
+        let links = vec![Link::<Bytes>::Cid(leaf)];
+        let root = ctx
+            .add_dag(&links, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+
+        // batch inserts are atomic
+        let blocks = vec![(raw(b"a"), &b"a"[..]), (raw(b"b"), &b"c"[..])];
+        assert!(ctx.put_blocks(blocks).is_err());
+        assert!(!ctx.has(&raw(b"a")).unwrap());
+        let blocks = vec![(raw(b"a"), &b"a"[..]), (leaf, &b"leaf"[..])];
+        assert_eq!(ctx.put_blocks(blocks).unwrap(), 1);
+        assert!(ctx.put_block(&raw(b"b"), b"c").is_err());
+
+        // reading blocks from a reopened context
+        drop(ctx);
+        let mut ctx = SqliteContext::open(&path).unwrap().with_links().unwrap();
+        assert_eq!(ctx.size(&leaf).unwrap(), Some(4));
+        assert_eq!(ctx.size(&raw(b"b")).unwrap(), None);
+        let mut expected = vec![leaf, root, raw(b"a")];
+        expected.sort_by_key(Cid::to_bytes);
+        assert_eq!(ctx.cids().unwrap(), expected);
+
+        assert_eq!(ctx.block_reader(&leaf).unwrap().into_inner(), b"leaf");
+        let block = ctx.block_reader(&root).unwrap().into_inner();
+        assert_eq!(
+            DagCbor::new().decode::<List<Link<Bytes>>>(&block).unwrap(),
+            links
+        );
+
+        // tracking links
+        ctx.put_links(&root, &[leaf, raw(b"a")]).unwrap();
+        assert!(ctx.put_links(&raw(b"b"), &[leaf]).is_err());
+        assert_eq!(ctx.referrers(&leaf).unwrap(), vec![root]);
+        assert_eq!(ctx.links(&root).unwrap().len(), 2);
+
+        // deleting blocks
+        assert!(ctx.delete(&root).unwrap());
+        assert!(!ctx.delete(&root).unwrap());
+        assert!(ctx.block_reader(&root).is_err());
+        assert!(ctx.referrers(&leaf).unwrap().is_empty());
+
+        drop(ctx);
+        assert!(SqliteContext::open(&path).unwrap().tracks_links());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,866 @@
+//! [CAR](https://ipld.io/specs/transport/car/) (Content Addressable aRchive)
+//! readers and writers, for importing and exporting sets of blocks.
+
+use crate::dev::*;
+use serde::de::value::Error as ValueError;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    fs::File,
+    io::{self, BufReader, Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// The CARv2 pragma, which is a CARv1 header of `{"version": 2}`.
+const V2_PRAGMA: [u8; 11] = [
+    0x0a, 0xa1, 0x67, 0x76, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, 0x02,
+];
+
+/// The length of the fixed-size CARv2 header that follows the pragma.
+const V2_HEADER_LEN: usize = 40;
+
+/// The offset of a CARv2 archive's data payload, when written by a
+/// [`CarWriter`].
+const V2_DATA_OFFSET: u64 = (V2_PRAGMA.len() + V2_HEADER_LEN) as u64;
+
+/// The multicodec code of the `MultihashIndexSorted` CARv2 index.
+const MULTIHASH_INDEX_SORTED: u64 = 0x0401;
+
+/// Reads the blocks of a CARv1 or CARv2 archive, verifying each block against
+/// its [`Cid`].
+///
+/// CARv2 archives are read sequentially, ignoring their index; use a
+/// [`CarContext`] for random access to an archive's blocks.
+pub struct CarReader<R> {
+    reader: io::Take<R>,
+    version: u64,
+    roots: List<Cid>,
+}
+
+impl<R: Read> CarReader<R> {
+    /// Reads the archive's header(s), leaving the reader at the first block.
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        let header = CarHeader::read(&mut reader)?;
+        match header.version {
+            1 => Ok(Self {
+                reader: reader.take(u64::MAX),
+                version: 1,
+                roots: header.roots()?,
+            }),
+            2 => {
+                let mut v2_header = [0u8; V2_HEADER_LEN];
+                reader.read_exact(&mut v2_header).map_err(decode_err)?;
+                let data_offset = read_u64_le(&v2_header[16..]);
+                let data_size = read_u64_le(&v2_header[24..]);
+
+                // skip any padding before the data payload
+                let padding = data_offset
+                    .checked_sub(V2_DATA_OFFSET)
+                    .ok_or_else(|| err("invalid CARv2 data offset"))?;
+                let skipped = io::copy(&mut (&mut reader).take(padding), &mut io::sink())
+                    .map_err(decode_err)?;
+                if skipped != padding {
+                    return Err(err("unexpected end of CARv2 archive"));
+                }
+
+                let mut reader = reader.take(data_size);
+                let header = CarHeader::read(&mut reader)?;
+                if header.version != 1 {
+                    return Err(err("CARv2 data payload is not a CARv1 archive"));
+                }
+
+                Ok(Self {
+                    reader,
+                    version: 2,
+                    roots: header.roots()?,
+                })
+            }
+            version => Err(err(format!("unsupported CAR version {}", version))),
+        }
+    }
+
+    /// The archive's CAR version.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The archive's root [`Cid`]s.
+    pub fn roots(&self) -> &[Cid] {
+        &self.roots
+    }
+
+    /// Reads the next block, failing if it doesn't match its [`Cid`].
+    pub fn next_block(&mut self) -> Result<Option<(Cid, Vec<u8>)>, Error> {
+        let (cid, block) = match read_section(&mut self.reader)? {
+            None => return Ok(None),
+            Some(section) => section,
+        };
+        verify_block(&cid, &block)?;
+        Ok(Some((cid, block)))
+    }
+
+    /// Reads every remaining block into a [`MemoryContext`].
+    pub fn into_context(self) -> Result<MemoryContext, Error> {
+        let mut ctx = MemoryContext::default();
+        for block in self {
+            let (cid, block) = block?;
+            ctx.insert_block(&cid, &block)?;
+        }
+        Ok(ctx)
+    }
+}
+
+impl<R: Read> Iterator for CarReader<R> {
+    type Item = Result<(Cid, Vec<u8>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_block().transpose()
+    }
+}
+
+impl<R> fmt::Debug for CarReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CarReader")
+            .field("version", &self.version)
+            .field("roots", &self.roots)
+            .finish()
+    }
+}
+
+/// Writes blocks to a CARv1 or CARv2 archive, verifying each block against
+/// its [`Cid`].
+///
+/// As a CARv2 archive's header and index depend on the written blocks, they
+/// are written by [`CarWriter::finish`]: seekable writers created with
+/// [`CarWriter::new_v2_seekable`] have their header patched in-place, while
+/// the data payloads of any other CARv2 archives are buffered in memory.
+#[derive(Debug)]
+pub struct CarWriter<W> {
+    writer: W,
+    v2: Option<V2Payload>,
+    /// Seeks within the writer, if the data payload is written directly to it.
+    seek: Option<fn(&mut W, SeekFrom) -> io::Result<u64>>,
+}
+
+#[derive(Debug, Default)]
+struct V2Payload {
+    /// The buffered data payload, unless written directly to the writer.
+    data: Vec<u8>,
+    /// The size of the data payload.
+    size: u64,
+    /// The offset of the archive within a seekable writer.
+    start: u64,
+    /// The offset of each block's section within the data payload.
+    offsets: Vec<(Cid, u64)>,
+}
+
+impl<W: Write> CarWriter<W> {
+    /// Creates a CARv1 archive, writing its header of root [`Cid`]s.
+    pub fn new(mut writer: W, roots: &[Cid]) -> Result<Self, Error> {
+        CarHeader::v1(roots).write(&mut writer)?;
+        Ok(Self {
+            writer,
+            v2: None,
+            seek: None,
+        })
+    }
+
+    /// Creates a CARv2 archive (with a `MultihashIndexSorted` index) with the
+    /// given root [`Cid`]s, buffering its data payload until
+    /// [`CarWriter::finish`].
+    pub fn new_v2(writer: W, roots: &[Cid]) -> Result<Self, Error> {
+        let mut v2 = V2Payload::default();
+        CarHeader::v1(roots).write(&mut v2.data)?;
+        v2.size = v2.data.len() as u64;
+        Ok(Self {
+            writer,
+            v2: Some(v2),
+            seek: None,
+        })
+    }
+
+    /// Writes a block, failing if it doesn't match its [`Cid`].
+    pub fn write_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        verify_block(cid, block)?;
+
+        let cid_bytes = cid.to_bytes();
+        let len = (cid_bytes.len() + block.len()) as u64;
+        let mut section = Vec::with_capacity(10 + cid_bytes.len());
+        write_varint(&mut section, len)?;
+        section.extend(cid_bytes);
+
+        let writer: &mut dyn Write = match &mut self.v2 {
+            None => &mut self.writer,
+            Some(v2) => {
+                v2.offsets.push((*cid, v2.size));
+                v2.size += section.len() as u64 + block.len() as u64;
+                match self.seek {
+                    Some(_) => &mut self.writer,
+                    None => &mut v2.data,
+                }
+            }
+        };
+        writer.write_all(&section).map_err(encode_err)?;
+        writer.write_all(block).map_err(encode_err)
+    }
+
+    /// Writes every block of a [`MemoryContext`], ordered by [`Cid`].
+    pub fn write_context(&mut self, ctx: &MemoryContext) -> Result<(), Error> {
+        let mut blocks: Vec<_> = ctx.blocks().collect();
+        blocks.sort_unstable_by_key(|(cid, _)| *cid);
+        for (cid, block) in blocks {
+            self.write_block(cid, block)?;
+        }
+        Ok(())
+    }
+
+    /// Completes the archive, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        if let Some(v2) = self.v2.take() {
+            let mut header = [0u8; V2_HEADER_LEN];
+            header[16..24].copy_from_slice(&V2_DATA_OFFSET.to_le_bytes());
+            header[24..32].copy_from_slice(&v2.size.to_le_bytes());
+            header[32..40].copy_from_slice(&(V2_DATA_OFFSET + v2.size).to_le_bytes());
+
+            match self.seek {
+                // patch the header, then return to the end of the index
+                Some(seek) => {
+                    write_index(&mut self.writer, &v2.offsets)?;
+                    let end = seek(&mut self.writer, SeekFrom::Current(0)).map_err(encode_err)?;
+                    let header_offset = v2.start + V2_PRAGMA.len() as u64;
+                    seek(&mut self.writer, SeekFrom::Start(header_offset)).map_err(encode_err)?;
+                    self.writer.write_all(&header).map_err(encode_err)?;
+                    seek(&mut self.writer, SeekFrom::Start(end)).map_err(encode_err)?;
+                }
+                None => {
+                    self.writer.write_all(&V2_PRAGMA).map_err(encode_err)?;
+                    self.writer.write_all(&header).map_err(encode_err)?;
+                    self.writer.write_all(&v2.data).map_err(encode_err)?;
+                    write_index(&mut self.writer, &v2.offsets)?;
+                }
+            }
+        }
+
+        self.writer.flush().map_err(encode_err)?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write + Seek> CarWriter<W> {
+    /// Creates a CARv2 archive (with a `MultihashIndexSorted` index) with the
+    /// given root [`Cid`]s, writing its data payload directly to the writer
+    /// and patching its header by [`CarWriter::finish`].
+    pub fn new_v2_seekable(mut writer: W, roots: &[Cid]) -> Result<Self, Error> {
+        let start = writer.stream_position().map_err(encode_err)?;
+        writer.write_all(&V2_PRAGMA).map_err(encode_err)?;
+        writer
+            .write_all(&[0u8; V2_HEADER_LEN])
+            .map_err(encode_err)?;
+
+        let mut header = Vec::new();
+        CarHeader::v1(roots).write(&mut header)?;
+        writer.write_all(&header).map_err(encode_err)?;
+        Ok(Self {
+            writer,
+            v2: Some(V2Payload {
+                size: header.len() as u64,
+                start,
+                ..Default::default()
+            }),
+            seek: Some(W::seek),
+        })
+    }
+}
+
+/// Index records of `(digest, offset)`, by record width.
+type Buckets<'a> = BTreeMap<u32, Vec<(&'a [u8], u64)>>;
+
+/// Writes a `MultihashIndexSorted` index: for each multihash code, buckets of
+/// equal-width `digest | offset` records, sorted by digest.
+fn write_index<W: Write>(mut writer: W, offsets: &[(Cid, u64)]) -> Result<(), Error> {
+    let mut codes: BTreeMap<u64, Buckets<'_>> = BTreeMap::new();
+    for (cid, offset) in offsets {
+        let digest = cid.digest();
+        codes
+            .entry(cid.multihash_code())
+            .or_default()
+            .entry(digest.len() as u32 + 8)
+            .or_default()
+            .push((digest, *offset));
+    }
+
+    let mut index = Vec::new();
+    write_varint(&mut index, MULTIHASH_INDEX_SORTED)?;
+    index.extend((codes.len() as i32).to_le_bytes());
+    for (code, buckets) in codes {
+        index.extend(code.to_le_bytes());
+        index.extend((buckets.len() as i32).to_le_bytes());
+        for (width, mut records) in buckets {
+            records.sort_unstable();
+            index.extend(width.to_le_bytes());
+            index.extend((records.len() as u64 * width as u64).to_le_bytes());
+            for (digest, offset) in records {
+                index.extend(digest);
+                index.extend(offset.to_le_bytes());
+            }
+        }
+    }
+
+    writer.write_all(&index).map_err(encode_err)
+}
+
+/// A [`Context`] that serves blocks directly from a CARv1 or CARv2 archive,
+/// seeking to each requested block rather than loading the archive into
+/// memory.
+///
+/// CARv2 archives are opened using their `MultihashIndexSorted` index, while
+/// CARv1 archives (and CARv2 archives without a supported index) are indexed
+/// by scanning their sections when opened. Blocks are found by their
+/// [`Cid`]'s multihash.
+pub struct CarContext<R> {
+    reader: R,
+    roots: List<Cid>,
+    /// The offset of the CARv1 data payload within the archive.
+    data_offset: u64,
+    /// The offset of each block's section within the data payload.
+    index: HashMap<DefaultMultihash, u64>,
+}
+
+impl CarContext<BufReader<File>> {
+    /// Opens and indexes the archive at the given path.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path).map_err(context_err)?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> CarContext<R> {
+    /// Reads the archive's header(s) and index.
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        reader.seek(SeekFrom::Start(0)).map_err(context_err)?;
+        let header = CarHeader::read(&mut reader)?;
+        let (data_offset, data_end, index_offset) = match header.version {
+            1 => (0, u64::MAX, None),
+            2 => {
+                let mut v2_header = [0u8; V2_HEADER_LEN];
+                reader.read_exact(&mut v2_header).map_err(decode_err)?;
+                let data_offset = read_u64_le(&v2_header[16..]);
+                let data_size = read_u64_le(&v2_header[24..]);
+                let index_offset = read_u64_le(&v2_header[32..]);
+                (
+                    data_offset,
+                    data_offset.saturating_add(data_size),
+                    Some(index_offset).filter(|offset| *offset != 0),
+                )
+            }
+            version => return Err(err(format!("unsupported CAR version {}", version))),
+        };
+
+        reader
+            .seek(SeekFrom::Start(data_offset))
+            .map_err(context_err)?;
+        let roots = CarHeader::read(&mut reader)?.roots()?;
+        let sections_offset = reader.stream_position().map_err(context_err)?;
+
+        let index = match index_offset {
+            Some(index_offset) => {
+                reader
+                    .seek(SeekFrom::Start(index_offset))
+                    .map_err(context_err)?;
+                read_index(&mut reader)?
+            }
+            None => None,
+        };
+        let index = match index {
+            Some(index) => index,
+            None => {
+                reader
+                    .seek(SeekFrom::Start(sections_offset))
+                    .map_err(context_err)?;
+                scan_index(&mut reader, data_offset, data_end)?
+            }
+        };
+
+        Ok(Self {
+            reader,
+            roots,
+            data_offset,
+            index,
+        })
+    }
+
+    /// The archive's root [`Cid`]s.
+    pub fn roots(&self) -> &[Cid] {
+        &self.roots
+    }
+
+    /// Whether or not the archive contains the block of the given [`Cid`].
+    pub fn contains(&self, cid: &Cid) -> bool {
+        self.index.contains_key(cid.multihash())
+    }
+}
+
+impl<R: Read + Seek> Context for CarContext<R> {
+    type Reader = Cursor<Vec<u8>>;
+    type Writer = Vec<u8>;
+
+    fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
+        let offset = self
+            .index
+            .get(cid.multihash())
+            .ok_or(Error::BlockNotFound(*cid))?;
+        self.reader
+            .seek(SeekFrom::Start(self.data_offset + offset))
+            .map_err(context_err)?;
+
+        match read_section(&mut self.reader)? {
+            Some((found, block)) if found.multihash() == cid.multihash() => Ok(Cursor::new(block)),
+            _ => Err(err(format!("index entry for Cid {:?} is invalid", cid))),
+        }
+    }
+}
+
+impl<R> fmt::Debug for CarContext<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CarContext")
+            .field("roots", &self.roots)
+            .field("blocks", &self.index.len())
+            .finish()
+    }
+}
+
+/// Reads a `MultihashIndexSorted` index, or `None` for any other kind of
+/// index.
+fn read_index<R: Read>(mut reader: R) -> Result<Option<HashMap<DefaultMultihash, u64>>, Error> {
+    if read_varint(&mut reader)? != Some(MULTIHASH_INDEX_SORTED) {
+        return Ok(None);
+    }
+
+    let mut index = HashMap::new();
+    let codes = read_le(&mut reader, 4)?;
+    for _ in 0..codes {
+        let code = read_le(&mut reader, 8)?;
+        let buckets = read_le(&mut reader, 4)?;
+        for _ in 0..buckets {
+            let width = read_le(&mut reader, 4)? as usize;
+            let len = read_le(&mut reader, 8)?;
+            if width <= 8 || len % width as u64 != 0 {
+                return Err(err("invalid CARv2 index bucket"));
+            }
+
+            let mut record = vec![0u8; width];
+            for _ in 0..len / width as u64 {
+                reader.read_exact(&mut record).map_err(decode_err)?;
+                let (digest, offset) = record.split_at(width - 8);
+                index.insert(DefaultMultihash::wrap(code, digest)?, read_u64_le(offset));
+            }
+        }
+    }
+
+    Ok(Some(index))
+}
+
+/// Indexes a CARv1 data payload by reading each of its sections' [`Cid`]s.
+fn scan_index<R: Read + Seek>(
+    mut reader: R,
+    data_offset: u64,
+    data_end: u64,
+) -> Result<HashMap<DefaultMultihash, u64>, Error> {
+    let mut index = HashMap::new();
+    loop {
+        let offset = reader.stream_position().map_err(context_err)?;
+        if offset >= data_end {
+            return Ok(index);
+        }
+
+        let len = match read_varint(&mut reader)? {
+            None => return Ok(index),
+            Some(len) => len,
+        };
+
+        let start = reader.stream_position().map_err(context_err)?;
+        let cid = DefaultCid::read_bytes(&mut reader)?;
+        index.insert(*cid.hash(), offset - data_offset);
+        reader
+            .seek(SeekFrom::Start(start + len))
+            .map_err(context_err)?;
+    }
+}
+
+/// The DAG-CBOR header of a CARv1 archive (or of the CARv2 pragma).
+#[derive(Clone, Debug, Default, PartialEq)]
+struct CarHeader {
+    version: u64,
+    roots: Option<List<Cid>>,
+}
+
+impl CarHeader {
+    fn v1(roots: &[Cid]) -> Self {
+        Self {
+            version: 1,
+            roots: Some(roots.to_vec()),
+        }
+    }
+
+    fn roots(self) -> Result<List<Cid>, Error> {
+        self.roots.ok_or_else(|| err("header is missing `roots`"))
+    }
+
+    fn read<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let len = read_varint(&mut reader)?.ok_or_else(|| err("missing header"))?;
+        let mut bytes = Vec::new();
+        reader
+            .take(len)
+            .read_to_end(&mut bytes)
+            .map_err(decode_err)?;
+        if bytes.len() as u64 != len {
+            return Err(err("unexpected end of header"));
+        }
+        DagCbor::new().decode(&bytes)
+    }
+
+    fn write<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let bytes = DagCbor::new().encode(self)?;
+        write_varint(&mut writer, bytes.len() as u64)?;
+        writer.write_all(&bytes).map_err(encode_err)
+    }
+}
+
+impl Representation for CarHeader {
+    const NAME: &'static str = "CarHeader";
+    const SCHEMA: &'static str = "type CarHeader struct { roots optional [&Any] version Int }";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+    const SCHEMA_KIND: Kind = Kind::Struct;
+    const HAS_LINKS: bool = true;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeStruct;
+
+        let mut st = serializer.serialize_struct(Self::NAME, 1 + self.roots.is_some() as usize)?;
+        if let Some(roots) = &self.roots {
+            st.serialize_field("roots", &EncoderElem::<'_, C, _>(roots))?;
+        }
+        st.serialize_field("version", &EncoderElem::<'_, C, _>(&self.version))?;
+        st.end()
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct HeaderVisitor<const C: u64>;
+        impl<'de, const C: u64> Visitor<'de> for HeaderVisitor<C> {
+            type Value = CarHeader;
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "A `{}`", CarHeader::NAME)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut version = None;
+                let mut roots = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "roots" if roots.is_none() => {
+                            roots = Some(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        "version" if version.is_none() => {
+                            version = Some(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        key => return Err(A::Error::custom(format!("unexpected key `{}`", key))),
+                    }
+                }
+
+                Ok(CarHeader {
+                    version: version.ok_or_else(|| A::Error::missing_field("version"))?,
+                    roots,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(HeaderVisitor::<C>)
+    }
+}
+
+impl Serialize for CarHeader {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Representation::serialize::<{ DagCbor::CODE }, _>(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CarHeader {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <Self as Representation>::deserialize::<{ DagCbor::CODE }, _>(deserializer)
+    }
+}
+
+/// Reads the next section's [`Cid`] and block, or `None` if the reader is
+/// already exhausted.
+fn read_section<R: Read>(mut reader: R) -> Result<Option<(Cid, Vec<u8>)>, Error> {
+    let len = match read_varint(&mut reader)? {
+        None => return Ok(None),
+        Some(len) => len,
+    };
+
+    let mut section = Vec::new();
+    reader
+        .take(len)
+        .read_to_end(&mut section)
+        .map_err(decode_err)?;
+    if section.len() as u64 != len {
+        return Err(err("unexpected end of section"));
+    }
+
+    let mut bytes = section.as_slice();
+    let cid = Cid::from(DefaultCid::read_bytes(&mut bytes)?);
+    Ok(Some((cid, bytes.to_vec())))
+}
+
+fn verify_block(cid: &Cid, block: &[u8]) -> Result<(), Error> {
+    if &cid.derive_new(block)? != cid {
+        return Err(err(format!("block does not match its Cid {:?}", cid)));
+    }
+    Ok(())
+}
+
+/// Reads a little-endian integer of `len` bytes.
+fn read_le<R: Read>(reader: &mut R, len: usize) -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf[..len]).map_err(decode_err)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_u64_le(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(buf)
+}
+
+/// Reads an unsigned varint, or `None` if the reader is already exhausted.
+fn read_varint<R: Read>(mut reader: R) -> Result<Option<u64>, Error> {
+    let mut value = 0u64;
+    for i in 0..10 {
+        let mut byte = [0u8];
+        if reader.read(&mut byte).map_err(decode_err)? == 0 {
+            return match i {
+                0 => Ok(None),
+                _ => Err(err("unexpected end of varint")),
+            };
+        }
+
+        let byte = byte[0];
+        value |= ((byte & 0x7f) as u64) << (i * 7);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i > 0 {
+                return Err(err("varint is not minimally encoded"));
+            }
+            return Ok(Some(value));
+        }
+    }
+    Err(err("invalid varint"))
+}
+
+fn write_varint<W: Write + ?Sized>(writer: &mut W, mut value: u64) -> Result<(), Error> {
+    let mut buf = [0u8; 10];
+    let mut len = 0;
+    loop {
+        buf[len] = (value as u8 & 0x7f) | if value >= 0x80 { 0x80 } else { 0 };
+        len += 1;
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+    }
+    writer.write_all(&buf[..len]).map_err(encode_err)
+}
+
+fn err(msg: impl fmt::Display) -> Error {
+    Error::decoder(<ValueError as de::Error>::custom(format!(
+        "invalid CAR: {}",
+        msg
+    )))
+}
+
+fn decode_err(err: io::Error) -> Error {
+    Error::Decoder(err.into())
+}
+
+fn encode_err(err: io::Error) -> Error {
+    Error::Encoder(err.into())
+}
+
+fn context_err(err: io::Error) -> Error {
+    Error::Context(err.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selectors::{add_leaf_dag, assert_resolves_leaf};
+
+    fn context() -> (MemoryContext, List<Cid>) {
+        let mut ctx = MemoryContext::default();
+        let (root, leaf) = add_leaf_dag(&mut ctx);
+        (ctx, vec![root, leaf])
+    }
+
+    fn assert_roundtrip(bytes: &[u8], version: u64, roots: &[Cid]) {
+        let reader = CarReader::new(bytes).unwrap();
+        assert_eq!(reader.version(), version);
+        assert_eq!(reader.roots(), roots);
+        assert_resolves_leaf(reader.into_context().unwrap(), roots[0]);
+
+        let ctx = CarContext::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(ctx.roots(), roots);
+        assert_resolves_leaf(ctx, roots[0]);
+    }
+
+    #[test]
+    fn test_v1() {
+        let (ctx, roots) = context();
+        let mut writer = CarWriter::new(Vec::new(), &roots[..1]).unwrap();
+        writer.write_context(&ctx).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        // header of `{"roots": [root], "version": 1}`
+        assert_eq!(bytes[0] as usize, 22 + roots[0].to_bytes().len());
+        assert_roundtrip(&bytes, 1, &roots[..1]);
+    }
+
+    #[test]
+    fn test_v2() {
+        let (ctx, roots) = context();
+        let mut writer = CarWriter::new_v2(Vec::new(), &roots).unwrap();
+        writer.write_context(&ctx).unwrap();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes[..V2_PRAGMA.len()], V2_PRAGMA);
+        assert_roundtrip(&bytes, 2, &roots);
+
+        // the index follows the data payload
+        let header = &bytes[V2_PRAGMA.len()..V2_DATA_OFFSET as usize];
+        let index_offset = read_u64_le(&header[32..]) as usize;
+        let mut index = &bytes[index_offset..];
+        assert_eq!(
+            read_varint(&mut index).unwrap(),
+            Some(MULTIHASH_INDEX_SORTED)
+        );
+        assert_eq!(index[..4], 2i32.to_le_bytes());
+
+        // the index's offsets point to each block's section
+        let data = &bytes[V2_DATA_OFFSET as usize..index_offset];
+        let offset = read_u64_le(&index[index.len() - 8..]) as usize;
+        let mut section = &data[offset..];
+        read_varint(&mut section).unwrap();
+        assert!(Cid::try_from(&section[..36]).is_ok());
+    }
+
+    #[test]
+    fn test_v2_seekable() {
+        let (ctx, roots) = context();
+        let mut writer = CarWriter::new_v2(Vec::new(), &roots).unwrap();
+        writer.write_context(&ctx).unwrap();
+        let buffered = writer.finish().unwrap();
+
+        // the archive is written as it goes, after anything already written
+        let mut cursor = Cursor::new(b"prefix".to_vec());
+        cursor.seek(SeekFrom::End(0)).unwrap();
+        let mut writer = CarWriter::new_v2_seekable(cursor, &roots).unwrap();
+        writer.write_context(&ctx).unwrap();
+        assert!(writer.v2.as_ref().unwrap().data.is_empty());
+
+        let cursor = writer.finish().unwrap();
+        assert_eq!(cursor.position(), cursor.get_ref().len() as u64);
+        let bytes = cursor.into_inner();
+        assert_eq!(&bytes[..6], b"prefix");
+        assert_eq!(bytes[6..], buffered);
+    }
+
+    #[test]
+    fn test_context() {
+        let (ctx, roots) = context();
+        let path = std::env::temp_dir().join(format!("ipld-car-{}.car", std::process::id()));
+        let file = File::create(&path).unwrap();
+        let mut writer = CarWriter::new_v2_seekable(file, &roots).unwrap();
+        writer.write_context(&ctx).unwrap();
+        writer.finish().unwrap();
+
+        let mut ctx = CarContext::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(ctx.contains(&roots[1]));
+
+        // blocks are found by multihash, and in any order
+        let leaf = Cid::from(DefaultCid::new_v1(DagCbor::CODE, *roots[1].multihash()));
+        let mut block = Vec::new();
+        ctx.block_reader(&leaf)
+            .unwrap()
+            .read_to_end(&mut block)
+            .unwrap();
+        assert_eq!(block, b"leaf");
+        assert_resolves_leaf(&mut ctx, roots[0]);
+
+        let missing =
+            Cid::new(Version::V1, Raw::CODE, Multihash::SHA2_256, &b"missing"[..]).unwrap();
+        assert!(!ctx.contains(&missing));
+        assert!(ctx.block_reader(&missing).is_err());
+
+        // selecting from the archive
+        let selected: Bytes = Params::<'_, _, Bytes>::new_select(roots[1])
+            .into_dag_iter(&mut ctx)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .dag
+            .downcast()
+            .unwrap();
+        assert_eq!(selected.as_ref(), b"leaf");
+    }
+
+    #[test]
+    fn test_invalid() {
+        let (ctx, roots) = context();
+        let mut writer = CarWriter::new(Vec::new(), &roots).unwrap();
+        writer.write_context(&ctx).unwrap();
+        let mut bytes = writer.finish().unwrap();
+
+        // writing a block that doesn't match its Cid
+        let mut writer = CarWriter::new(Vec::new(), &roots).unwrap();
+        assert!(writer.write_block(&roots[1], b"not the leaf").is_err());
+
+        // reading a corrupted block
+        *bytes.last_mut().unwrap() ^= 0xff;
+        let reader = CarReader::new(bytes.as_slice()).unwrap();
+        assert!(reader.into_context().is_err());
+
+        // reading a truncated archive
+        let reader = CarReader::new(&bytes[..bytes.len() - 1]).unwrap();
+        assert!(reader.into_context().is_err());
+
+        // reading an unsupported version
+        let header = DagCbor::new()
+            .encode(&CarHeader {
+                version: 3,
+                roots: None,
+            })
+            .unwrap();
+        let mut bytes = vec![header.len() as u8];
+        bytes.extend(header);
+        assert!(CarReader::new(bytes.as_slice()).is_err());
+    }
+}
@@ -182,6 +182,34 @@ impl Representation for Cid {
                 }
             }
         }
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "dag-pb")] {
+                if C == DagPb::CODE {
+                    return DagPb::serialize_link(self, serializer);
+                }
+            }
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "dag-jose")] {
+                if C == DagJose::CODE {
+                    return DagJose::serialize_link(self, serializer);
+                }
+            }
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "dag-cose")] {
+                if C == DagCose::CODE {
+                    return DagCose::serialize_link(self, serializer);
+                }
+            }
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "raw")] {
+                if C == Raw::CODE {
+                    return Err(S::Error::custom("Raw codec cannot encode links"));
+                }
+            }
+        }
 
         Serialize::serialize(self, serializer)
     }
@@ -206,6 +234,27 @@ impl Representation for Cid {
                 }
             }
         }
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "dag-pb")] {
+                if C == DagPb::CODE {
+                    return DagPb::deserialize_link(deserializer, CidVisitor);
+                }
+            }
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "dag-jose")] {
+                if C == DagJose::CODE {
+                    return DagJose::deserialize_link(deserializer, CidVisitor);
+                }
+            }
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "dag-cose")] {
+                if C == DagCose::CODE {
+                    return DagCose::deserialize_link(deserializer, CidVisitor);
+                }
+            }
+        }
 
         Deserialize::deserialize(deserializer)
     }
@@ -37,7 +37,10 @@ impl DagCbor {
         cid: &Cid,
         serializer: S,
     ) -> Result<S::Ok, S::Error> {
-        Tagged::new(Some(Self::LINK_TAG), &cid.to_bytes()).serialize(serializer)
+        // links are prefixed with the identity multibase prefix
+        let mut bytes = vec![0];
+        bytes.extend(cid.to_bytes());
+        Tagged::new(Some(Self::LINK_TAG), bytes::Bytes::from(bytes)).serialize(serializer)
     }
 
     ///
@@ -65,11 +68,14 @@ impl DagCbor {
         D: Deserializer<'de>,
         V: IpldVisitorExt<'de>,
     {
-        match Tagged::<&'de [u8]>::deserialize(deserializer)? {
+        match Tagged::<bytes::Bytes>::deserialize(deserializer)? {
             Tagged {
                 tag: Some(Self::LINK_TAG),
                 value,
-            } => visitor.visit_link_borrowed_bytes(value),
+            } => match value.split_first() {
+                Some((0, cid_bytes)) => visitor.visit_link_bytes(cid_bytes),
+                _ => Err(D::Error::custom("expected a Cid with a multibase prefix")),
+            },
             Tagged { tag: Some(tag), .. } => Err(D::Error::custom(format!(
                 "unexpected CBOR tag for Cid: {}",
                 tag
@@ -127,8 +133,25 @@ impl TryFrom<u64> for DagCbor {
     }
 }
 
+/// Serializes the payload `Cid` of a signed DagJOSE or DagCOSE envelope as
+/// bytes, rather than as a link.
+#[cfg(any(feature = "dag-cose", feature = "dag-jose"))]
+pub(crate) struct PayloadElem<'a>(pub &'a Cid);
+#[cfg(any(feature = "dag-cose", feature = "dag-jose"))]
+impl<'a> Serialize for PayloadElem<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.0.to_bytes())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::codecs_::test_utils::*;
+
     #[test]
     fn test_null() {}
 
@@ -145,7 +168,13 @@ mod tests {
     fn test_bytes() {}
 
     #[test]
-    fn test_link() {}
+    fn test_link() {
+        let cid =
+            Cid::try_from("bafkreidon73zkcrwdb5iafqtijxildoonbwnpv7dyd6ef3qdgads2jc4su").unwrap();
+        let mut expected = vec![0xd8, 0x2a, 0x58, 0x25, 0x00];
+        expected.extend(cid.to_bytes());
+        roundtrip_bytes_codec::<Cid>(DagCbor::CODE, &[(cid, &expected)]);
+    }
 
     #[test]
     fn test_seq() {}
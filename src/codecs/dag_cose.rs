@@ -0,0 +1,934 @@
+//! IPLD DagCose codec.
+
+use super::dag_cbor::PayloadElem;
+use crate::dev::{macros::impl_selector_seed_serde, *};
+use serde_cbor::{
+    de::{IoRead, SliceRead},
+    ser::IoWrite,
+    tags::{current_cbor_tag, Tagged},
+    Deserializer as CborDeserializer, Serializer as CborSerializer, Value as CborValue,
+};
+use std::{
+    convert::TryFrom,
+    fmt,
+    io::{Read, Write},
+};
+
+/// The DagCOSE codec, which encodes [`DagCoseMessage`]s as tagged
+/// [COSE](https://www.rfc-editor.org/rfc/rfc9052) `COSE_Sign1` or
+/// `COSE_Encrypt0` structures, reusing the [`DagCbor`] encoding for headers.
+///
+/// Both tagged and untagged messages are decoded, but messages are always
+/// written with their COSE tag.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DagCose;
+
+impl DagCose {
+    /// The multicodec code that identifies this IPLD Codec.
+    pub const CODE: u64 = 0x86;
+
+    /// The CBOR tag of a `COSE_Sign1` message.
+    pub const SIGN1_TAG: u64 = 18;
+
+    /// The CBOR tag of a `COSE_Encrypt0` message.
+    pub const ENCRYPT0_TAG: u64 = 16;
+
+    #[doc(hidden)]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Serializes links as tagged DagCBOR links.
+    #[inline]
+    pub(crate) fn serialize_link<S: Serializer>(
+        cid: &Cid,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        DagCbor::serialize_link(cid, serializer)
+    }
+
+    /// Deserializes tagged DagCBOR links.
+    #[inline]
+    pub(crate) fn deserialize_link<'de, D, V>(
+        deserializer: D,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: IpldVisitorExt<'de>,
+    {
+        DagCbor::deserialize_link(deserializer, visitor)
+    }
+
+    pub(crate) fn read_with_seed<'de, S, R>(&mut self, seed: S, reader: R) -> Result<(), Error>
+    where
+        S: CodecDeserializeSeed<'de>,
+        R: Read,
+    {
+        let mut de = CborDeserializer::new(IoRead::new(reader));
+        seed.deserialize::<{ Self::CODE }, _>(&mut de)
+            .map_err(Error::decoder)
+    }
+}
+
+impl Codec for DagCose {
+    fn write<T, W>(&mut self, dag: &T, writer: W) -> Result<(), Error>
+    where
+        T: Representation,
+        W: Write,
+    {
+        let mut ser = CborSerializer::new(IoWrite::new(writer));
+        Representation::serialize::<{ Self::CODE }, _>(dag, &mut ser).map_err(Error::encoder)
+    }
+
+    fn decode<'de, T>(&mut self, bytes: &'de [u8]) -> Result<T, Error>
+    where
+        T: Representation,
+    {
+        let mut de = CborDeserializer::new(SliceRead::new(bytes));
+        let dag =
+            Representation::deserialize::<{ Self::CODE }, _>(&mut de).map_err(Error::decoder)?;
+        de.end().map_err(Error::decoder)?;
+        Ok(dag)
+    }
+
+    fn read<T, R>(&mut self, reader: R) -> Result<T, Error>
+    where
+        T: Representation,
+        R: Read,
+    {
+        let mut de = CborDeserializer::new(IoRead::new(reader));
+        let dag =
+            Representation::deserialize::<{ Self::CODE }, _>(&mut de).map_err(Error::decoder)?;
+        de.end().map_err(Error::decoder)?;
+        Ok(dag)
+    }
+}
+
+impl TryFrom<u64> for DagCose {
+    type Error = Error;
+    fn try_from(code: u64) -> Result<Self, Self::Error> {
+        match code {
+            Self::CODE => Ok(Self),
+            _ => Err(Error::UnknownMulticodecCode(code)),
+        }
+    }
+}
+
+/// A DagCose block, either a signed [`DagCoseSign1`] or an encrypted
+/// [`DagCoseEncrypt0`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum DagCoseMessage {
+    /// A `COSE_Sign1` message.
+    Sign1(DagCoseSign1),
+    /// A `COSE_Encrypt0` message.
+    Encrypt0(DagCoseEncrypt0),
+}
+
+/// A `COSE_Sign1` message, whose payload is the `Cid` of the signed dag.
+///
+/// ```ipldsch
+/// type DagCoseSign1 struct {
+///     protected CoseProtectedHeader
+///     unprotected CoseHeader
+///     payload Bytes
+///     signature Bytes
+/// } representation tuple
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DagCoseSign1 {
+    /// The integrity-protected header.
+    pub protected: CoseProtectedHeader,
+    /// The unprotected header.
+    pub unprotected: CoseHeader,
+    /// The link to the signed dag, encoded as the bytes of its `Cid`.
+    pub payload: Link,
+    /// The signature over the protected header and payload.
+    pub signature: Bytes,
+}
+
+/// A `COSE_Encrypt0` message.
+///
+/// ```ipldsch
+/// type DagCoseEncrypt0 struct {
+///     protected CoseProtectedHeader
+///     unprotected CoseHeader
+///     ciphertext Bytes
+/// } representation tuple
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DagCoseEncrypt0 {
+    /// The integrity-protected header.
+    pub protected: CoseProtectedHeader,
+    /// The unprotected header.
+    pub unprotected: CoseHeader,
+    /// The encrypted content.
+    pub ciphertext: Bytes,
+}
+
+/// A COSE header map, whose entries are kept in their encoded order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CoseHeader(List<(CoseLabel, Any)>);
+
+/// A COSE header label, either an integer or a string.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CoseLabel {
+    /// An integer label, such as those registered with IANA.
+    Int(Int),
+    /// A string label.
+    Text(IpldString),
+}
+
+/// A COSE protected header, along with the exact bytes it was encoded as.
+///
+/// The bytes are what get signed, so they are preserved as decoded.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CoseProtectedHeader {
+    header: CoseHeader,
+    bytes: Bytes,
+}
+
+impl CoseHeader {
+    /// The `alg` header label.
+    pub const ALG: Int = 1;
+    /// The `content type` header label.
+    pub const CONTENT_TYPE: Int = 3;
+    /// The `kid` header label.
+    pub const KID: Int = 4;
+    /// The `IV` header label.
+    pub const IV: Int = 5;
+
+    /// Creates an empty header.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds (or replaces) a header parameter.
+    pub fn with(mut self, label: impl Into<CoseLabel>, value: impl Into<Any>) -> Self {
+        self.insert(label, value);
+        self
+    }
+
+    /// Adds (or replaces) a header parameter, returning the previous value.
+    pub fn insert(&mut self, label: impl Into<CoseLabel>, value: impl Into<Any>) -> Option<Any> {
+        let label = label.into();
+        let value = value.into();
+        match self.0.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, prev)) => Some(std::mem::replace(prev, value)),
+            None => {
+                self.0.push((label, value));
+                None
+            }
+        }
+    }
+
+    /// Returns the value of a header parameter.
+    pub fn get(&self, label: impl Into<CoseLabel>) -> Option<&Any> {
+        let label = label.into();
+        self.0.iter().find(|(l, _)| *l == label).map(|(_, v)| v)
+    }
+
+    /// Iterates over the header parameters, in their encoded order.
+    pub fn iter(&self) -> impl Iterator<Item = (&CoseLabel, &Any)> {
+        self.0.iter().map(|(l, v)| (l, v))
+    }
+
+    /// The number of header parameters.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether or not the header has any parameters.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The integer `alg` header parameter.
+    pub fn alg(&self) -> Option<Int> {
+        match self.get(Self::ALG)? {
+            Any::Int(alg) => Some(*alg),
+            _ => None,
+        }
+    }
+
+    /// The `kid` header parameter.
+    pub fn kid(&self) -> Option<&[u8]> {
+        match self.get(Self::KID)? {
+            Any::Bytes(kid) => Some(kid.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// The `IV` header parameter.
+    pub fn iv(&self) -> Option<&[u8]> {
+        match self.get(Self::IV)? {
+            Any::Bytes(iv) => Some(iv.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<Int> for CoseLabel {
+    fn from(label: Int) -> Self {
+        Self::Int(label)
+    }
+}
+
+impl<'a> From<&'a str> for CoseLabel {
+    fn from(label: &'a str) -> Self {
+        Self::Text(label.into())
+    }
+}
+
+impl CoseProtectedHeader {
+    /// Encodes the header, using an empty byte string for an empty header.
+    pub fn new(header: CoseHeader) -> Result<Self, Error> {
+        let bytes = if header.is_empty() {
+            Bytes::new()
+        } else {
+            Bytes::from(DagCose::new().encode(&header)?)
+        };
+        Ok(Self { header, bytes })
+    }
+
+    /// Decodes the header from its encoded bytes.
+    pub fn from_bytes(bytes: Bytes) -> Result<Self, Error> {
+        let header = if bytes.is_empty() {
+            CoseHeader::new()
+        } else {
+            DagCose::new().decode(bytes.as_ref())?
+        };
+        Ok(Self { header, bytes })
+    }
+
+    /// The decoded header.
+    pub fn header(&self) -> &CoseHeader {
+        &self.header
+    }
+
+    /// The encoded header.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_ref()
+    }
+}
+
+impl DagCoseSign1 {
+    /// Signs a payload `Cid`, setting the protected `alg` header parameter to
+    /// the signer's algorithm.
+    pub fn sign<S: CoseSigner + ?Sized>(
+        signer: &S,
+        protected: CoseHeader,
+        unprotected: CoseHeader,
+        payload: Cid,
+        external_aad: &[u8],
+    ) -> Result<Self, Error> {
+        let mut msg = Self {
+            protected: CoseProtectedHeader::new(protected.with(CoseHeader::ALG, signer.alg()))?,
+            unprotected,
+            payload: Link::Cid(payload),
+            signature: Bytes::new(),
+        };
+        let signature = signer.cose_sign(&msg.to_be_signed(external_aad)?)?;
+        msg.signature = Bytes::from(signature);
+        Ok(msg)
+    }
+
+    /// The encoded `Sig_structure` covered by the signature.
+    pub fn to_be_signed(&self, external_aad: &[u8]) -> Result<Vec<u8>, Error> {
+        let sig_structure = CborValue::Array(vec![
+            CborValue::Text("Signature1".into()),
+            CborValue::Bytes(self.protected.as_bytes().to_vec()),
+            CborValue::Bytes(external_aad.to_vec()),
+            CborValue::Bytes(self.payload.cid().to_bytes()),
+        ]);
+        serde_cbor::to_vec(&sig_structure).map_err(Error::encoder)
+    }
+
+    /// Verifies the signature.
+    pub fn verify<V: CoseVerifier + ?Sized>(
+        &self,
+        verifier: &V,
+        external_aad: &[u8],
+    ) -> Result<(), Error> {
+        verifier.cose_verify(
+            self.protected.header(),
+            &self.unprotected,
+            &self.to_be_signed(external_aad)?,
+            self.signature.as_ref(),
+        )
+    }
+}
+
+/// Produces `COSE_Sign1` signatures.
+pub trait CoseSigner {
+    /// The COSE algorithm identifier of the signatures.
+    fn alg(&self) -> Int;
+
+    /// Signs the encoded `Sig_structure`.
+    fn cose_sign(&self, to_be_signed: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// Verifies `COSE_Sign1` signatures.
+///
+/// Implementors can use the headers (e.g. their `kid`) to look up the key.
+pub trait CoseVerifier {
+    /// Verifies the signature over the encoded `Sig_structure`.
+    fn cose_verify(
+        &self,
+        protected: &CoseHeader,
+        unprotected: &CoseHeader,
+        to_be_signed: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Error>;
+}
+
+/// The COSE algorithm identifier for `EdDSA`.
+const EDDSA: Int = -8;
+
+/// The COSE algorithm identifier for `ES256K`.
+const ES256K: Int = -47;
+
+fn check_alg(protected: &CoseHeader, alg: Int) -> Result<(), Error> {
+    match protected.alg() {
+        Some(found) if found == alg => Ok(()),
+        found => Err(Error::InvalidSignature(format!(
+            "expected `alg` {}, found {:?}",
+            alg, found
+        ))),
+    }
+}
+
+impl CoseSigner for ed25519_dalek::SigningKey {
+    fn alg(&self) -> Int {
+        EDDSA
+    }
+
+    fn cose_sign(&self, to_be_signed: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(ed25519_dalek::Signer::sign(self, to_be_signed)
+            .to_bytes()
+            .to_vec())
+    }
+}
+
+impl CoseVerifier for ed25519_dalek::VerifyingKey {
+    fn cose_verify(
+        &self,
+        protected: &CoseHeader,
+        _: &CoseHeader,
+        to_be_signed: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Error> {
+        check_alg(protected, EDDSA)?;
+        ed25519_dalek::Signature::from_slice(signature)
+            .and_then(|sig| ed25519_dalek::Verifier::verify(self, to_be_signed, &sig))
+            .map_err(|err| Error::InvalidSignature(err.to_string()))
+    }
+}
+
+impl CoseSigner for k256::ecdsa::SigningKey {
+    fn alg(&self) -> Int {
+        ES256K
+    }
+
+    fn cose_sign(&self, to_be_signed: &[u8]) -> Result<Vec<u8>, Error> {
+        use k256::ecdsa::{signature::Signer, Signature};
+
+        let sig: Signature = Signer::sign(self, to_be_signed);
+        Ok(sig.to_bytes().to_vec())
+    }
+}
+
+impl CoseVerifier for k256::ecdsa::VerifyingKey {
+    fn cose_verify(
+        &self,
+        protected: &CoseHeader,
+        _: &CoseHeader,
+        to_be_signed: &[u8],
+        signature: &[u8],
+    ) -> Result<(), Error> {
+        use k256::ecdsa::{signature::Verifier, Signature};
+
+        check_alg(protected, ES256K)?;
+        Signature::from_slice(signature)
+            .and_then(|sig| Verifier::verify(self, to_be_signed, &sig))
+            .map_err(|err| Error::InvalidSignature(err.to_string()))
+    }
+}
+
+impl Representation for DagCoseMessage {
+    const NAME: &'static str = "DagCoseMessage";
+    const SCHEMA: &'static str =
+        "type DagCoseMessage union { | DagCoseSign1 list | DagCoseEncrypt0 list } representation kinded";
+    const DATA_MODEL_KIND: Kind = Kind::List;
+    const SCHEMA_KIND: Kind = Kind::Union;
+    const HAS_LINKS: bool = true;
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Sign1(msg) => Representation::name(msg),
+            Self::Encrypt0(msg) => Representation::name(msg),
+        }
+    }
+
+    fn has_links(&self) -> bool {
+        matches!(self, Self::Sign1(_))
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Sign1(msg) => Representation::serialize::<C, S>(msg, serializer),
+            Self::Encrypt0(msg) => Representation::serialize::<C, S>(msg, serializer),
+        }
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(MessageVisitor::<C>(None))
+    }
+}
+
+impl Representation for DagCoseSign1 {
+    const NAME: &'static str = "DagCoseSign1";
+    const SCHEMA: &'static str = "type DagCoseSign1 struct { protected CoseProtectedHeader unprotected CoseHeader payload Bytes signature Bytes } representation tuple";
+    const DATA_MODEL_KIND: Kind = Kind::List;
+    const SCHEMA_KIND: Kind = Kind::Struct;
+    const HAS_LINKS: bool = true;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Tagged::new(Some(DagCose::SIGN1_TAG), &Sign1Elem::<'_, C>(self)).serialize(serializer)
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match deserializer.deserialize_any(MessageVisitor::<C>(Some(DagCose::SIGN1_TAG)))? {
+            DagCoseMessage::Sign1(msg) => Ok(msg),
+            DagCoseMessage::Encrypt0(_) => Err(D::Error::custom("expected a COSE_Sign1")),
+        }
+    }
+}
+
+impl Representation for DagCoseEncrypt0 {
+    const NAME: &'static str = "DagCoseEncrypt0";
+    const SCHEMA: &'static str = "type DagCoseEncrypt0 struct { protected CoseProtectedHeader unprotected CoseHeader ciphertext Bytes } representation tuple";
+    const DATA_MODEL_KIND: Kind = Kind::List;
+    const SCHEMA_KIND: Kind = Kind::Struct;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Tagged::new(Some(DagCose::ENCRYPT0_TAG), &Encrypt0Elem::<'_, C>(self)).serialize(serializer)
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match deserializer.deserialize_any(MessageVisitor::<C>(Some(DagCose::ENCRYPT0_TAG)))? {
+            DagCoseMessage::Encrypt0(msg) => Ok(msg),
+            DagCoseMessage::Sign1(_) => Err(D::Error::custom("expected a COSE_Encrypt0")),
+        }
+    }
+}
+
+impl Representation for CoseHeader {
+    const NAME: &'static str = "CoseHeader";
+    const SCHEMA: &'static str = "type CoseHeader {CoseLabel:Any}";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (label, value) in self.iter() {
+            match label {
+                CoseLabel::Int(label) => map.serialize_key(label)?,
+                CoseLabel::Text(label) => map.serialize_key(label.as_str())?,
+            }
+            map.serialize_value(&EncoderElem::<'_, C, _>(value))?;
+        }
+        map.end()
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct HeaderVisitor<const C: u64>;
+        impl<'de, const C: u64> Visitor<'de> for HeaderVisitor<C> {
+            type Value = CoseHeader;
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "A `{}`", CoseHeader::NAME)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut header = CoseHeader::new();
+                while let Some(label) = map.next_key::<CoseLabel>()? {
+                    if header.get(label.clone()).is_some() {
+                        return Err(A::Error::custom(format!("duplicate label {:?}", label)));
+                    }
+                    let value = map.next_value_seed(DecoderElem::<C, Any>::default())?;
+                    header.0.push((label, value));
+                }
+                Ok(header)
+            }
+        }
+
+        deserializer.deserialize_map(HeaderVisitor::<C>)
+    }
+}
+
+impl Representation for CoseProtectedHeader {
+    const NAME: &'static str = "CoseProtectedHeader";
+    const SCHEMA: &'static str = "type CoseProtectedHeader bytes";
+    const DATA_MODEL_KIND: Kind = Kind::Bytes;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.as_bytes())
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = <Bytes as Representation>::deserialize::<C, _>(deserializer)?;
+        Self::from_bytes(bytes).map_err(D::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for CoseLabel {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct LabelVisitor;
+        impl<'de> Visitor<'de> for LabelVisitor {
+            type Value = CoseLabel;
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "An integer or string COSE label")
+            }
+            fn visit_i64<E: de::Error>(self, label: i64) -> Result<Self::Value, E> {
+                Ok(CoseLabel::Int(label))
+            }
+            fn visit_u64<E: de::Error>(self, label: u64) -> Result<Self::Value, E> {
+                Int::try_from(label)
+                    .map(CoseLabel::Int)
+                    .map_err(|_| E::custom("COSE label out of range"))
+            }
+            fn visit_str<E: de::Error>(self, label: &str) -> Result<Self::Value, E> {
+                Ok(CoseLabel::from(label))
+            }
+        }
+
+        deserializer.deserialize_any(LabelVisitor)
+    }
+}
+
+/// Decodes either kind of message, optionally expecting a specific tag.
+struct MessageVisitor<const C: u64>(Option<u64>);
+impl<'de, const C: u64> Visitor<'de> for MessageVisitor<C> {
+    type Value = DagCoseMessage;
+    #[inline]
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A `{}`", DagCoseMessage::NAME)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match current_cbor_tag() {
+            Some(tag @ (DagCose::SIGN1_TAG | DagCose::ENCRYPT0_TAG))
+                if self.0.unwrap_or(tag) == tag =>
+            {
+                deserializer.deserialize_seq(MessageVisitor::<C>(Some(tag)))
+            }
+            tag => Err(D::Error::custom(format!(
+                "unexpected CBOR tag for a COSE message: {:?}",
+                tag
+            ))),
+        }
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let protected = seq
+            .next_element_seed(DecoderElem::<C, CoseProtectedHeader>::default())?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let unprotected = seq
+            .next_element_seed(DecoderElem::<C, CoseHeader>::default())?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        let bytes = seq
+            .next_element_seed(DecoderElem::<C, Bytes>::default())?
+            .ok_or_else(|| A::Error::invalid_length(2, &self))?;
+        let signature = seq.next_element_seed(DecoderElem::<C, Bytes>::default())?;
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            return Err(A::Error::invalid_length(5, &self));
+        }
+
+        match (self.0, signature) {
+            (None | Some(DagCose::SIGN1_TAG), Some(signature)) => {
+                let cid = Cid::try_from(bytes.as_ref()).map_err(A::Error::custom)?;
+                Ok(DagCoseMessage::Sign1(DagCoseSign1 {
+                    protected,
+                    unprotected,
+                    payload: Link::Cid(cid),
+                    signature,
+                }))
+            }
+            (None | Some(DagCose::ENCRYPT0_TAG), None) => {
+                Ok(DagCoseMessage::Encrypt0(DagCoseEncrypt0 {
+                    protected,
+                    unprotected,
+                    ciphertext: bytes,
+                }))
+            }
+            (_, Some(_)) => Err(A::Error::invalid_length(4, &self)),
+            (_, None) => Err(A::Error::invalid_length(3, &self)),
+        }
+    }
+}
+
+/// Serializes the `COSE_Sign1` array.
+struct Sign1Elem<'a, const C: u64>(&'a DagCoseSign1);
+impl<'a, const C: u64> Serialize for Sign1Elem<'a, C> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeTuple;
+
+        let mut tup = serializer.serialize_tuple(4)?;
+        tup.serialize_element(&EncoderElem::<'_, C, _>(&self.0.protected))?;
+        tup.serialize_element(&EncoderElem::<'_, C, _>(&self.0.unprotected))?;
+        tup.serialize_element(&PayloadElem(self.0.payload.cid()))?;
+        tup.serialize_element(&EncoderElem::<'_, C, _>(&self.0.signature))?;
+        tup.end()
+    }
+}
+
+/// Serializes the `COSE_Encrypt0` array.
+struct Encrypt0Elem<'a, const C: u64>(&'a DagCoseEncrypt0);
+impl<'a, const C: u64> Serialize for Encrypt0Elem<'a, C> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeTuple;
+
+        let mut tup = serializer.serialize_tuple(3)?;
+        tup.serialize_element(&EncoderElem::<'_, C, _>(&self.0.protected))?;
+        tup.serialize_element(&EncoderElem::<'_, C, _>(&self.0.unprotected))?;
+        tup.serialize_element(&EncoderElem::<'_, C, _>(&self.0.ciphertext))?;
+        tup.end()
+    }
+}
+
+macro_rules! impl_serde {
+    ($($ty:ty),*) => {$(
+        impl Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                Representation::serialize::<{ DagCose::CODE }, _>(self, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                <Self as Representation>::deserialize::<{ DagCose::CODE }, _>(deserializer)
+            }
+        }
+    )*};
+}
+
+impl_serde!(
+    DagCoseMessage,
+    DagCoseSign1,
+    DagCoseEncrypt0,
+    CoseHeader,
+    CoseProtectedHeader
+);
+
+impl From<DagCoseMessage> for SelectedNode {
+    fn from(_: DagCoseMessage) -> Self {
+        Self::List
+    }
+}
+
+impl_selector_seed_serde! { @selector_seed_codec_deseed {} {} DagCoseMessage {
+    #[inline]
+    fn deserialize<const C: u64, D>(mut self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let matcher = self.selector.matcher().ok_or_else(|| {
+            D::Error::custom(Error::unsupported_selector::<DagCoseMessage>(self.selector))
+        })?;
+
+        let msg = <DagCoseMessage as Representation>::deserialize::<C, _>(deserializer)?;
+        match self.mode() {
+            SelectionMode::SelectNode => self
+                .select_matched_node(msg.into(), matcher.label.as_deref())
+                .map_err(D::Error::custom),
+            SelectionMode::SelectDag => self
+                .select_matched_dag(msg, matcher.label.as_deref())
+                .map_err(D::Error::custom),
+            SelectionMode::Patch => unreachable!(),
+        }
+    }
+}}
+
+impl_selector_seed_serde! { @selector_seed_select {} {} DagCoseMessage }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> Cid {
+        Cid::new(
+            Version::V1,
+            DagCbor::CODE,
+            Multihash::SHA2_256,
+            &b"\xa1\x65hello\x65world"[..],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sign1() {
+        let ed_key = ed25519_dalek::SigningKey::from_bytes(&[1; 32]);
+        let k256_key = k256::ecdsa::SigningKey::from_bytes(&[2; 32].into()).unwrap();
+        let unprotected = CoseHeader::new().with(CoseHeader::KID, Bytes::copy_from_slice(b"ed"));
+
+        let msg =
+            DagCoseSign1::sign(&ed_key, CoseHeader::new(), unprotected, payload(), b"").unwrap();
+        assert_eq!(msg.protected.header().alg(), Some(-8));
+        assert_eq!(msg.unprotected.kid(), Some(&b"ed"[..]));
+
+        // round-trips byte-identically
+        let mut codec = DagCose::new();
+        let bytes = codec.encode(&msg).unwrap();
+        assert_eq!(bytes[0], 0xd2);
+        let decoded: DagCoseMessage = codec.decode(&bytes).unwrap();
+        assert_eq!(decoded, DagCoseMessage::Sign1(msg.clone()));
+        assert_eq!(codec.encode(&decoded).unwrap(), bytes);
+
+        // untagged messages are also accepted
+        let decoded: DagCoseSign1 = codec.decode(&bytes[1..]).unwrap();
+        assert_eq!(decoded, msg);
+
+        // verifies only with the right key, aad and payload
+        msg.verify(&ed_key.verifying_key(), b"").unwrap();
+        assert!(msg.verify(&ed_key.verifying_key(), b"aad").is_err());
+        assert!(msg.verify(k256_key.verifying_key(), b"").is_err());
+        let mut tampered = msg;
+        tampered.payload = Link::Cid(Cid::default());
+        assert!(tampered.verify(&ed_key.verifying_key(), b"").is_err());
+
+        let msg = DagCoseSign1::sign(
+            &k256_key,
+            CoseHeader::new(),
+            CoseHeader::new(),
+            payload(),
+            b"aad",
+        )
+        .unwrap();
+        msg.verify(k256_key.verifying_key(), b"aad").unwrap();
+        assert!(msg.verify(&ed_key.verifying_key(), b"aad").is_err());
+    }
+
+    #[test]
+    fn test_encrypt0() {
+        let msg = DagCoseEncrypt0 {
+            protected: CoseProtectedHeader::new(CoseHeader::new().with(CoseHeader::ALG, 24))
+                .unwrap(),
+            unprotected: CoseHeader::new()
+                .with(CoseHeader::IV, Bytes::copy_from_slice(&[1; 12]))
+                .with("partner", IpldString::from("acme")),
+            ciphertext: Bytes::copy_from_slice(b"ciphertext"),
+        };
+
+        let mut codec = DagCose::new();
+        let bytes = codec.encode(&msg).unwrap();
+        assert_eq!(bytes[0], 0xd0);
+        let decoded: DagCoseMessage = codec.decode(&bytes).unwrap();
+        assert_eq!(decoded, DagCoseMessage::Encrypt0(msg.clone()));
+        assert_eq!(codec.encode(&decoded).unwrap(), bytes);
+        assert_eq!(msg.protected.as_bytes(), b"\xa1\x01\x18\x18");
+        assert_eq!(msg.unprotected.iv(), Some(&[1; 12][..]));
+
+        assert!(codec.decode::<DagCoseSign1>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut codec = DagCose::new();
+
+        // wrong tag
+        assert!(codec
+            .decode::<DagCoseMessage>(b"\xd8\x2a\x83\x40\xa0\x40")
+            .is_err());
+        // wrong length
+        assert!(codec.decode::<DagCoseMessage>(b"\x82\x40\xa0").is_err());
+        assert!(codec
+            .decode::<DagCoseMessage>(b"\xd0\x84\x40\xa0\x40\x40")
+            .is_err());
+        // non-Cid payload
+        assert!(codec
+            .decode::<DagCoseMessage>(b"\xd2\x84\x40\xa0\x43abc\x40")
+            .is_err());
+        // duplicate header labels
+        assert!(codec
+            .decode::<DagCoseMessage>(b"\x83\x40\xa2\x01\x01\x01\x02\x40")
+            .is_err());
+    }
+}
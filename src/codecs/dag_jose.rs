@@ -0,0 +1,962 @@
+//! IPLD DagJose codec.
+
+use super::dag_cbor::PayloadElem;
+use crate::dev::{macros::impl_selector_seed_serde, *};
+use serde_cbor::{
+    de::{IoRead, SliceRead},
+    ser::IoWrite,
+    Deserializer as CborDeserializer, Serializer as CborSerializer,
+};
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    fmt,
+    io::{Read, Write},
+};
+
+/// The [DagJOSE](https://ipld.io/specs/codecs/dag-jose/spec/) codec, which
+/// encodes [`DagJoseEnvelope`]s (general-serialization JWS and JWE objects) as
+/// DagCBOR maps, with each base64url-encoded member stored as raw bytes.
+///
+/// Envelopes are always written in canonical DagCBOR order, so canonically
+/// encoded blocks re-encode byte-identically.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DagJose;
+
+impl DagJose {
+    /// The multicodec code that identifies this IPLD Codec.
+    pub const CODE: u64 = 0x85;
+
+    #[doc(hidden)]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Serializes links as tagged DagCBOR links.
+    #[inline]
+    pub(crate) fn serialize_link<S: Serializer>(
+        cid: &Cid,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        DagCbor::serialize_link(cid, serializer)
+    }
+
+    /// Deserializes tagged DagCBOR links.
+    #[inline]
+    pub(crate) fn deserialize_link<'de, D, V>(
+        deserializer: D,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: IpldVisitorExt<'de>,
+    {
+        DagCbor::deserialize_link(deserializer, visitor)
+    }
+
+    pub(crate) fn read_with_seed<'de, S, R>(&mut self, seed: S, reader: R) -> Result<(), Error>
+    where
+        S: CodecDeserializeSeed<'de>,
+        R: Read,
+    {
+        let mut de = CborDeserializer::new(IoRead::new(reader));
+        seed.deserialize::<{ Self::CODE }, _>(&mut de)
+            .map_err(Error::decoder)
+    }
+}
+
+impl Codec for DagJose {
+    fn write<T, W>(&mut self, dag: &T, writer: W) -> Result<(), Error>
+    where
+        T: Representation,
+        W: Write,
+    {
+        let mut ser = CborSerializer::new(IoWrite::new(writer));
+        Representation::serialize::<{ Self::CODE }, _>(dag, &mut ser).map_err(Error::encoder)
+    }
+
+    fn decode<'de, T>(&mut self, bytes: &'de [u8]) -> Result<T, Error>
+    where
+        T: Representation,
+    {
+        let mut de = CborDeserializer::new(SliceRead::new(bytes));
+        let dag =
+            Representation::deserialize::<{ Self::CODE }, _>(&mut de).map_err(Error::decoder)?;
+        de.end().map_err(Error::decoder)?;
+        Ok(dag)
+    }
+
+    fn read<T, R>(&mut self, reader: R) -> Result<T, Error>
+    where
+        T: Representation,
+        R: Read,
+    {
+        let mut de = CborDeserializer::new(IoRead::new(reader));
+        let dag =
+            Representation::deserialize::<{ Self::CODE }, _>(&mut de).map_err(Error::decoder)?;
+        de.end().map_err(Error::decoder)?;
+        Ok(dag)
+    }
+}
+
+impl TryFrom<u64> for DagJose {
+    type Error = Error;
+    fn try_from(code: u64) -> Result<Self, Self::Error> {
+        match code {
+            Self::CODE => Ok(Self),
+            _ => Err(Error::UnknownMulticodecCode(code)),
+        }
+    }
+}
+
+/// A DagJose block, either a signed [`DagJoseJws`] or an encrypted
+/// [`DagJoseJwe`].
+///
+/// ```ipldsch
+/// type DagJoseEnvelope union {
+///     | DagJoseJws "payload"
+///     | DagJoseJwe "ciphertext"
+/// } representation inline
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum DagJoseEnvelope {
+    /// A general-serialization JSON Web Signature.
+    Jws(DagJoseJws),
+    /// A general-serialization JSON Web Encryption.
+    Jwe(DagJoseJwe),
+}
+
+/// A general-serialization [JWS](https://www.rfc-editor.org/rfc/rfc7515),
+/// whose payload is the `Cid` of the signed dag.
+///
+/// ```ipldsch
+/// type DagJoseJws struct {
+///     payload Bytes
+///     signatures [DagJoseSignature]
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DagJoseJws {
+    /// The link to the signed dag, encoded as the bytes of its `Cid`.
+    pub payload: Link,
+    /// The signatures over the payload.
+    pub signatures: List<DagJoseSignature>,
+}
+
+/// A signature within a [`DagJoseJws`].
+///
+/// ```ipldsch
+/// type DagJoseSignature struct {
+///     header optional {String:Any}
+///     protected optional Bytes
+///     signature Bytes
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DagJoseSignature {
+    /// The unprotected JOSE header.
+    pub header: Option<Map>,
+    /// The JSON-encoded protected JOSE header.
+    pub protected: Option<Bytes>,
+    /// The signature over the protected header and payload.
+    pub signature: Bytes,
+}
+
+/// A general-serialization [JWE](https://www.rfc-editor.org/rfc/rfc7516).
+///
+/// ```ipldsch
+/// type DagJoseJwe struct {
+///     iv optional Bytes
+///     aad optional Bytes
+///     tag optional Bytes
+///     protected optional Bytes
+///     ciphertext Bytes
+///     recipients optional [DagJoseRecipient]
+///     unprotected optional {String:Any}
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DagJoseJwe {
+    /// The initialization vector.
+    pub iv: Option<Bytes>,
+    /// The additional authenticated data.
+    pub aad: Option<Bytes>,
+    /// The authentication tag.
+    pub tag: Option<Bytes>,
+    /// The JSON-encoded protected JOSE header.
+    pub protected: Option<Bytes>,
+    /// The encrypted content.
+    pub ciphertext: Bytes,
+    /// The per-recipient headers and encrypted keys.
+    pub recipients: Option<List<DagJoseRecipient>>,
+    /// The shared unprotected JOSE header.
+    pub unprotected: Option<Map>,
+}
+
+/// A recipient within a [`DagJoseJwe`].
+///
+/// ```ipldsch
+/// type DagJoseRecipient struct {
+///     header optional {String:Any}
+///     encrypted_key optional Bytes
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DagJoseRecipient {
+    /// The per-recipient unprotected JOSE header.
+    pub header: Option<Map>,
+    /// The encrypted content encryption key.
+    pub encrypted_key: Option<Bytes>,
+}
+
+impl DagJoseJws {
+    /// Verifies every signature of the JWS, resolving each signature's key
+    /// from its `alg` and `kid` header parameters.
+    pub fn verify<R: JoseKeyResolver>(&self, resolver: &R) -> Result<(), Error> {
+        if self.signatures.is_empty() {
+            return Err(Error::InvalidSignature("JWS has no signatures".into()));
+        }
+
+        for signature in &self.signatures {
+            signature.verify(self.payload.cid(), resolver)?;
+        }
+        Ok(())
+    }
+}
+
+impl DagJoseSignature {
+    /// The JWS Signing Input for the given payload, i.e.
+    /// `BASE64URL(protected) || '.' || BASE64URL(payload)`.
+    pub fn signing_input(&self, payload: &Cid) -> Vec<u8> {
+        let protected = self.protected.as_deref().unwrap_or_default();
+        format!(
+            "{}.{}",
+            Multibase::Base64Url.encode(protected),
+            Multibase::Base64Url.encode(payload.to_bytes()),
+        )
+        .into_bytes()
+    }
+
+    /// Verifies the signature over the given payload, resolving the key from
+    /// the `alg` and `kid` header parameters.
+    pub fn verify<R: JoseKeyResolver>(&self, payload: &Cid, resolver: &R) -> Result<(), Error> {
+        let protected: BTreeMap<String, serde_json::Value> = match &self.protected {
+            Some(protected) => serde_json::from_slice(protected).map_err(Error::decoder)?,
+            None => BTreeMap::new(),
+        };
+        let param = |name: &str| -> Option<&str> {
+            protected
+                .get(name)
+                .and_then(serde_json::Value::as_str)
+                .or_else(
+                    || match self.header.as_ref()?.get(&IpldString::from(name))? {
+                        Any::String(s) => Some(s.as_str()),
+                        _ => None,
+                    },
+                )
+        };
+
+        let alg = param("alg")
+            .ok_or_else(|| Error::InvalidSignature("missing `alg` header parameter".into()))?;
+        let kid = param("kid");
+        let key = resolver.resolve(alg, kid).ok_or_else(|| {
+            Error::InvalidSignature(format!("no `{}` key found for kid {:?}", alg, kid))
+        })?;
+        if key.alg() != alg {
+            return Err(Error::InvalidSignature(format!(
+                "resolved a `{}` key for a `{}` signature",
+                key.alg(),
+                alg
+            )));
+        }
+
+        key.verify(&self.signing_input(payload), &self.signature)
+    }
+}
+
+/// A public key capable of verifying JWS signatures.
+#[derive(Clone, Debug)]
+pub enum JoseKey {
+    /// An Ed25519 public key, for the `EdDSA` algorithm.
+    Ed25519(ed25519_dalek::VerifyingKey),
+    /// A secp256k1 public key, for the `ES256K` algorithm.
+    Es256k(k256::ecdsa::VerifyingKey),
+}
+
+impl JoseKey {
+    /// The JWS `alg` header parameter this key verifies.
+    pub const fn alg(&self) -> &'static str {
+        match self {
+            Self::Ed25519(_) => "EdDSA",
+            Self::Es256k(_) => "ES256K",
+        }
+    }
+
+    /// Verifies the signature over the JWS Signing Input.
+    pub fn verify(&self, signing_input: &[u8], signature: &[u8]) -> Result<(), Error> {
+        use ed25519_dalek::Verifier as _;
+
+        match self {
+            Self::Ed25519(key) => ed25519_dalek::Signature::from_slice(signature)
+                .and_then(|sig| key.verify(signing_input, &sig))
+                .map_err(|err| Error::InvalidSignature(err.to_string())),
+            Self::Es256k(key) => k256::ecdsa::Signature::from_slice(signature)
+                .and_then(|sig| key.verify(signing_input, &sig))
+                .map_err(|err| Error::InvalidSignature(err.to_string())),
+        }
+    }
+}
+
+impl From<ed25519_dalek::VerifyingKey> for JoseKey {
+    fn from(key: ed25519_dalek::VerifyingKey) -> Self {
+        Self::Ed25519(key)
+    }
+}
+
+impl From<k256::ecdsa::VerifyingKey> for JoseKey {
+    fn from(key: k256::ecdsa::VerifyingKey) -> Self {
+        Self::Es256k(key)
+    }
+}
+
+/// Resolves the [`JoseKey`] that should verify a signature.
+pub trait JoseKeyResolver {
+    /// Returns the key for the given `alg` and (optional) `kid` header
+    /// parameters, if known.
+    fn resolve(&self, alg: &str, kid: Option<&str>) -> Option<JoseKey>;
+}
+
+/// A [`JoseKeyResolver`] over a local set of keys, identified by `kid`.
+///
+/// Signatures without a `kid` are verified by the only key of their `alg`, if
+/// there is exactly one.
+#[derive(Clone, Debug, Default)]
+pub struct LocalKeys(BTreeMap<String, JoseKey>);
+
+impl LocalKeys {
+    /// Creates an empty set of keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key, identified by the given `kid`.
+    pub fn with_key(mut self, kid: impl Into<String>, key: impl Into<JoseKey>) -> Self {
+        self.insert(kid, key);
+        self
+    }
+
+    /// Adds a key, identified by the given `kid`.
+    pub fn insert(&mut self, kid: impl Into<String>, key: impl Into<JoseKey>) {
+        self.0.insert(kid.into(), key.into());
+    }
+}
+
+impl JoseKeyResolver for LocalKeys {
+    fn resolve(&self, alg: &str, kid: Option<&str>) -> Option<JoseKey> {
+        if let Some(kid) = kid {
+            return self.0.get(kid).filter(|key| key.alg() == alg).cloned();
+        }
+
+        let mut keys = self.0.values().filter(|key| key.alg() == alg);
+        match (keys.next(), keys.next()) {
+            (Some(key), None) => Some(key.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<R: JoseKeyResolver + ?Sized> JoseKeyResolver for &R {
+    fn resolve(&self, alg: &str, kid: Option<&str>) -> Option<JoseKey> {
+        (**self).resolve(alg, kid)
+    }
+}
+
+impl Representation for DagJoseEnvelope {
+    const NAME: &'static str = "DagJoseEnvelope";
+    const SCHEMA: &'static str = "type DagJoseEnvelope union { | DagJoseJws \"payload\" | DagJoseJwe \"ciphertext\" } representation inline";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+    const SCHEMA_KIND: Kind = Kind::Union;
+    const HAS_LINKS: bool = true;
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Jws(jws) => Representation::name(jws),
+            Self::Jwe(jwe) => Representation::name(jwe),
+        }
+    }
+
+    fn has_links(&self) -> bool {
+        matches!(self, Self::Jws(_))
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Jws(jws) => Representation::serialize::<C, S>(jws, serializer),
+            Self::Jwe(jwe) => Representation::serialize::<C, S>(jwe, serializer),
+        }
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(EnvelopeVisitor::<C>)
+    }
+}
+
+impl Representation for DagJoseJws {
+    const NAME: &'static str = "DagJoseJws";
+    const SCHEMA: &'static str =
+        "type DagJoseJws struct { payload Bytes signatures [DagJoseSignature] }";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+    const SCHEMA_KIND: Kind = Kind::Struct;
+    const HAS_LINKS: bool = true;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeStruct;
+
+        let mut st = serializer.serialize_struct(Self::NAME, 2)?;
+        st.serialize_field("payload", &PayloadElem(self.payload.cid()))?;
+        st.serialize_field("signatures", &EncoderElem::<'_, C, _>(&self.signatures))?;
+        st.end()
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match deserializer.deserialize_map(EnvelopeVisitor::<C>)? {
+            DagJoseEnvelope::Jws(jws) => Ok(jws),
+            DagJoseEnvelope::Jwe(_) => Err(D::Error::custom("expected a JWS, found a JWE")),
+        }
+    }
+}
+
+impl Representation for DagJoseSignature {
+    const NAME: &'static str = "DagJoseSignature";
+    const SCHEMA: &'static str = "type DagJoseSignature struct { header optional {String:Any} protected optional Bytes signature Bytes }";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+    const SCHEMA_KIND: Kind = Kind::Struct;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeStruct;
+
+        let len = 1 + self.header.is_some() as usize + self.protected.is_some() as usize;
+        let mut st = serializer.serialize_struct(Self::NAME, len)?;
+        if let Some(header) = &self.header {
+            st.serialize_field("header", &HeaderElem::<'_, C>(header))?;
+        }
+        if let Some(protected) = &self.protected {
+            st.serialize_field("protected", &EncoderElem::<'_, C, _>(protected))?;
+        }
+        st.serialize_field("signature", &EncoderElem::<'_, C, _>(&self.signature))?;
+        st.end()
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SignatureVisitor<const C: u64>;
+        impl<'de, const C: u64> Visitor<'de> for SignatureVisitor<C> {
+            type Value = DagJoseSignature;
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "A `{}`", DagJoseSignature::NAME)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut header = None;
+                let mut protected = None;
+                let mut signature = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "header" if header.is_none() => {
+                            header.replace(map.next_value_seed(HeaderSeed::<C>)?);
+                        }
+                        "protected" if protected.is_none() => {
+                            protected.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        "signature" if signature.is_none() => {
+                            signature.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        key => return Err(A::Error::custom(format!("unexpected key `{}`", key))),
+                    }
+                }
+
+                Ok(DagJoseSignature {
+                    header,
+                    protected,
+                    signature: signature.ok_or_else(|| A::Error::missing_field("signature"))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(SignatureVisitor::<C>)
+    }
+}
+
+impl Representation for DagJoseJwe {
+    const NAME: &'static str = "DagJoseJwe";
+    const SCHEMA: &'static str = "type DagJoseJwe struct { iv optional Bytes aad optional Bytes tag optional Bytes protected optional Bytes ciphertext Bytes recipients optional [DagJoseRecipient] unprotected optional {String:Any} }";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+    const SCHEMA_KIND: Kind = Kind::Struct;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeStruct;
+
+        let len = 1
+            + self.iv.is_some() as usize
+            + self.aad.is_some() as usize
+            + self.tag.is_some() as usize
+            + self.protected.is_some() as usize
+            + self.recipients.is_some() as usize
+            + self.unprotected.is_some() as usize;
+        let mut st = serializer.serialize_struct(Self::NAME, len)?;
+        if let Some(iv) = &self.iv {
+            st.serialize_field("iv", &EncoderElem::<'_, C, _>(iv))?;
+        }
+        if let Some(aad) = &self.aad {
+            st.serialize_field("aad", &EncoderElem::<'_, C, _>(aad))?;
+        }
+        if let Some(tag) = &self.tag {
+            st.serialize_field("tag", &EncoderElem::<'_, C, _>(tag))?;
+        }
+        if let Some(protected) = &self.protected {
+            st.serialize_field("protected", &EncoderElem::<'_, C, _>(protected))?;
+        }
+        st.serialize_field("ciphertext", &EncoderElem::<'_, C, _>(&self.ciphertext))?;
+        if let Some(recipients) = &self.recipients {
+            st.serialize_field("recipients", &EncoderElem::<'_, C, _>(recipients))?;
+        }
+        if let Some(unprotected) = &self.unprotected {
+            st.serialize_field("unprotected", &HeaderElem::<'_, C>(unprotected))?;
+        }
+        st.end()
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match deserializer.deserialize_map(EnvelopeVisitor::<C>)? {
+            DagJoseEnvelope::Jwe(jwe) => Ok(jwe),
+            DagJoseEnvelope::Jws(_) => Err(D::Error::custom("expected a JWE, found a JWS")),
+        }
+    }
+}
+
+impl Representation for DagJoseRecipient {
+    const NAME: &'static str = "DagJoseRecipient";
+    const SCHEMA: &'static str = "type DagJoseRecipient struct { header optional {String:Any} encrypted_key optional Bytes }";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+    const SCHEMA_KIND: Kind = Kind::Struct;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeStruct;
+
+        let len = self.header.is_some() as usize + self.encrypted_key.is_some() as usize;
+        let mut st = serializer.serialize_struct(Self::NAME, len)?;
+        if let Some(header) = &self.header {
+            st.serialize_field("header", &HeaderElem::<'_, C>(header))?;
+        }
+        if let Some(encrypted_key) = &self.encrypted_key {
+            st.serialize_field("encrypted_key", &EncoderElem::<'_, C, _>(encrypted_key))?;
+        }
+        st.end()
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RecipientVisitor<const C: u64>;
+        impl<'de, const C: u64> Visitor<'de> for RecipientVisitor<C> {
+            type Value = DagJoseRecipient;
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "A `{}`", DagJoseRecipient::NAME)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut recipient = DagJoseRecipient::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "header" if recipient.header.is_none() => {
+                            recipient.header = Some(map.next_value_seed(HeaderSeed::<C>)?);
+                        }
+                        "encrypted_key" if recipient.encrypted_key.is_none() => {
+                            recipient.encrypted_key =
+                                Some(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        key => return Err(A::Error::custom(format!("unexpected key `{}`", key))),
+                    }
+                }
+                Ok(recipient)
+            }
+        }
+
+        deserializer.deserialize_map(RecipientVisitor::<C>)
+    }
+}
+
+/// Decodes either kind of envelope, rejecting any members of the other kind.
+struct EnvelopeVisitor<const C: u64>;
+impl<'de, const C: u64> Visitor<'de> for EnvelopeVisitor<C> {
+    type Value = DagJoseEnvelope;
+    #[inline]
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A `{}`", DagJoseEnvelope::NAME)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut payload: Option<Bytes> = None;
+        let mut signatures = None;
+        let mut jwe = DagJoseJwe::default();
+        let mut ciphertext = None;
+        let mut has_jwe_members = false;
+        while let Some(key) = map.next_key::<String>()? {
+            has_jwe_members |= !matches!(key.as_str(), "payload" | "signatures");
+            match key.as_str() {
+                "payload" if payload.is_none() => {
+                    payload.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                }
+                "signatures" if signatures.is_none() => {
+                    signatures.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                }
+                "iv" if jwe.iv.is_none() => {
+                    jwe.iv = Some(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                }
+                "aad" if jwe.aad.is_none() => {
+                    jwe.aad = Some(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                }
+                "tag" if jwe.tag.is_none() => {
+                    jwe.tag = Some(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                }
+                "protected" if jwe.protected.is_none() => {
+                    jwe.protected = Some(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                }
+                "ciphertext" if ciphertext.is_none() => {
+                    ciphertext.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                }
+                "recipients" if jwe.recipients.is_none() => {
+                    jwe.recipients = Some(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                }
+                "unprotected" if jwe.unprotected.is_none() => {
+                    jwe.unprotected = Some(map.next_value_seed(HeaderSeed::<C>)?);
+                }
+                key => return Err(A::Error::custom(format!("unexpected key `{}`", key))),
+            }
+        }
+
+        match (payload, signatures, ciphertext) {
+            (Some(payload), Some(signatures), None) if !has_jwe_members => {
+                let cid = Cid::try_from(payload.as_ref()).map_err(A::Error::custom)?;
+                Ok(DagJoseEnvelope::Jws(DagJoseJws {
+                    payload: Link::Cid(cid),
+                    signatures,
+                }))
+            }
+            (None, None, Some(ciphertext)) => {
+                jwe.ciphertext = ciphertext;
+                Ok(DagJoseEnvelope::Jwe(jwe))
+            }
+            (Some(_), None, _) => Err(A::Error::missing_field("signatures")),
+            (None, Some(_), _) => Err(A::Error::missing_field("payload")),
+            (None, None, None) => Err(A::Error::missing_field("ciphertext")),
+            _ => Err(A::Error::custom("cannot mix JWS and JWE members")),
+        }
+    }
+}
+
+/// Serializes a JOSE header map with its keys in canonical DagCBOR order.
+struct HeaderElem<'a, const C: u64>(&'a Map);
+impl<'a, const C: u64> Serialize for HeaderElem<'a, C> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeMap;
+
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| (a.len(), a.as_str()).cmp(&(b.len(), b.as_str())));
+
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+        for (key, val) in entries {
+            map.serialize_entry(key.as_str(), &EncoderElem::<'_, C, _>(val))?;
+        }
+        map.end()
+    }
+}
+
+/// Deserializes a JOSE header map.
+struct HeaderSeed<const C: u64>;
+impl<'de, const C: u64> DeserializeSeed<'de> for HeaderSeed<C> {
+    type Value = Map;
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+impl<'de, const C: u64> Visitor<'de> for HeaderSeed<C> {
+    type Value = Map;
+    #[inline]
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A JOSE header map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut header = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let val = map.next_value_seed(DecoderElem::<C, Any>::default())?;
+            if header.insert(IpldString::from(key.as_str()), val).is_some() {
+                return Err(A::Error::custom(format!("duplicate key `{}`", key)));
+            }
+        }
+        Ok(header)
+    }
+}
+
+macro_rules! impl_serde {
+    ($($ty:ty),*) => {$(
+        impl Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                Representation::serialize::<{ DagJose::CODE }, _>(self, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                <Self as Representation>::deserialize::<{ DagJose::CODE }, _>(deserializer)
+            }
+        }
+    )*};
+}
+
+impl_serde!(
+    DagJoseEnvelope,
+    DagJoseJws,
+    DagJoseSignature,
+    DagJoseJwe,
+    DagJoseRecipient
+);
+
+impl From<DagJoseEnvelope> for SelectedNode {
+    fn from(_: DagJoseEnvelope) -> Self {
+        Self::Map
+    }
+}
+
+impl_selector_seed_serde! { @selector_seed_codec_deseed {} {} DagJoseEnvelope {
+    #[inline]
+    fn deserialize<const C: u64, D>(mut self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let matcher = self.selector.matcher().ok_or_else(|| {
+            D::Error::custom(Error::unsupported_selector::<DagJoseEnvelope>(self.selector))
+        })?;
+
+        let envelope = <DagJoseEnvelope as Representation>::deserialize::<C, _>(deserializer)?;
+        match self.mode() {
+            SelectionMode::SelectNode => self
+                .select_matched_node(envelope.into(), matcher.label.as_deref())
+                .map_err(D::Error::custom),
+            SelectionMode::SelectDag => self
+                .select_matched_dag(envelope, matcher.label.as_deref())
+                .map_err(D::Error::custom),
+            SelectionMode::Patch => unreachable!(),
+        }
+    }
+}}
+
+impl_selector_seed_serde! { @selector_seed_select {} {} DagJoseEnvelope }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer as _;
+
+    fn payload() -> Cid {
+        Cid::new(
+            Version::V1,
+            DagCbor::CODE,
+            Multihash::SHA2_256,
+            &b"\xa1\x65hello\x65world"[..],
+        )
+        .unwrap()
+    }
+
+    fn sign(payload: &Cid, protected: &str, signer: impl Fn(&[u8]) -> Vec<u8>) -> DagJoseSignature {
+        let mut signature = DagJoseSignature {
+            header: None,
+            protected: Some(Bytes::copy_from_slice(protected.as_bytes())),
+            signature: Bytes::new(),
+        };
+        let sig = signer(&signature.signing_input(payload));
+        signature.signature = Bytes::from(sig);
+        signature
+    }
+
+    #[test]
+    fn test_jws() {
+        let ed_key = ed25519_dalek::SigningKey::from_bytes(&[1; 32]);
+        let k256_key = k256::ecdsa::SigningKey::from_bytes(&[2; 32].into()).unwrap();
+
+        let payload = payload();
+        let jws = DagJoseJws {
+            payload: Link::Cid(payload),
+            signatures: vec![
+                sign(&payload, r#"{"alg":"EdDSA","kid":"ed"}"#, |msg| {
+                    ed_key.sign(msg).to_bytes().to_vec()
+                }),
+                sign(&payload, r#"{"alg":"ES256K"}"#, |msg| {
+                    let sig: k256::ecdsa::Signature = k256_key.sign(msg);
+                    sig.to_bytes().to_vec()
+                }),
+            ],
+        };
+
+        // round-trips byte-identically
+        let mut codec = DagJose::new();
+        let bytes = codec.encode(&jws).unwrap();
+        let envelope: DagJoseEnvelope = codec.decode(&bytes).unwrap();
+        assert_eq!(envelope, DagJoseEnvelope::Jws(jws.clone()));
+        assert_eq!(codec.encode(&envelope).unwrap(), bytes);
+        assert_eq!(&bytes[..9], b"\xa2\x67payload");
+
+        // verifies with local keys
+        let keys = LocalKeys::new()
+            .with_key("ed", ed_key.verifying_key())
+            .with_key("k256", *k256_key.verifying_key());
+        jws.verify(&keys).unwrap();
+
+        // fails to verify with missing keys or tampered payloads
+        let keys = LocalKeys::new().with_key("ed", ed_key.verifying_key());
+        assert!(jws.verify(&keys).is_err());
+
+        let keys = LocalKeys::new()
+            .with_key(
+                "ed",
+                ed25519_dalek::SigningKey::from_bytes(&[3; 32]).verifying_key(),
+            )
+            .with_key("k256", *k256_key.verifying_key());
+        assert!(jws.verify(&keys).is_err());
+
+        let mut tampered = jws;
+        tampered.payload = Link::Cid(Cid::default());
+        let keys = LocalKeys::new()
+            .with_key("ed", ed_key.verifying_key())
+            .with_key("k256", *k256_key.verifying_key());
+        assert!(tampered.verify(&keys).is_err());
+    }
+
+    #[test]
+    fn test_jwe() {
+        let mut header = Map::new();
+        header.insert(IpldString::from("kid"), Any::String("did:key:z".into()));
+        header.insert(
+            IpldString::from("alg"),
+            Any::String("ECDH-ES+XC20PKW".into()),
+        );
+        header.insert(
+            IpldString::from("epk"),
+            Any::Bytes(Bytes::copy_from_slice(b"k")),
+        );
+
+        let jwe = DagJoseJwe {
+            iv: Some(Bytes::copy_from_slice(&[1; 24])),
+            tag: Some(Bytes::copy_from_slice(&[2; 16])),
+            protected: Some(Bytes::copy_from_slice(br#"{"enc":"XC20P"}"#)),
+            ciphertext: Bytes::copy_from_slice(b"ciphertext"),
+            recipients: Some(vec![DagJoseRecipient {
+                header: Some(header),
+                encrypted_key: Some(Bytes::copy_from_slice(&[3; 32])),
+            }]),
+            ..Default::default()
+        };
+
+        let mut codec = DagJose::new();
+        let bytes = codec.encode(&jwe).unwrap();
+        let envelope: DagJoseEnvelope = codec.decode(&bytes).unwrap();
+        assert_eq!(envelope, DagJoseEnvelope::Jwe(jwe.clone()));
+        assert_eq!(codec.encode(&envelope).unwrap(), bytes);
+        assert!(codec.decode::<DagJoseJws>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut codec = DagJose::new();
+
+        // not a map
+        assert!(codec.decode::<DagJoseEnvelope>(b"\x80").is_err());
+        // payload without signatures
+        let mut bytes = b"\xa1\x67payload\x58\x24".to_vec();
+        bytes.extend(payload().to_bytes());
+        assert!(codec.decode::<DagJoseEnvelope>(&bytes).is_err());
+        // unknown members
+        assert!(codec
+            .decode::<DagJoseEnvelope>(b"\xa2\x6aciphertext\x40\x63foo\x40")
+            .is_err());
+        // mixed JWS and JWE members
+        assert!(codec
+            .decode::<DagJoseEnvelope>(b"\xa2\x6aciphertext\x40\x6asignatures\x80")
+            .is_err());
+    }
+}
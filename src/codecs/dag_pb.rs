@@ -1,59 +1,65 @@
-use crate::dev::*;
-use std::convert::TryFrom;
+//! IPLD DagPb codec.
 
+use crate::dev::{macros::impl_selector_seed_serde, *};
+use serde::de::value::Error as ValueError;
+use std::{
+    convert::TryFrom,
+    fmt,
+    io::{Read, Write},
+};
+
+/// The [DagPB](https://ipld.io/specs/codecs/dag-pb/spec/) codec, which
+/// encodes [`DagPbNode`]s as `PBNode` protobuf messages.
 ///
-#[derive(Debug)]
+/// Unlike the other codecs, DagPb can only represent a single fixed schema, so
+/// any encoded type must have the same shape as a [`DagPbNode`].
+#[derive(Clone, Copy, Debug, Default)]
 pub struct DagPb;
 
-// schema! {
-//     #[ipld_attr(internal)]
-//     #[derive(Clone, Debug)]
-//     pub type DagPbNode struct {
-//         Links [DabPbLink],
-//         Data optional Bytes
-//     }
-// }
-
-schema! {
-    #[ipld_attr(internal)]
-    #[derive(Clone, Debug)]
-    pub type DagPbLink struct {
-        Hash Link,
-        Name optional String,
-        Tsize optional Int,
-    };
-}
-
-// pub struct DagPbNode {
-//     data: &'a [u8],
-//     links: Vec<DagPbLink>,
-//     serializedSize: usize,
-//     size: usize,
-// }
-
-// pub struct DagPbLink {
-//     name: String,
-//     size: usize,
-//     cid: Cid,
-// }
-
 impl DagPb {
+    /// The multicodec code that identifies this IPLD Codec.
     pub const CODE: u64 = 0x70;
-}
 
-impl Into<u64> for DagPb {
-    fn into(self) -> u64 {
-        Self::CODE
+    #[doc(hidden)]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
     }
-}
 
-impl TryFrom<u64> for DagPb {
-    type Error = Error;
-    fn try_from(code: u64) -> Result<Self, Self::Error> {
-        match code {
-            Self::CODE => Ok(Self),
-            _ => Err(Error::UnknownMulticodec(code)),
-        }
+    /// Serializes links as the raw bytes of the `Cid`.
+    #[inline]
+    pub(crate) fn serialize_link<S: Serializer>(
+        cid: &Cid,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&cid.to_bytes())
+    }
+
+    /// Deserializes the raw bytes of a `Cid` as a link.
+    #[inline]
+    pub(crate) fn deserialize_link<'de, D, V>(
+        deserializer: D,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: IpldVisitorExt<'de>,
+    {
+        deserializer.deserialize_bytes(LinkVisitor(visitor))
+    }
+
+    pub(crate) fn read_with_seed<'de, S, R>(&mut self, seed: S, mut reader: R) -> Result<(), Error>
+    where
+        S: CodecDeserializeSeed<'de>,
+        R: Read,
+    {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|err| Error::Decoder(err.into()))?;
+        let node = pb::decode_node(&bytes)?;
+        seed.deserialize::<{ Self::CODE }, _>(value::Value::from(node))
+            .map_err(Error::decoder)
     }
 }
 
@@ -63,62 +69,1045 @@ impl Codec for DagPb {
         T: Representation,
         W: Write,
     {
-        unimplemented!()
+        let value = Representation::serialize::<{ Self::CODE }, _>(dag, value::ValueSerializer)
+            .map_err(Error::encoder)?;
+        let node = DagPbNode::try_from(value).map_err(Error::encoder)?;
+        pb::encode_node(&node, writer)
     }
 
     fn decode<'de, T>(&mut self, bytes: &'de [u8]) -> Result<T, Error>
     where
         T: Representation,
     {
-        unimplemented!()
+        let node = pb::decode_node(bytes)?;
+        Representation::deserialize::<{ Self::CODE }, _>(value::Value::from(node))
+            .map_err(Error::decoder)
     }
 
-    fn read<T, R>(&mut self, reader: R) -> Result<T, Error>
+    fn read<T, R>(&mut self, mut reader: R) -> Result<T, Error>
     where
         T: Representation,
         R: Read,
     {
-        unimplemented!()
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|err| Error::Decoder(err.into()))?;
+        self.decode(&bytes)
     }
+}
 
-    /// Given a `Read`, deserialize a dag.
-    fn read_with_seed<'de, S, R>(
-        &mut self,
-        seed: S,
-        reader: R,
-    ) -> Result<<S as DeserializeSeed<'de>>::Value, Error>
+impl TryFrom<u64> for DagPb {
+    type Error = Error;
+    fn try_from(code: u64) -> Result<Self, Self::Error> {
+        match code {
+            Self::CODE => Ok(Self),
+            _ => Err(Error::UnknownMulticodecCode(code)),
+        }
+    }
+}
+
+struct LinkVisitor<V>(V);
+impl<'de, V: IpldVisitorExt<'de>> Visitor<'de> for LinkVisitor<V> {
+    type Value = V::Value;
+    #[inline]
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the bytes of a Cid")
+    }
+    #[inline]
+    fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Self::Value, E>
     where
-        S: DeserializeSeed<'de>,
-        R: Read,
+        E: de::Error,
+    {
+        self.0.visit_link_bytes(bytes)
+    }
+    #[inline]
+    fn visit_borrowed_bytes<E>(self, bytes: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_link_borrowed_bytes(bytes)
+    }
+    #[inline]
+    fn visit_byte_buf<E>(self, bytes: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_link_bytes(&bytes)
+    }
+}
+
+/// A DagPb `PBNode`, the only type representable by the [`DagPb`] codec.
+///
+/// ```ipldsch
+/// type PBNode struct {
+///     Links [PBLink]
+///     Data optional Bytes
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DagPbNode {
+    /// The node's links, sorted by [`DagPbLink::name`] when encoded.
+    pub links: List<DagPbLink>,
+    /// The node's opaque payload.
+    pub data: Option<Bytes>,
+}
+
+/// A link within a [`DagPbNode`].
+///
+/// ```ipldsch
+/// type PBLink struct {
+///     Hash Link
+///     Name optional String
+///     Tsize optional Int
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DagPbLink {
+    /// The `Cid` of the linked block.
+    pub hash: Cid,
+    /// The name of the link.
+    pub name: Option<IpldString>,
+    /// The cumulative size of the linked dag.
+    pub tsize: Option<u64>,
+}
+
+impl DagPbLink {
+    /// Creates a link to the given `Cid`, without a name or size.
+    pub fn new(hash: Cid) -> Self {
+        Self {
+            hash,
+            name: None,
+            tsize: None,
+        }
+    }
+
+    fn name_bytes(&self) -> &[u8] {
+        self.name.as_ref().map(|n| n.as_bytes()).unwrap_or_default()
+    }
+}
+
+impl Representation for DagPbNode {
+    const NAME: &'static str = "PBNode";
+    const SCHEMA: &'static str = "type PBNode struct { Links [PBLink] Data optional Bytes }";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+    const SCHEMA_KIND: Kind = Kind::Struct;
+    const HAS_LINKS: bool = true;
+
+    fn has_links(&self) -> bool {
+        !self.links.is_empty()
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeStruct;
+
+        let len = 1 + self.data.is_some() as usize;
+        let mut st = serializer.serialize_struct(Self::NAME, len)?;
+        if let Some(data) = &self.data {
+            st.serialize_field("Data", &EncoderElem::<'_, C, _>(data))?;
+        }
+        st.serialize_field("Links", &EncoderElem::<'_, C, _>(&self.links))?;
+        st.end()
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NodeVisitor<const C: u64>;
+        impl<'de, const C: u64> Visitor<'de> for NodeVisitor<C> {
+            type Value = DagPbNode;
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "A `{}`", DagPbNode::NAME)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut links = None;
+                let mut data = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "Links" if links.is_none() => {
+                            links.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        "Data" if data.is_none() => {
+                            data.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        key => return Err(A::Error::custom(format!("unexpected key `{}`", key))),
+                    }
+                }
+
+                Ok(DagPbNode {
+                    links: links.ok_or_else(|| A::Error::missing_field("Links"))?,
+                    data,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(NodeVisitor::<C>)
+    }
+}
+
+impl Representation for DagPbLink {
+    const NAME: &'static str = "PBLink";
+    const SCHEMA: &'static str =
+        "type PBLink struct { Hash Link Name optional String Tsize optional Int }";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+    const SCHEMA_KIND: Kind = Kind::Struct;
+    const HAS_LINKS: bool = true;
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use ser::SerializeStruct;
+
+        let len = 1 + self.name.is_some() as usize + self.tsize.is_some() as usize;
+        let mut st = serializer.serialize_struct(Self::NAME, len)?;
+        st.serialize_field("Hash", &EncoderElem::<'_, C, _>(&self.hash))?;
+        if let Some(name) = &self.name {
+            st.serialize_field("Name", &EncoderElem::<'_, C, _>(name))?;
+        }
+        if let Some(tsize) = &self.tsize {
+            st.serialize_field("Tsize", &EncoderElem::<'_, C, _>(tsize))?;
+        }
+        st.end()
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct LinkVisitor<const C: u64>;
+        impl<'de, const C: u64> Visitor<'de> for LinkVisitor<C> {
+            type Value = DagPbLink;
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "A `{}`", DagPbLink::NAME)
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut hash = None;
+                let mut name = None;
+                let mut tsize = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "Hash" if hash.is_none() => {
+                            hash.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        "Name" if name.is_none() => {
+                            name.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        "Tsize" if tsize.is_none() => {
+                            tsize.replace(map.next_value_seed(DecoderElem::<C, _>::default())?);
+                        }
+                        key => return Err(A::Error::custom(format!("unexpected key `{}`", key))),
+                    }
+                }
+
+                Ok(DagPbLink {
+                    hash: hash.ok_or_else(|| A::Error::missing_field("Hash"))?,
+                    name,
+                    tsize,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(LinkVisitor::<C>)
+    }
+}
+
+impl Serialize for DagPbNode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Representation::serialize::<{ DagPb::CODE }, _>(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DagPbNode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <Self as Representation>::deserialize::<{ DagPb::CODE }, _>(deserializer)
+    }
+}
+
+impl Serialize for DagPbLink {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Representation::serialize::<{ DagPb::CODE }, _>(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DagPbLink {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
     {
-        unimplemented!()
-    }
-}
-
-// impl<'a, W: CborWrite> Encoder for &'a mut CborSerializer<W> {
-//     #[inline]
-//     fn serialize_link(self, cid: &Cid) -> Result<<Self as Serializer>::Ok, CborError> {
-//         let vec: Vec<u8> = cid.to_bytes();
-//         let bytes: &[u8] = vec.as_ref();
-//         Tagged::new(Some(CBOR_LINK_TAG), bytes).serialize(self)
-//     }
-// }
-
-// impl<'de, 'a, R: CborRead<'de>> Decoder<'de> for &'a mut CborDeserializer<R> {
-//     #[inline]
-//     fn deserialize_link<V>(self, visitor: V) -> Result<V::Value, CborError>
-//     where
-//         V: IpldVisitorExt<'de>,
-//     {
-//         match current_cbor_tag() {
-//             Some(CBOR_LINK_TAG) => {
-//                 let bytes = <&[u8]>::deserialize(self)?;
-//                 let cid = ToCid::to_cid(bytes)
-//                     .or::<CborError>(Err(de::Error::custom("expected a CID")))?;
-//                 visitor.visit_link(cid)
-//             }
-//             Some(_tag) => Err(de::Error::custom("unexpected CBOR tag")),
-//             _ => Err(de::Error::custom("expected a CID")),
-//         }
-//     }
-// }
+        <Self as Representation>::deserialize::<{ DagPb::CODE }, _>(deserializer)
+    }
+}
+
+impl From<DagPbNode> for SelectedNode {
+    fn from(_: DagPbNode) -> Self {
+        Self::Map
+    }
+}
+
+impl_selector_seed_serde! { @selector_seed_codec_deseed {} {} DagPbNode {
+    #[inline]
+    fn deserialize<const C: u64, D>(mut self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let matcher = self.selector.matcher().ok_or_else(|| {
+            D::Error::custom(Error::unsupported_selector::<DagPbNode>(self.selector))
+        })?;
+
+        let node = <DagPbNode as Representation>::deserialize::<C, _>(deserializer)?;
+        match self.mode() {
+            SelectionMode::SelectNode => self
+                .select_matched_node(node.into(), matcher.label.as_deref())
+                .map_err(D::Error::custom),
+            SelectionMode::SelectDag => self
+                .select_matched_dag(node, matcher.label.as_deref())
+                .map_err(D::Error::custom),
+            SelectionMode::Patch => unreachable!(),
+        }
+    }
+}}
+
+impl_selector_seed_serde! { @selector_seed_select {} {} DagPbNode }
+
+impl TryFrom<value::Value> for DagPbNode {
+    type Error = ValueError;
+    fn try_from(value: value::Value) -> Result<Self, Self::Error> {
+        let mut node = Self::default();
+        let mut has_links = false;
+        for (key, val) in value.into_map("PBNode")? {
+            match (key.as_str(), val) {
+                (_, value::Value::Null) => {}
+                ("Links", value::Value::List(links)) => {
+                    has_links = true;
+                    node.links = links
+                        .into_iter()
+                        .map(DagPbLink::try_from)
+                        .collect::<Result<_, _>>()?;
+                }
+                ("Data", value::Value::Bytes(data)) => node.data = Some(data.into()),
+                (key, _) => {
+                    return Err(ser::Error::custom(format!(
+                        "invalid PBNode field `{}`",
+                        key
+                    )))
+                }
+            }
+        }
+
+        if !has_links {
+            return Err(ser::Error::custom("PBNode is missing `Links`"));
+        }
+        Ok(node)
+    }
+}
+
+impl TryFrom<value::Value> for DagPbLink {
+    type Error = ValueError;
+    fn try_from(value: value::Value) -> Result<Self, Self::Error> {
+        let (mut hash, mut name, mut tsize) = (None, None, None);
+        for (key, val) in value.into_map("PBLink")? {
+            match (key.as_str(), val) {
+                (_, value::Value::Null) => {}
+                ("Hash", value::Value::Bytes(bytes)) => {
+                    hash.replace(Cid::try_from(bytes.as_slice()).map_err(ser::Error::custom)?);
+                }
+                ("Name", value::Value::String(s)) => {
+                    name.replace(IpldString::from(s));
+                }
+                ("Tsize", value::Value::Int(n)) => {
+                    tsize.replace(n);
+                }
+                (key, _) => {
+                    return Err(ser::Error::custom(format!(
+                        "invalid PBLink field `{}`",
+                        key
+                    )))
+                }
+            }
+        }
+
+        Ok(Self {
+            hash: hash.ok_or_else(|| ser::Error::custom("PBLink is missing `Hash`"))?,
+            name,
+            tsize,
+        })
+    }
+}
+
+/// The protobuf wire format of `PBNode` and `PBLink`.
+///
+/// ```protobuf
+/// message PBLink {
+///   optional bytes Hash = 1;
+///   optional string Name = 2;
+///   optional uint64 Tsize = 3;
+/// }
+///
+/// message PBNode {
+///   repeated PBLink Links = 2;
+///   optional bytes Data = 1;
+/// }
+/// ```
+pub(crate) mod pb {
+    use super::*;
+
+    pub(crate) const WIRE_VARINT: u64 = 0;
+    pub(crate) const WIRE_LEN: u64 = 2;
+
+    const NODE_DATA: u64 = 1;
+    const NODE_LINKS: u64 = 2;
+    const LINK_HASH: u64 = 1;
+    const LINK_NAME: u64 = 2;
+    const LINK_TSIZE: u64 = 3;
+
+    fn err(msg: &str) -> Error {
+        Error::decoder(<ValueError as de::Error>::custom(format!(
+            "invalid DagPb block: {}",
+            msg
+        )))
+    }
+
+    /// Encodes the node, with its `Links` (sorted by name) preceding its
+    /// `Data`, as required by the spec.
+    pub(super) fn encode_node<W: Write>(node: &DagPbNode, mut writer: W) -> Result<(), Error> {
+        let mut links: Vec<&DagPbLink> = node.links.iter().collect();
+        links.sort_by(|a, b| a.name_bytes().cmp(b.name_bytes()));
+
+        let mut buf = Vec::new();
+        let mut link_buf = Vec::new();
+        for link in links {
+            link_buf.clear();
+            write_bytes_field(&mut link_buf, LINK_HASH, &link.hash.to_bytes());
+            if let Some(name) = &link.name {
+                write_bytes_field(&mut link_buf, LINK_NAME, name.as_bytes());
+            }
+            if let Some(tsize) = link.tsize {
+                write_varint(&mut link_buf, LINK_TSIZE << 3 | WIRE_VARINT);
+                write_varint(&mut link_buf, tsize);
+            }
+            write_bytes_field(&mut buf, NODE_LINKS, &link_buf);
+        }
+        if let Some(data) = &node.data {
+            write_bytes_field(&mut buf, NODE_DATA, data.as_ref());
+        }
+
+        writer
+            .write_all(&buf)
+            .map_err(|err| Error::Encoder(err.into()))
+    }
+
+    /// Strictly decodes a node, rejecting unknown fields, unexpected wire
+    /// types, and any `Links` following the `Data`.
+    pub(super) fn decode_node(mut bytes: &[u8]) -> Result<DagPbNode, Error> {
+        let mut node = DagPbNode::default();
+        while !bytes.is_empty() {
+            let (field, wire) = read_key(&mut bytes)?;
+            if wire != WIRE_LEN {
+                return Err(err("unexpected wire type for PBNode field"));
+            }
+
+            let chunk = read_len_delimited(&mut bytes)?;
+            match field {
+                NODE_LINKS if node.data.is_none() => node.links.push(decode_link(chunk)?),
+                NODE_LINKS => return Err(err("`Links` must precede `Data`")),
+                NODE_DATA if node.data.is_none() => node.data = Some(Bytes::copy_from_slice(chunk)),
+                NODE_DATA => return Err(err("duplicate `Data` field")),
+                _ => return Err(err("unknown PBNode field")),
+            }
+        }
+
+        Ok(node)
+    }
+
+    fn decode_link(mut bytes: &[u8]) -> Result<DagPbLink, Error> {
+        let (mut hash, mut name, mut tsize) = (None, None, None);
+        let mut last_field = 0;
+        while !bytes.is_empty() {
+            let (field, wire) = read_key(&mut bytes)?;
+            if field <= last_field {
+                return Err(err("PBLink fields are duplicated or out of order"));
+            }
+            last_field = field;
+
+            match (field, wire) {
+                (LINK_HASH, WIRE_LEN) => {
+                    let chunk = read_len_delimited(&mut bytes)?;
+                    hash.replace(Cid::try_from(chunk)?);
+                }
+                (LINK_NAME, WIRE_LEN) => {
+                    let chunk = read_len_delimited(&mut bytes)?;
+                    let s = std::str::from_utf8(chunk).map_err(|_| err("`Name` is not UTF-8"))?;
+                    name.replace(IpldString::from(s));
+                }
+                (LINK_TSIZE, WIRE_VARINT) => {
+                    tsize.replace(read_varint(&mut bytes)?);
+                }
+                (LINK_HASH | LINK_NAME | LINK_TSIZE, _) => {
+                    return Err(err("unexpected wire type for PBLink field"))
+                }
+                _ => return Err(err("unknown PBLink field")),
+            }
+        }
+
+        Ok(DagPbLink {
+            hash: hash.ok_or_else(|| err("PBLink is missing `Hash`"))?,
+            name,
+            tsize,
+        })
+    }
+
+    pub(crate) fn read_key(bytes: &mut &[u8]) -> Result<(u64, u64), Error> {
+        let key = read_varint(bytes)?;
+        Ok((key >> 3, key & 0x7))
+    }
+
+    pub(crate) fn read_len_delimited<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+        let len = read_varint(bytes)?;
+        if len > bytes.len() as u64 {
+            return Err(err("length-delimited field exceeds block length"));
+        }
+
+        let (chunk, rest) = bytes.split_at(len as usize);
+        *bytes = rest;
+        Ok(chunk)
+    }
+
+    pub(crate) fn read_varint(bytes: &mut &[u8]) -> Result<u64, Error> {
+        let mut n = 0u64;
+        for (i, byte) in bytes.iter().enumerate().take(10) {
+            if i == 9 && *byte > 1 {
+                break;
+            }
+
+            n |= ((byte & 0x7f) as u64) << (7 * i);
+            if byte & 0x80 == 0 {
+                if i > 0 && *byte == 0 {
+                    return Err(err("varint is not minimally encoded"));
+                }
+                *bytes = &bytes[i + 1..];
+                return Ok(n);
+            }
+        }
+
+        Err(err("invalid varint"))
+    }
+
+    fn write_bytes_field(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+        write_varint(buf, field << 3 | WIRE_LEN);
+        write_varint(buf, bytes.len() as u64);
+        buf.extend_from_slice(bytes);
+    }
+
+    fn write_varint(buf: &mut Vec<u8>, mut n: u64) {
+        while n >= 0x80 {
+            buf.push((n as u8) | 0x80);
+            n >>= 7;
+        }
+        buf.push(n as u8);
+    }
+}
+
+/// An intermediate, DagPb-shaped view of the data model, used to bridge
+/// arbitrary [`Representation`]s to and from [`DagPbNode`]s.
+mod value {
+    use super::*;
+    use serde::de::value::{MapDeserializer, SeqDeserializer};
+
+    #[derive(Debug)]
+    pub(super) enum Value {
+        Null,
+        Int(u64),
+        String(String),
+        Bytes(Vec<u8>),
+        List(Vec<Value>),
+        Map(Vec<(String, Value)>),
+    }
+
+    impl Value {
+        pub(super) fn into_map(self, name: &str) -> Result<Vec<(String, Value)>, ValueError> {
+            match self {
+                Self::Map(entries) => Ok(entries),
+                _ => Err(ser::Error::custom(format!("expected a {} map", name))),
+            }
+        }
+    }
+
+    impl From<DagPbNode> for Value {
+        fn from(node: DagPbNode) -> Self {
+            let links = node.links.into_iter().map(Value::from).collect();
+            let mut entries = vec![];
+            if let Some(data) = node.data {
+                entries.push(("Data".into(), Self::Bytes(data.to_vec())));
+            }
+            entries.push(("Links".into(), Self::List(links)));
+            Self::Map(entries)
+        }
+    }
+
+    impl From<DagPbLink> for Value {
+        fn from(link: DagPbLink) -> Self {
+            let mut entries = vec![("Hash".into(), Self::Bytes(link.hash.to_bytes()))];
+            if let Some(name) = link.name {
+                entries.push(("Name".into(), Self::String(name.into())));
+            }
+            if let Some(tsize) = link.tsize {
+                entries.push(("Tsize".into(), Self::Int(tsize)));
+            }
+            Self::Map(entries)
+        }
+    }
+
+    impl<'de> Deserializer<'de> for Value {
+        type Error = ValueError;
+
+        fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            match self {
+                Self::Null => visitor.visit_unit(),
+                Self::Int(n) => visitor.visit_u64(n),
+                Self::String(s) => visitor.visit_string(s),
+                Self::Bytes(b) => visitor.visit_byte_buf(b),
+                Self::List(list) => {
+                    let mut seq = SeqDeserializer::new(list.into_iter());
+                    let v = visitor.visit_seq(&mut seq)?;
+                    seq.end()?;
+                    Ok(v)
+                }
+                Self::Map(entries) => {
+                    let mut map = MapDeserializer::new(entries.into_iter());
+                    let v = visitor.visit_map(&mut map)?;
+                    map.end()?;
+                    Ok(v)
+                }
+            }
+        }
+
+        fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            match self {
+                Self::Null => visitor.visit_none(),
+                value => visitor.visit_some(value),
+            }
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    impl<'de> de::IntoDeserializer<'de, ValueError> for Value {
+        type Deserializer = Self;
+        fn into_deserializer(self) -> Self::Deserializer {
+            self
+        }
+    }
+
+    /// Serializes a dag into a [`Value`], rejecting any kinds that DagPb cannot
+    /// represent.
+    pub(super) struct ValueSerializer;
+
+    fn unsupported<T>(kind: &str) -> Result<T, ValueError> {
+        Err(ser::Error::custom(format!(
+            "DagPb cannot represent the {} kind",
+            kind
+        )))
+    }
+
+    impl Serializer for ValueSerializer {
+        type Ok = Value;
+        type Error = ValueError;
+        type SerializeSeq = SerializeList;
+        type SerializeTuple = SerializeList;
+        type SerializeTupleStruct = SerializeList;
+        type SerializeTupleVariant = ser::Impossible<Value, ValueError>;
+        type SerializeMap = SerializeMap;
+        type SerializeStruct = SerializeMap;
+        type SerializeStructVariant = ser::Impossible<Value, ValueError>;
+
+        fn serialize_bool(self, _: bool) -> Result<Value, ValueError> {
+            unsupported("bool")
+        }
+        fn serialize_i8(self, v: i8) -> Result<Value, ValueError> {
+            self.serialize_i64(v as i64)
+        }
+        fn serialize_i16(self, v: i16) -> Result<Value, ValueError> {
+            self.serialize_i64(v as i64)
+        }
+        fn serialize_i32(self, v: i32) -> Result<Value, ValueError> {
+            self.serialize_i64(v as i64)
+        }
+        fn serialize_i64(self, v: i64) -> Result<Value, ValueError> {
+            u64::try_from(v)
+                .map(Value::Int)
+                .map_err(|_| ser::Error::custom("DagPb cannot represent negative integers"))
+        }
+        fn serialize_i128(self, v: i128) -> Result<Value, ValueError> {
+            u64::try_from(v)
+                .map(Value::Int)
+                .map_err(|_| ser::Error::custom("integer out of DagPb range"))
+        }
+        fn serialize_u8(self, v: u8) -> Result<Value, ValueError> {
+            self.serialize_u64(v as u64)
+        }
+        fn serialize_u16(self, v: u16) -> Result<Value, ValueError> {
+            self.serialize_u64(v as u64)
+        }
+        fn serialize_u32(self, v: u32) -> Result<Value, ValueError> {
+            self.serialize_u64(v as u64)
+        }
+        fn serialize_u64(self, v: u64) -> Result<Value, ValueError> {
+            Ok(Value::Int(v))
+        }
+        fn serialize_u128(self, v: u128) -> Result<Value, ValueError> {
+            u64::try_from(v)
+                .map(Value::Int)
+                .map_err(|_| ser::Error::custom("integer out of DagPb range"))
+        }
+        fn serialize_f32(self, _: f32) -> Result<Value, ValueError> {
+            unsupported("float")
+        }
+        fn serialize_f64(self, _: f64) -> Result<Value, ValueError> {
+            unsupported("float")
+        }
+        fn serialize_char(self, v: char) -> Result<Value, ValueError> {
+            Ok(Value::String(v.to_string()))
+        }
+        fn serialize_str(self, v: &str) -> Result<Value, ValueError> {
+            Ok(Value::String(v.to_string()))
+        }
+        fn serialize_bytes(self, v: &[u8]) -> Result<Value, ValueError> {
+            Ok(Value::Bytes(v.to_vec()))
+        }
+        fn serialize_none(self) -> Result<Value, ValueError> {
+            Ok(Value::Null)
+        }
+        fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, ValueError> {
+            value.serialize(self)
+        }
+        fn serialize_unit(self) -> Result<Value, ValueError> {
+            Ok(Value::Null)
+        }
+        fn serialize_unit_struct(self, _: &'static str) -> Result<Value, ValueError> {
+            Ok(Value::Null)
+        }
+        fn serialize_unit_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+        ) -> Result<Value, ValueError> {
+            unsupported("enum")
+        }
+        fn serialize_newtype_struct<T: ?Sized + Serialize>(
+            self,
+            _: &'static str,
+            value: &T,
+        ) -> Result<Value, ValueError> {
+            value.serialize(self)
+        }
+        fn serialize_newtype_variant<T: ?Sized + Serialize>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &T,
+        ) -> Result<Value, ValueError> {
+            unsupported("enum")
+        }
+        fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, ValueError> {
+            Ok(SerializeList(Vec::with_capacity(len.unwrap_or_default())))
+        }
+        fn serialize_tuple(self, len: usize) -> Result<SerializeList, ValueError> {
+            self.serialize_seq(Some(len))
+        }
+        fn serialize_tuple_struct(
+            self,
+            _: &'static str,
+            len: usize,
+        ) -> Result<SerializeList, ValueError> {
+            self.serialize_seq(Some(len))
+        }
+        fn serialize_tuple_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeTupleVariant, ValueError> {
+            unsupported("enum")
+        }
+        fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, ValueError> {
+            Ok(SerializeMap(
+                Vec::with_capacity(len.unwrap_or_default()),
+                None,
+            ))
+        }
+        fn serialize_struct(self, _: &'static str, len: usize) -> Result<SerializeMap, ValueError> {
+            self.serialize_map(Some(len))
+        }
+        fn serialize_struct_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> Result<Self::SerializeStructVariant, ValueError> {
+            unsupported("enum")
+        }
+    }
+
+    pub(super) struct SerializeList(Vec<Value>);
+
+    impl ser::SerializeSeq for SerializeList {
+        type Ok = Value;
+        type Error = ValueError;
+        fn serialize_element<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), ValueError> {
+            self.0.push(v.serialize(ValueSerializer)?);
+            Ok(())
+        }
+        fn end(self) -> Result<Value, ValueError> {
+            Ok(Value::List(self.0))
+        }
+    }
+
+    impl ser::SerializeTuple for SerializeList {
+        type Ok = Value;
+        type Error = ValueError;
+        fn serialize_element<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), ValueError> {
+            ser::SerializeSeq::serialize_element(self, v)
+        }
+        fn end(self) -> Result<Value, ValueError> {
+            ser::SerializeSeq::end(self)
+        }
+    }
+
+    impl ser::SerializeTupleStruct for SerializeList {
+        type Ok = Value;
+        type Error = ValueError;
+        fn serialize_field<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), ValueError> {
+            ser::SerializeSeq::serialize_element(self, v)
+        }
+        fn end(self) -> Result<Value, ValueError> {
+            ser::SerializeSeq::end(self)
+        }
+    }
+
+    pub(super) struct SerializeMap(Vec<(String, Value)>, Option<String>);
+
+    impl ser::SerializeMap for SerializeMap {
+        type Ok = Value;
+        type Error = ValueError;
+        fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), ValueError> {
+            match key.serialize(ValueSerializer)? {
+                Value::String(key) => {
+                    self.1.replace(key);
+                    Ok(())
+                }
+                _ => Err(ser::Error::custom("DagPb map keys must be strings")),
+            }
+        }
+        fn serialize_value<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), ValueError> {
+            let key = self
+                .1
+                .take()
+                .ok_or_else(|| ser::Error::custom("map value serialized before its key"))?;
+            self.0.push((key, v.serialize(ValueSerializer)?));
+            Ok(())
+        }
+        fn end(self) -> Result<Value, ValueError> {
+            Ok(Value::Map(self.0))
+        }
+    }
+
+    impl ser::SerializeStruct for SerializeMap {
+        type Ok = Value;
+        type Error = ValueError;
+        fn serialize_field<T: ?Sized + Serialize>(
+            &mut self,
+            key: &'static str,
+            v: &T,
+        ) -> Result<(), ValueError> {
+            self.0.push((key.into(), v.serialize(ValueSerializer)?));
+            Ok(())
+        }
+        fn end(self) -> Result<Value, ValueError> {
+            Ok(Value::Map(self.0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs_::test_utils::*;
+
+    const CID_BYTES: &[u8] = &[
+        0x01, 0x55, 0x12, 0x20, 0x2c, 0xf2, 0x4d, 0xba, 0x5f, 0xb0, 0xa3, 0x0e, 0x26, 0xe8, 0x3b,
+        0x2a, 0xc5, 0xb9, 0xe2, 0x9e, 0x1b, 0x16, 0x1e, 0x5c, 0x1f, 0xa7, 0x42, 0x5e, 0x73, 0x04,
+        0x33, 0x62, 0x93, 0x8b, 0x98, 0x24,
+    ];
+
+    fn link(name: Option<&str>, tsize: Option<u64>) -> DagPbLink {
+        DagPbLink {
+            hash: Cid::try_from(CID_BYTES).unwrap(),
+            name: name.map(IpldString::from),
+            tsize,
+        }
+    }
+
+    fn encoded_link(name: Option<&str>, tsize: Option<u8>) -> Vec<u8> {
+        let mut link = vec![0x0a, CID_BYTES.len() as u8];
+        link.extend_from_slice(CID_BYTES);
+        if let Some(name) = name {
+            link.extend_from_slice(&[0x12, name.len() as u8]);
+            link.extend_from_slice(name.as_bytes());
+        }
+        if let Some(tsize) = tsize {
+            link.extend_from_slice(&[0x18, tsize]);
+        }
+
+        let mut bytes = vec![0x12, link.len() as u8];
+        bytes.extend(link);
+        bytes
+    }
+
+    #[test]
+    fn test_node() {
+        let data_only = DagPbNode {
+            links: vec![],
+            data: Some(Bytes::copy_from_slice(b"hello")),
+        };
+        let link_and_data = [encoded_link(Some("a"), Some(3)), vec![0x0a, 0x01, 0xff]].concat();
+        let tests = &[
+            (DagPbNode::default(), &[][..]),
+            (
+                DagPbNode {
+                    links: vec![],
+                    data: Some(Bytes::new()),
+                },
+                &[0x0a, 0x00][..],
+            ),
+            (data_only, &[0x0a, 0x05, b'h', b'e', b'l', b'l', b'o'][..]),
+            (
+                DagPbNode {
+                    links: vec![link(Some("a"), Some(3))],
+                    data: Some(Bytes::copy_from_slice(&[0xff])),
+                },
+                link_and_data.as_slice(),
+            ),
+        ];
+        roundtrip_bytes_codec::<DagPbNode>(DagPb::CODE, tests);
+    }
+
+    #[test]
+    fn test_sorted_links() {
+        let node = DagPbNode {
+            links: vec![
+                link(Some("b"), None),
+                link(None, None),
+                link(Some("a"), None),
+            ],
+            data: None,
+        };
+        let expected = [
+            encoded_link(None, None),
+            encoded_link(Some("a"), None),
+            encoded_link(Some("b"), None),
+        ]
+        .concat();
+
+        let bytes = DagPb::new().encode(&node).unwrap();
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_select() {
+        let node = DagPbNode {
+            links: vec![link(Some("a"), Some(3))],
+            data: Some(Bytes::copy_from_slice(b"hello")),
+        };
+        let block = DagPb::new().encode(&node).unwrap();
+
+        let mut ctx = MemoryContext::default();
+        let cid = ctx
+            .add_block(Version::V1, DagPb::CODE, Multihash::SHA2_256, block)
+            .unwrap();
+        let selected: DagPbNode = Params::<'_, _, DagPbNode>::new_select(cid)
+            .into_dag_iter(&mut ctx)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .dag
+            .downcast()
+            .unwrap();
+        assert_eq!(node, selected);
+    }
+
+    #[test]
+    fn test_strict_decoding() {
+        let mut codec = DagPb::new();
+        let link = encoded_link(None, None);
+
+        // data before links
+        let bytes = [&[0x0a, 0x00][..], &link].concat();
+        assert!(codec.decode::<DagPbNode>(&bytes).is_err());
+        // duplicate data
+        assert!(codec
+            .decode::<DagPbNode>(&[0x0a, 0x00, 0x0a, 0x00])
+            .is_err());
+        // unknown node field
+        assert!(codec.decode::<DagPbNode>(&[0x1a, 0x00]).is_err());
+        // wrong wire type
+        assert!(codec.decode::<DagPbNode>(&[0x08, 0x00]).is_err());
+        // truncated field
+        assert!(codec.decode::<DagPbNode>(&[0x0a, 0x05, 0x00]).is_err());
+        // link without a hash
+        assert!(codec
+            .decode::<DagPbNode>(&[0x12, 0x02, 0x18, 0x01])
+            .is_err());
+        // link with out-of-order fields
+        let mut bytes = vec![0x12, 0x04 + CID_BYTES.len() as u8, 0x18, 0x01, 0x0a];
+        bytes.push(CID_BYTES.len() as u8);
+        bytes.extend_from_slice(CID_BYTES);
+        assert!(codec.decode::<DagPbNode>(&bytes).is_err());
+    }
+}
@@ -1,80 +1,907 @@
-use crate::prelude::*;
+//! IPLD GitRaw codec.
 
-/// The GitRaw codec.
+use super::raw::{RawDeserializer, RawSerializer};
+use crate::dev::{macros::impl_selector_seed_serde, *};
+use serde::de::value::{BytesDeserializer, Error as ValueError};
+use std::{
+    convert::TryFrom,
+    fmt::{self, Write as _},
+    io::{Read, Write},
+    str,
+};
+
+/// The [GitRaw](https://ipld.io/specs/codecs/dag-git/) codec, which reads and
+/// writes (zlib-decompressed) git objects as [`GitObject`]s.
+///
+/// Git objects are linked by the SHA-1 [`Cid`]s of their encoded bytes, which
+/// are their git object ids.
+#[derive(Clone, Copy, Debug, Default)]
 pub struct GitRaw;
 
-pub struct Commit {
-    tree: Cid,
-    parents: Vec<Cid>,
-    author: String,
-    committer: String,
-    encoding: String,
-    #[serde(rename = "mergetag")]
-    merge_tag: Vec<Tag>,
-    signature: String,
-    message: String,
-}
+impl GitRaw {
+    /// The multicodec code that identifies this IPLD Codec.
+    pub const CODE: u64 = 0x78;
 
-pub struct Tag {}
-pub struct Tree {}
+    #[doc(hidden)]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
 
-impl Codec for DagPb {
-    const VERSION: cid::Version = cid::Version::V1;
-    const CODEC: cid::Codec = cid::Codec::GitRaw;
+    /// Decompresses a loose object (as found in `.git/objects`) into the
+    /// bytes of a git object.
+    pub fn inflate<R: Read>(loose: R) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        flate2::read::ZlibDecoder::new(loose)
+            .read_to_end(&mut bytes)
+            .map_err(|err| Error::Decoder(err.into()))?;
+        Ok(bytes)
+    }
 
-    type Error = CborError;
+    /// The SHA-1 [`Cid`] of a git object with the given object id.
+    pub fn cid_from_id(id: &[u8]) -> Result<Cid, Error> {
+        let mh = DefaultMultihash::wrap(Multihash::SHA1, id)?;
+        Ok(Cid::from(DefaultCid::new_v1(Self::CODE, mh)))
+    }
 
-    fn encode<S>(dag: &S) -> Result<Box<[u8]>, Self::Error>
+    /// The git object id of a SHA-1 [`Cid`].
+    pub fn id_from_cid(cid: &Cid) -> Result<[u8; ID_LEN], Error> {
+        if cid.multihash_code() != Multihash::SHA1 {
+            return Err(err(format!("link {:?} is not a SHA-1 Cid", cid)));
+        }
+        <[u8; ID_LEN]>::try_from(cid.digest()).map_err(|_| err("invalid SHA-1 digest"))
+    }
+
+    pub(crate) fn read_with_seed<'de, S, R>(&mut self, seed: S, mut reader: R) -> Result<(), Error>
     where
-        S: Serialize,
+        S: CodecDeserializeSeed<'de>,
+        R: Read,
     {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|err| Error::Decoder(err.into()))?;
+        let de = BytesDeserializer::<ValueError>::new(bytes.as_slice());
+        seed.deserialize::<{ Self::CODE }, _>(de)
+            .map_err(Error::decoder)
     }
+}
 
-    fn decode<'de, D>(bytes: &'de [u8]) -> Result<D, Self::Error>
+impl Codec for GitRaw {
+    fn write<T, W>(&mut self, dag: &T, writer: W) -> Result<(), Error>
     where
-        D: Deserialize<'de>,
+        T: Representation,
+        W: Write,
     {
+        Representation::serialize::<{ Self::CODE }, _>(dag, RawSerializer(writer))
+            .map_err(Error::encoder)
     }
 
-    fn write<S, W>(dag: &S, writer: W) -> Result<(), Self::Error>
+    fn decode<'de, T>(&mut self, bytes: &'de [u8]) -> Result<T, Error>
     where
-        S: Serialize,
-        W: Write,
+        T: Representation,
     {
+        Representation::deserialize::<{ Self::CODE }, _>(RawDeserializer(bytes))
+            .map_err(Error::decoder)
     }
 
-    fn read<D, R>(reader: R) -> Result<D, Self::Error>
+    fn read<T, R>(&mut self, mut reader: R) -> Result<T, Error>
     where
-        D: DeserializeOwned,
+        T: Representation,
         R: Read,
     {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|err| Error::Decoder(err.into()))?;
+        self.decode(&bytes)
     }
 }
 
-impl<'a, W: CborWrite> Encoder for &'a mut CborSerializer<W> {
-    #[inline]
-    fn serialize_link(self, cid: &Cid) -> Result<<Self as Serializer>::Ok, CborError> {
-        let vec: Vec<u8> = cid.to_bytes();
-        let bytes: &[u8] = vec.as_ref();
-        Tagged::new(Some(CBOR_LINK_TAG), bytes).serialize(self)
+impl TryFrom<u64> for GitRaw {
+    type Error = Error;
+    fn try_from(code: u64) -> Result<Self, Self::Error> {
+        match code {
+            Self::CODE => Ok(Self),
+            _ => Err(Error::UnknownMulticodecCode(code)),
+        }
+    }
+}
+
+/// The length of a SHA-1 git object id.
+const ID_LEN: usize = 20;
+
+fn err(msg: impl fmt::Display) -> Error {
+    Error::decoder(<ValueError as de::Error>::custom(format!(
+        "invalid git object: {}",
+        msg
+    )))
+}
+
+/// A git object.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GitObject {
+    /// A file's contents.
+    Blob(Bytes),
+    /// A directory listing.
+    Tree(Tree),
+    /// A commit.
+    Commit(Box<Commit>),
+    /// An annotated tag.
+    Tag(Box<Tag>),
+}
+
+/// A git commit.
+///
+/// Signatures (i.e. `author` and `committer`) are kept as they are encoded, as
+/// `Name <email> timestamp timezone`. As git doesn't require them (or the
+/// message) to be UTF-8, they are kept as bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commit {
+    /// The commit's root tree.
+    pub tree: Link<Tree>,
+    /// The commit's parents.
+    pub parents: List<Link<Commit>>,
+    /// The commit's author.
+    pub author: Bytes,
+    /// The commit's committer.
+    pub committer: Bytes,
+    /// The headers following the committer, in their encoded order.
+    pub headers: List<CommitHeader>,
+    /// The commit message, in the commit's [`encoding`](Commit::encoding).
+    pub message: Bytes,
+}
+
+/// A header of a [`Commit`] following its `committer`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommitHeader {
+    /// The commit message's encoding, if not UTF-8.
+    Encoding(Bytes),
+    /// A signed tag merged by the commit.
+    MergeTag(Box<Tag>),
+    /// A GPG signature of the commit.
+    Signature(Bytes),
+    /// Any other header, as `key value`.
+    Other(Bytes),
+}
+
+/// An annotated git tag.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    /// The tagged object.
+    pub object: Link<GitObject>,
+    /// The type of the tagged object.
+    pub tag_type: String,
+    /// The tag's name.
+    pub tag: Bytes,
+    /// The tag's creator, encoded as `Name <email> timestamp timezone`.
+    pub tagger: Option<Bytes>,
+    /// The tag message, including any signature.
+    pub message: Bytes,
+}
+
+/// A git tree.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tree {
+    /// The tree's entries, in their (git-sorted) encoded order.
+    pub entries: List<TreeEntry>,
+}
+
+/// An entry within a git [`Tree`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeEntry {
+    /// The entry's octal file mode, as encoded (e.g. `100644` or `40000`).
+    pub mode: String,
+    /// The entry's name, which (like any file name) may not be UTF-8.
+    pub name: Bytes,
+    /// The linked blob, tree, or (for submodules) commit.
+    pub hash: Link<GitObject>,
+}
+
+impl GitObject {
+    const BLOB: &'static str = "blob";
+    const TREE: &'static str = "tree";
+    const COMMIT: &'static str = "commit";
+    const TAG: &'static str = "tag";
+
+    /// Parses a (decompressed) git object, including its `<type> <size>\0`
+    /// header.
+    ///
+    /// Objects that wouldn't re-encode to the exact same bytes are rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let nul = bytes
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| err("missing header"))?;
+        let header = str::from_utf8(&bytes[..nul]).map_err(err)?;
+        let body = &bytes[nul + 1..];
+
+        let (kind, size) = header
+            .split_once(' ')
+            .ok_or_else(|| err("invalid header"))?;
+        if size != body.len().to_string() {
+            return Err(err(format!(
+                "expected {} bytes, found {}",
+                size,
+                body.len()
+            )));
+        }
+
+        let obj = match kind {
+            Self::BLOB => Self::Blob(Bytes::copy_from_slice(body)),
+            Self::TREE => Self::Tree(Tree::parse_body(body)?),
+            Self::COMMIT => Self::Commit(Box::new(Commit::parse_body(body)?)),
+            Self::TAG => Self::Tag(Box::new(Tag::parse_body(body)?)),
+            kind => return Err(err(format!("unknown object type `{}`", kind))),
+        };
+
+        if obj.to_bytes()? != bytes {
+            return Err(err("object is not canonically encoded"));
+        }
+        Ok(obj)
+    }
+
+    /// Encodes the git object, including its `<type> <size>\0` header.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let (kind, body) = match self {
+            Self::Blob(bytes) => (Self::BLOB, bytes.to_vec()),
+            Self::Tree(tree) => (Self::TREE, tree.body()?),
+            Self::Commit(commit) => (Self::COMMIT, commit.body()?),
+            Self::Tag(tag) => (Self::TAG, tag.body()?),
+        };
+
+        let mut bytes = format!("{} {}\0", kind, body.len()).into_bytes();
+        bytes.extend(body);
+        Ok(bytes)
+    }
+
+    /// The SHA-1 [`Cid`] of the encoded object.
+    pub fn cid(&self) -> Result<Cid, Error> {
+        let bytes = self.to_bytes()?;
+        Cid::new(Version::V1, GitRaw::CODE, Multihash::SHA1, bytes.as_slice())
+    }
+}
+
+impl Tree {
+    fn parse_body(mut body: &[u8]) -> Result<Self, Error> {
+        let mut entries = List::new();
+        while !body.is_empty() {
+            let space = body
+                .iter()
+                .position(|b| *b == b' ')
+                .ok_or_else(|| err("missing tree entry mode"))?;
+            let nul = body
+                .iter()
+                .position(|b| *b == 0)
+                .filter(|nul| *nul > space)
+                .ok_or_else(|| err("missing tree entry name"))?;
+            if body.len() < nul + 1 + ID_LEN {
+                return Err(err("truncated tree entry"));
+            }
+
+            entries.push(TreeEntry {
+                mode: str::from_utf8(&body[..space]).map_err(err)?.into(),
+                name: Bytes::copy_from_slice(&body[space + 1..nul]),
+                hash: Link::Cid(GitRaw::cid_from_id(&body[nul + 1..nul + 1 + ID_LEN])?),
+            });
+            body = &body[nul + 1 + ID_LEN..];
+        }
+
+        Ok(Self { entries })
+    }
+
+    fn body(&self) -> Result<Vec<u8>, Error> {
+        let mut body = Vec::new();
+        for entry in &self.entries {
+            write!(&mut body, "{} ", entry.mode).map_err(|err| Error::Encoder(err.into()))?;
+            body.extend_from_slice(&entry.name);
+            body.push(0);
+            body.extend(GitRaw::id_from_cid(entry.hash.cid())?);
+        }
+        Ok(body)
+    }
+}
+
+impl Commit {
+    fn parse_body(body: &[u8]) -> Result<Self, Error> {
+        let (headers, message) = parse_headers(body)?;
+        let mut headers = headers.into_iter().peekable();
+
+        let tree = match headers.next() {
+            Some(("tree", id)) => Link::Cid(parse_id(&id)?),
+            _ => return Err(err("commit is missing `tree`")),
+        };
+        let mut parents = List::new();
+        while let Some(("parent", id)) = headers.peek() {
+            parents.push(Link::Cid(parse_id(id)?));
+            headers.next();
+        }
+        let author = match headers.next() {
+            Some(("author", author)) => author,
+            _ => return Err(err("commit is missing `author`")),
+        };
+        let committer = match headers.next() {
+            Some(("committer", committer)) => committer,
+            _ => return Err(err("commit is missing `committer`")),
+        };
+
+        let headers = headers
+            .map(|(key, mut value)| {
+                Ok(match key {
+                    "encoding" => CommitHeader::Encoding(value.into()),
+                    "mergetag" => {
+                        value.push(b'\n');
+                        CommitHeader::MergeTag(Box::new(Tag::parse_body(&value)?))
+                    }
+                    "gpgsig" => CommitHeader::Signature(value.into()),
+                    key => CommitHeader::Other([key.as_bytes(), b" ", &value].concat().into()),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self {
+            tree,
+            parents,
+            author: author.into(),
+            committer: committer.into(),
+            headers,
+            message: Bytes::copy_from_slice(message),
+        })
+    }
+
+    /// The commit message's encoding, if not UTF-8.
+    pub fn encoding(&self) -> Option<&[u8]> {
+        self.headers.iter().find_map(|header| match header {
+            CommitHeader::Encoding(encoding) => Some(&encoding[..]),
+            _ => None,
+        })
+    }
+
+    /// The signed tags merged by the commit.
+    pub fn merge_tags(&self) -> impl Iterator<Item = &Tag> {
+        self.headers.iter().filter_map(|header| match header {
+            CommitHeader::MergeTag(tag) => Some(tag.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// The commit's (first) GPG signature.
+    pub fn signature(&self) -> Option<&[u8]> {
+        self.headers.iter().find_map(|header| match header {
+            CommitHeader::Signature(signature) => Some(&signature[..]),
+            _ => None,
+        })
+    }
+
+    fn body(&self) -> Result<Vec<u8>, Error> {
+        let mut body = Vec::new();
+        write_header(&mut body, b"tree", hex_id(self.tree.cid())?.as_bytes());
+        for parent in &self.parents {
+            write_header(&mut body, b"parent", hex_id(parent.cid())?.as_bytes());
+        }
+        write_header(&mut body, b"author", &self.author);
+        write_header(&mut body, b"committer", &self.committer);
+        for header in &self.headers {
+            match header {
+                CommitHeader::Encoding(encoding) => write_header(&mut body, b"encoding", encoding),
+                CommitHeader::MergeTag(tag) => {
+                    let tag = tag.body()?;
+                    write_header(
+                        &mut body,
+                        b"mergetag",
+                        tag.strip_suffix(b"\n").unwrap_or(&tag),
+                    );
+                }
+                CommitHeader::Signature(signature) => write_header(&mut body, b"gpgsig", signature),
+                CommitHeader::Other(other) => {
+                    let (key, value) = match other.iter().position(|b| *b == b' ') {
+                        Some(space) => (&other[..space], &other[space + 1..]),
+                        None => (&other[..], &[][..]),
+                    };
+                    write_header(&mut body, key, value);
+                }
+            }
+        }
+        body.push(b'\n');
+        body.extend_from_slice(&self.message);
+        Ok(body)
+    }
+}
+
+impl Tag {
+    fn parse_body(body: &[u8]) -> Result<Self, Error> {
+        let (headers, message) = parse_headers(body)?;
+        let mut headers = headers.into_iter();
+
+        let object = match headers.next() {
+            Some(("object", id)) => Link::Cid(parse_id(&id)?),
+            _ => return Err(err("tag is missing `object`")),
+        };
+        let tag_type = match headers.next() {
+            Some(("type", tag_type)) => String::from_utf8(tag_type).map_err(err)?,
+            _ => return Err(err("tag is missing `type`")),
+        };
+        let tag = match headers.next() {
+            Some(("tag", tag)) => tag.into(),
+            _ => return Err(err("tag is missing `tag`")),
+        };
+        let tagger = match headers.next() {
+            Some(("tagger", tagger)) => Some(tagger.into()),
+            None => None,
+            Some((key, _)) => return Err(err(format!("unexpected tag header `{}`", key))),
+        };
+        if let Some((key, _)) = headers.next() {
+            return Err(err(format!("unexpected tag header `{}`", key)));
+        }
+
+        Ok(Self {
+            object,
+            tag_type,
+            tag,
+            tagger,
+            message: Bytes::copy_from_slice(message),
+        })
+    }
+
+    fn body(&self) -> Result<Vec<u8>, Error> {
+        let mut body = Vec::new();
+        write_header(&mut body, b"object", hex_id(self.object.cid())?.as_bytes());
+        write_header(&mut body, b"type", self.tag_type.as_bytes());
+        write_header(&mut body, b"tag", &self.tag);
+        if let Some(tagger) = &self.tagger {
+            write_header(&mut body, b"tagger", tagger);
+        }
+        body.push(b'\n');
+        body.extend_from_slice(&self.message);
+        Ok(body)
+    }
+}
+
+type Headers<'a> = Vec<(&'a str, Vec<u8>)>;
+
+/// Splits a commit or tag into its headers and message, joining multi-line
+/// header values (whose continuation lines begin with a space).
+///
+/// Header keys must be UTF-8, while their values and the message are left as
+/// the bytes they were encoded as.
+fn parse_headers(body: &[u8]) -> Result<(Headers<'_>, &[u8]), Error> {
+    let split = body
+        .windows(2)
+        .position(|window| window == b"\n\n")
+        .ok_or_else(|| err("missing message"))?;
+    let (headers, message) = (&body[..split], &body[split + 2..]);
+
+    let mut parsed = Headers::new();
+    for line in headers.split(|b| *b == b'\n') {
+        match (line.strip_prefix(b" "), parsed.last_mut()) {
+            (Some(continuation), Some((_, value))) => {
+                value.push(b'\n');
+                value.extend_from_slice(continuation);
+            }
+            (Some(_), None) => return Err(err("unexpected header continuation")),
+            (None, _) => {
+                let space = line
+                    .iter()
+                    .position(|b| *b == b' ')
+                    .ok_or_else(|| err("invalid header"))?;
+                let key = str::from_utf8(&line[..space]).map_err(err)?;
+                parsed.push((key, line[space + 1..].to_vec()));
+            }
+        }
+    }
+
+    Ok((parsed, message))
+}
+
+fn write_header(body: &mut Vec<u8>, key: &[u8], value: &[u8]) {
+    body.extend_from_slice(key);
+    body.push(b' ');
+    for (i, line) in value.split(|b| *b == b'\n').enumerate() {
+        if i > 0 {
+            body.extend_from_slice(b"\n ");
+        }
+        body.extend_from_slice(line);
+    }
+    body.push(b'\n');
+}
+
+fn parse_id(hex: &[u8]) -> Result<Cid, Error> {
+    let hex = str::from_utf8(hex).map_err(err)?;
+    if hex.len() != ID_LEN * 2 {
+        return Err(err(format!("invalid object id `{}`", hex)));
     }
+
+    let mut id = [0u8; ID_LEN];
+    for (i, byte) in id.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| err(format!("invalid object id `{}`", hex)))?;
+    }
+    GitRaw::cid_from_id(&id)
+}
+
+fn hex_id(cid: &Cid) -> Result<String, Error> {
+    let mut hex = String::with_capacity(ID_LEN * 2);
+    for byte in GitRaw::id_from_cid(cid)? {
+        write!(hex, "{:02x}", byte).map_err(|err| Error::Encoder(err.into()))?;
+    }
+    Ok(hex)
+}
+
+macro_rules! impl_git_object {
+    ($ty:ty: $name:literal, $variant:ident, $schema:literal, $wrap:path, $unwrap:expr) => {
+        impl From<$ty> for GitObject {
+            fn from(inner: $ty) -> Self {
+                Self::$variant($wrap(inner))
+            }
+        }
+
+        impl TryFrom<GitObject> for $ty {
+            type Error = Error;
+            fn try_from(obj: GitObject) -> Result<Self, Self::Error> {
+                match obj {
+                    GitObject::$variant(inner) => Ok($unwrap(inner)),
+                    obj => Err(err(format!(
+                        concat!("expected a ", $name, ", found a {}"),
+                        Representation::name(&obj)
+                    ))),
+                }
+            }
+        }
+
+        impl From<$ty> for SelectedNode {
+            fn from(_: $ty) -> Self {
+                Self::Map
+            }
+        }
+
+        impl Representation for $ty {
+            const NAME: &'static str = $name;
+            const SCHEMA: &'static str = $schema;
+            const DATA_MODEL_KIND: Kind = Kind::Map;
+            const SCHEMA_KIND: Kind = Kind::Struct;
+            const REPR_KIND: Kind = Kind::Bytes;
+            const HAS_LINKS: bool = true;
+
+            #[inline]
+            #[doc(hidden)]
+            fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let obj = GitObject::from(self.clone());
+                Representation::serialize::<C, _>(&obj, serializer)
+            }
+
+            #[inline]
+            #[doc(hidden)]
+            fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let obj = <GitObject as Representation>::deserialize::<C, _>(deserializer)?;
+                Self::try_from(obj).map_err(D::Error::custom)
+            }
+        }
+
+        impl_git_object!(@serde $ty);
+    };
+    (@serde $ty:ty) => {
+        impl Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                Representation::serialize::<{ GitRaw::CODE }, _>(self, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                <Self as Representation>::deserialize::<{ GitRaw::CODE }, _>(deserializer)
+            }
+        }
+
+        impl_selector_seed_serde! { @selector_seed_codec_deseed {} {} $ty {
+            #[inline]
+            fn deserialize<const C: u64, D>(mut self, deserializer: D) -> Result<(), D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let matcher = self.selector.matcher().ok_or_else(|| {
+                    D::Error::custom(Error::unsupported_selector::<$ty>(self.selector))
+                })?;
+
+                let dag = <$ty as Representation>::deserialize::<C, _>(deserializer)?;
+                match self.mode() {
+                    SelectionMode::SelectNode => self
+                        .select_matched_node(dag.into(), matcher.label.as_deref())
+                        .map_err(D::Error::custom),
+                    SelectionMode::SelectDag => self
+                        .select_matched_dag(dag, matcher.label.as_deref())
+                        .map_err(D::Error::custom),
+                    SelectionMode::Patch => unreachable!(),
+                }
+            }
+        }}
+
+        impl_selector_seed_serde! { @selector_seed_select {} {} $ty }
+    };
 }
 
-impl<'de, 'a, R: CborRead<'de>> Decoder<'de> for &'a mut CborDeserializer<R> {
+impl Representation for GitObject {
+    const NAME: &'static str = "GitObject";
+    const SCHEMA: &'static str =
+        "type GitObject union { | Bytes blob | Tree tree | Commit commit | Tag tag } representation bytes";
+    const DATA_MODEL_KIND: Kind = Kind::Map;
+    const SCHEMA_KIND: Kind = Kind::Union;
+    const REPR_KIND: Kind = Kind::Bytes;
+    const HAS_LINKS: bool = true;
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Blob(_) => "Blob",
+            Self::Tree(_) => Tree::NAME,
+            Self::Commit(_) => Commit::NAME,
+            Self::Tag(_) => Tag::NAME,
+        }
+    }
+
+    fn data_model_kind(&self) -> Kind {
+        match self {
+            Self::Blob(_) => Kind::Bytes,
+            _ => Kind::Map,
+        }
+    }
+
+    fn has_links(&self) -> bool {
+        !matches!(self, Self::Blob(_))
+    }
+
+    #[inline]
+    #[doc(hidden)]
+    fn serialize<const C: u64, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes().map_err(S::Error::custom)?)
+    }
+
     #[inline]
-    fn deserialize_link<V>(self, visitor: V) -> Result<V::Value, CborError>
+    #[doc(hidden)]
+    fn deserialize<'de, const C: u64, D>(deserializer: D) -> Result<Self, D::Error>
     where
-        V: IpldVisitorExt<'de>,
+        D: Deserializer<'de>,
     {
-        match current_cbor_tag() {
-            Some(CBOR_LINK_TAG) => {
-                let bytes = <&[u8]>::deserialize(self)?;
-                let cid = ToCid::to_cid(bytes)
-                    .or::<CborError>(Err(de::Error::custom("expected a CID")))?;
-                visitor.visit_link(cid)
+        let bytes = <Bytes as Representation>::deserialize::<C, _>(deserializer)?;
+        Self::from_bytes(bytes.as_ref()).map_err(D::Error::custom)
+    }
+}
+
+impl_git_object!(Tree: "Tree", Tree,
+    "type Tree struct { entries [TreeEntry] } representation bytes",
+    std::convert::identity, std::convert::identity);
+impl_git_object!(Commit: "Commit", Commit,
+    "type Commit struct { tree &Tree parents [&Commit] author Bytes committer Bytes headers [CommitHeader] message Bytes } representation bytes",
+    Box::new, |commit: Box<Commit>| *commit);
+impl_git_object!(Tag: "Tag", Tag,
+    "type Tag struct { object &GitObject type String tag Bytes tagger optional Bytes message Bytes } representation bytes",
+    Box::new, |tag: Box<Tag>| *tag);
+impl_git_object!(@serde GitObject);
+
+impl From<GitObject> for SelectedNode {
+    fn from(obj: GitObject) -> Self {
+        match obj {
+            GitObject::Blob(bytes) => Self::Bytes(bytes),
+            _ => Self::Map,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path, process::Command};
+
+    fn git(dir: &Path, args: &[&str]) -> Option<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "A U Thor")
+            .env("GIT_AUTHOR_EMAIL", "author@example.com")
+            .env("GIT_AUTHOR_DATE", "1112911993 -0700")
+            .env("GIT_COMMITTER_NAME", "C O Mitter")
+            .env("GIT_COMMITTER_EMAIL", "committer@example.com")
+            .env("GIT_COMMITTER_DATE", "1112911993 -0700")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("HOME", dir)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8(output.stdout).unwrap().trim().to_string())
+    }
+
+    /// Loads every loose object of the repository into a [`MemoryContext`].
+    fn load_objects(dir: &Path) -> MemoryContext {
+        let mut ctx = MemoryContext::default();
+        for shard in fs::read_dir(dir.join(".git/objects")).unwrap() {
+            let shard = shard.unwrap().path();
+            let prefix = shard.file_name().unwrap().to_str().unwrap().to_string();
+            if prefix.len() != 2 {
+                continue;
+            }
+
+            for file in fs::read_dir(&shard).unwrap() {
+                let file = file.unwrap().path();
+                let bytes = GitRaw::inflate(fs::File::open(&file).unwrap()).unwrap();
+                let cid = ctx
+                    .add_block(Version::V1, GitRaw::CODE, Multihash::SHA1, bytes.clone())
+                    .unwrap();
+
+                // the Cid is the object id, and the object re-encodes exactly
+                let id = format!("{}{}", prefix, file.file_name().unwrap().to_str().unwrap());
+                assert_eq!(hex_id(&cid).unwrap(), id);
+                let obj: GitObject = GitRaw::new().decode(&bytes).unwrap();
+                assert_eq!(GitRaw::new().encode(&obj).unwrap(), bytes);
+                assert_eq!(obj.cid().unwrap(), cid);
             }
-            Some(_tag) => Err(de::Error::custom("unexpected CBOR tag")),
-            _ => Err(de::Error::custom("expected a CID")),
         }
+        ctx
+    }
+
+    #[test]
+    fn test_repo() {
+        let dir = std::env::temp_dir().join(format!("ipld-git-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        git(&dir, &["init", "-q"]).expect("`git init` should succeed");
+
+        fs::write(dir.join("README"), "hello\n").unwrap();
+        fs::write(dir.join("src/lib.rs"), "fn main() {}\n").unwrap();
+        git(&dir, &["add", "."]).unwrap();
+        git(&dir, &["commit", "-q", "-m", "first"]).unwrap();
+        fs::write(dir.join("README"), "hello, world\n").unwrap();
+        git(&dir, &["commit", "-q", "-a", "-m", "second\n\nwith a body"]).unwrap();
+        git(&dir, &["tag", "-a", "v1", "-m", "release"]).unwrap();
+
+        let head = git(&dir, &["rev-parse", "HEAD"]).unwrap();
+        let tag = git(&dir, &["rev-parse", "v1"]).unwrap();
+        let mut ctx = load_objects(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        // walk from the tag to the commit, its parent and their trees
+        let mut tag = Link::<Tag>::Cid(parse_id(tag.as_bytes()).unwrap());
+        let tag = tag.resolve(&mut ctx).unwrap().clone();
+        assert_eq!(&tag.tag[..], b"v1");
+        assert_eq!(tag.tag_type, "commit");
+        assert_eq!(&tag.message[..], b"release\n");
+        assert_eq!(hex_id(tag.object.cid()).unwrap(), head);
+
+        let mut commit = Link::<Commit>::Cid(*tag.object.cid());
+        let mut commit = commit.resolve(&mut ctx).unwrap().clone();
+        assert_eq!(&commit.message[..], b"second\n\nwith a body\n");
+        assert_eq!(
+            &commit.author[..],
+            b"A U Thor <author@example.com> 1112911993 -0700"
+        );
+        assert_eq!(commit.parents.len(), 1);
+
+        let parent = commit.parents[0].resolve(&mut ctx).unwrap();
+        assert_eq!(&parent.message[..], b"first\n");
+        assert!(parent.parents.is_empty());
+
+        let tree = commit.tree.resolve(&mut ctx).unwrap().clone();
+        let names: Vec<_> = tree.entries.iter().map(|e| &e.name[..]).collect();
+        assert_eq!(names, [&b"README"[..], b"src"]);
+        assert_eq!(tree.entries[1].mode, "40000");
+
+        let mut readme = tree.entries[0].hash.clone();
+        assert_eq!(
+            readme.resolve(&mut ctx).unwrap(),
+            &GitObject::Blob(Bytes::copy_from_slice(b"hello, world\n"))
+        );
+    }
+
+    #[test]
+    fn test_commit() {
+        let bytes = b"commit 322\0tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+            parent 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+            author A <a@example.com> 0 +0000\n\
+            committer C <c@example.com> 0 +0000\n\
+            gpgsig -----BEGIN PGP SIGNATURE-----\n \n abc\n -----END PGP SIGNATURE-----\n\
+            x-custom one\n two\n\
+            \n\
+            message\n";
+        let bytes = {
+            let body = &bytes[bytes.iter().position(|b| *b == 0).unwrap() + 1..];
+            let mut obj = format!("commit {}\0", body.len()).into_bytes();
+            obj.extend(body);
+            obj
+        };
+
+        let obj = GitObject::from_bytes(&bytes).unwrap();
+        let commit = match &obj {
+            GitObject::Commit(commit) => commit,
+            obj => panic!("expected a commit, found {:?}", obj),
+        };
+        assert_eq!(
+            commit.signature(),
+            Some(&b"-----BEGIN PGP SIGNATURE-----\n\nabc\n-----END PGP SIGNATURE-----"[..])
+        );
+        assert_eq!(
+            commit.headers[1],
+            CommitHeader::Other(Bytes::copy_from_slice(b"x-custom one\ntwo"))
+        );
+        assert_eq!(obj.to_bytes().unwrap(), bytes);
+
+        // headers round-trip in any order, including repeated headers
+        let body = "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+            author A <a@example.com> 0 +0000\n\
+            committer C <c@example.com> 0 +0000\n\
+            x-custom one\n\
+            gpgsig first\n\
+            encoding ISO-8859-1\n\
+            gpgsig second\n\
+            \n\
+            message\n";
+        let bytes = [
+            format!("commit {}\0", body.len()).as_bytes(),
+            body.as_bytes(),
+        ]
+        .concat();
+        let obj = GitObject::from_bytes(&bytes).unwrap();
+        let commit = match &obj {
+            GitObject::Commit(commit) => commit,
+            obj => panic!("expected a commit, found {:?}", obj),
+        };
+        assert_eq!(commit.encoding(), Some(&b"ISO-8859-1"[..]));
+        assert_eq!(commit.signature(), Some(&b"first"[..]));
+        assert_eq!(commit.headers.len(), 4);
+        assert_eq!(obj.to_bytes().unwrap(), bytes);
+
+        // non-UTF-8 messages and signatures are kept as they are encoded
+        let body = b"tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+            author Andr\xe9 <a@example.com> 0 +0000\n\
+            committer Andr\xe9 <a@example.com> 0 +0000\n\
+            encoding ISO-8859-1\n\
+            \n\
+            caf\xe9\n";
+        let bytes = [format!("commit {}\0", body.len()).as_bytes(), body].concat();
+        let obj = GitObject::from_bytes(&bytes).unwrap();
+        let commit = match &obj {
+            GitObject::Commit(commit) => commit,
+            obj => panic!("expected a commit, found {:?}", obj),
+        };
+        assert_eq!(&commit.author[..], b"Andr\xe9 <a@example.com> 0 +0000");
+        assert_eq!(&commit.message[..], b"caf\xe9\n");
+        assert_eq!(obj.to_bytes().unwrap(), bytes);
+
+        // non-canonical and invalid objects are rejected
+        assert!(GitObject::from_bytes(b"blob 2\0a").is_err());
+        assert!(GitObject::from_bytes(b"blob 01\0a").is_err());
+        assert!(GitObject::from_bytes(b"commit 6\0tree \n\n").is_err());
+        assert!(GitObject::from_bytes(b"tree 5\x00100644").is_err());
+        assert!(GitRaw::new().encode(&Null).is_err());
+    }
+
+    #[test]
+    fn test_tree() {
+        // file names needn't be UTF-8
+        let id = [0u8; ID_LEN];
+        let body = [&b"100644 caf\xe9\0"[..], &id, b"40000 src\0", &id].concat();
+        let bytes = [format!("tree {}\0", body.len()).as_bytes(), &body].concat();
+
+        let obj = GitObject::from_bytes(&bytes).unwrap();
+        let tree = match &obj {
+            GitObject::Tree(tree) => tree,
+            obj => panic!("expected a tree, found {:?}", obj),
+        };
+        let names: Vec<_> = tree.entries.iter().map(|e| &e.name[..]).collect();
+        assert_eq!(names, [&b"caf\xe9"[..], b"src"]);
+        assert_eq!(tree.entries[0].mode, "100644");
+        assert_eq!(obj.to_bytes().unwrap(), bytes);
     }
 }
@@ -2,10 +2,18 @@
 
 #[cfg(feature = "dag-cbor")]
 pub mod dag_cbor;
+#[cfg(feature = "dag-cose")]
+pub mod dag_cose;
+#[cfg(feature = "dag-jose")]
+pub mod dag_jose;
 #[cfg(feature = "dag-json")]
 pub mod dag_json;
-// #[cfg(feature = "dag-pb")]
-// pub mod dag_pb;
+#[cfg(feature = "dag-pb")]
+pub mod dag_pb;
+#[cfg(feature = "git")]
+pub mod git;
+#[cfg(feature = "raw")]
+pub mod raw;
 
 use crate::dev::*;
 use serde::{de, ser};
@@ -0,0 +1,317 @@
+//! IPLD Raw codec.
+
+use crate::dev::*;
+use serde::de::value::{BytesDeserializer, Error as ValueError};
+use std::{
+    convert::TryFrom,
+    io::{Read, Write},
+};
+
+/// The [Raw](https://ipld.io/specs/codecs/raw/) codec, which reads and writes
+/// a single [`Bytes`] node verbatim.
+///
+/// Encoding any other kind fails with an [`Error::Encoder`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Raw;
+
+impl Raw {
+    /// The multicodec code that identifies this IPLD Codec.
+    pub const CODE: u64 = 0x55;
+
+    #[doc(hidden)]
+    #[inline]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    pub(crate) fn read_with_seed<'de, S, R>(&mut self, seed: S, mut reader: R) -> Result<(), Error>
+    where
+        S: CodecDeserializeSeed<'de>,
+        R: Read,
+    {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|err| Error::Decoder(err.into()))?;
+        let de = BytesDeserializer::<ValueError>::new(bytes.as_slice());
+        seed.deserialize::<{ Self::CODE }, _>(de)
+            .map_err(Error::decoder)
+    }
+}
+
+impl Codec for Raw {
+    fn write<T, W>(&mut self, dag: &T, writer: W) -> Result<(), Error>
+    where
+        T: Representation,
+        W: Write,
+    {
+        Representation::serialize::<{ Self::CODE }, _>(dag, RawSerializer(writer))
+            .map_err(Error::encoder)
+    }
+
+    fn decode<'de, T>(&mut self, bytes: &'de [u8]) -> Result<T, Error>
+    where
+        T: Representation,
+    {
+        Representation::deserialize::<{ Self::CODE }, _>(RawDeserializer(bytes))
+            .map_err(Error::decoder)
+    }
+
+    fn read<T, R>(&mut self, mut reader: R) -> Result<T, Error>
+    where
+        T: Representation,
+        R: Read,
+    {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|err| Error::Decoder(err.into()))?;
+        self.decode(&bytes)
+    }
+}
+
+impl TryFrom<u64> for Raw {
+    type Error = Error;
+    fn try_from(code: u64) -> Result<Self, Self::Error> {
+        match code {
+            Self::CODE => Ok(Self),
+            _ => Err(Error::UnknownMulticodecCode(code)),
+        }
+    }
+}
+
+/// Presents the entire block as a single bytes node.
+pub(crate) struct RawDeserializer<'de>(pub(crate) &'de [u8]);
+
+impl<'de> Deserializer<'de> for RawDeserializer<'de> {
+    type Error = ValueError;
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_bytes(self.0)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Writes bytes directly to the writer, rejecting every other kind.
+pub(crate) struct RawSerializer<W>(pub(crate) W);
+
+impl<W: Write> RawSerializer<W> {
+    fn unsupported<T>(kind: &str) -> Result<T, ValueError> {
+        Err(ser::Error::custom(format!(
+            "codec can only encode bytes, not {}",
+            kind
+        )))
+    }
+}
+
+impl<W: Write> Serializer for RawSerializer<W> {
+    type Ok = ();
+    type Error = ValueError;
+    type SerializeSeq = ser::Impossible<(), ValueError>;
+    type SerializeTuple = ser::Impossible<(), ValueError>;
+    type SerializeTupleStruct = ser::Impossible<(), ValueError>;
+    type SerializeTupleVariant = ser::Impossible<(), ValueError>;
+    type SerializeMap = ser::Impossible<(), ValueError>;
+    type SerializeStruct = ser::Impossible<(), ValueError>;
+    type SerializeStructVariant = ser::Impossible<(), ValueError>;
+
+    #[inline]
+    fn serialize_bytes(mut self, v: &[u8]) -> Result<(), ValueError> {
+        self.0.write_all(v).map_err(ser::Error::custom)
+    }
+    #[inline]
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), ValueError> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _: bool) -> Result<(), ValueError> {
+        Self::unsupported("a bool")
+    }
+    fn serialize_i8(self, _: i8) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_i16(self, _: i16) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_i32(self, _: i32) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_i64(self, _: i64) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_i128(self, _: i128) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_u8(self, _: u8) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_u16(self, _: u16) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_u32(self, _: u32) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_u64(self, _: u64) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_u128(self, _: u128) -> Result<(), ValueError> {
+        Self::unsupported("an int")
+    }
+    fn serialize_f32(self, _: f32) -> Result<(), ValueError> {
+        Self::unsupported("a float")
+    }
+    fn serialize_f64(self, _: f64) -> Result<(), ValueError> {
+        Self::unsupported("a float")
+    }
+    fn serialize_char(self, _: char) -> Result<(), ValueError> {
+        Self::unsupported("a string")
+    }
+    fn serialize_str(self, _: &str) -> Result<(), ValueError> {
+        Self::unsupported("a string")
+    }
+    fn serialize_none(self) -> Result<(), ValueError> {
+        Self::unsupported("null")
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<(), ValueError> {
+        Self::unsupported("an optional value")
+    }
+    fn serialize_unit(self) -> Result<(), ValueError> {
+        Self::unsupported("null")
+    }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), ValueError> {
+        Self::unsupported("null")
+    }
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<(), ValueError> {
+        Self::unsupported("an enum")
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), ValueError> {
+        Self::unsupported("an enum")
+    }
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, ValueError> {
+        Self::unsupported("a list")
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, ValueError> {
+        Self::unsupported("a list")
+    }
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, ValueError> {
+        Self::unsupported("a list")
+    }
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, ValueError> {
+        Self::unsupported("an enum")
+    }
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, ValueError> {
+        Self::unsupported("a map")
+    }
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, ValueError> {
+        Self::unsupported("a map")
+    }
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, ValueError> {
+        Self::unsupported("an enum")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codecs_::test_utils::*;
+
+    #[test]
+    fn test_bytes() {
+        let tests = &[
+            (Bytes::new(), &[][..]),
+            (Bytes::copy_from_slice(&[0x00, 0xff]), &[0x00, 0xff][..]),
+        ];
+        roundtrip_bytes_codec::<Bytes>(Raw::CODE, tests);
+
+        let tests = &[(Any::Bytes(Bytes::copy_from_slice(b"raw")), &b"raw"[..])];
+        roundtrip_bytes_codec::<Any>(Raw::CODE, tests);
+    }
+
+    #[test]
+    fn test_other_kinds() {
+        let mut codec = Raw::new();
+        assert!(codec.encode(&Null).is_err());
+        assert!(codec.encode(&true).is_err());
+        assert!(codec.encode(&IpldString::from("raw")).is_err());
+        assert!(codec.encode(&vec![Bytes::new()]).is_err());
+        assert!(codec.encode(&Cid::default()).is_err());
+        assert!(codec.decode::<Vec<Bytes>>(b"raw").is_err());
+        assert!(codec.decode::<IpldString>(&[0xff]).is_err());
+        assert_eq!(
+            codec.decode::<IpldString>(b"raw").unwrap(),
+            IpldString::from("raw")
+        );
+    }
+
+    #[test]
+    fn test_select() {
+        let mut ctx = MemoryContext::default();
+        let cid = ctx
+            .add_block(
+                Version::V1,
+                Raw::CODE,
+                Multihash::SHA2_256,
+                b"leaf".to_vec(),
+            )
+            .unwrap();
+
+        let selected: Bytes = Params::<'_, _, Bytes>::new_select(cid)
+            .into_dag_iter(&mut ctx)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .dag
+            .downcast()
+            .unwrap();
+        assert_eq!(selected.as_ref(), b"leaf");
+
+        let mut link = Link::<Bytes>::Cid(cid);
+        assert_eq!(link.resolve(&mut ctx).unwrap().as_ref(), b"leaf");
+        assert!(matches!(link, Link::Inner { dirty: false, .. }));
+    }
+}
@@ -22,8 +22,9 @@ schema! {
         | Bool bool
         | Int int
         | Float float
-        | String string
+        // bytes are attempted before strings, as strings also decode from utf-8 bytes
         | Bytes bytes
+        | String string
         | List list
         | Map map
         // todo? should this be listed above? decoding untagged variants is attempted in def order
@@ -165,3 +166,279 @@ impl Any {
     // Calling this method should not cause an allocation.
     // Prototype() NodePrototype
 }
+
+impl Any {
+    /// Produces the data model view of a dag, as it would be seen by a codec.
+    pub(crate) fn from_dag<T: Representation>(dag: &T) -> Result<Self, Error> {
+        Representation::serialize::<{ Multicodec::DYN_CODE }, _>(dag, value::AnySerializer)
+            .map_err(Error::encoder)
+    }
+}
+
+/// Serializes any [`Representation`] into an [`Any`].
+mod value {
+    use super::*;
+    use serde::de::value::Error as ValueError;
+
+    pub(super) struct AnySerializer;
+
+    impl Serializer for AnySerializer {
+        type Ok = Any;
+        type Error = ValueError;
+        type SerializeSeq = SerializeList;
+        type SerializeTuple = SerializeList;
+        type SerializeTupleStruct = SerializeList;
+        type SerializeTupleVariant = SerializeVariant<SerializeList>;
+        type SerializeMap = SerializeMap;
+        type SerializeStruct = SerializeMap;
+        type SerializeStructVariant = SerializeVariant<SerializeMap>;
+
+        fn serialize_bool(self, v: bool) -> Result<Any, ValueError> {
+            Ok(Any::Bool(v))
+        }
+        fn serialize_i8(self, v: i8) -> Result<Any, ValueError> {
+            self.serialize_i64(v as i64)
+        }
+        fn serialize_i16(self, v: i16) -> Result<Any, ValueError> {
+            self.serialize_i64(v as i64)
+        }
+        fn serialize_i32(self, v: i32) -> Result<Any, ValueError> {
+            self.serialize_i64(v as i64)
+        }
+        fn serialize_i64(self, v: i64) -> Result<Any, ValueError> {
+            Ok(Any::Int(v))
+        }
+        fn serialize_i128(self, v: i128) -> Result<Any, ValueError> {
+            Int::try_from(v)
+                .map(Any::Int)
+                .map_err(|_| ser::Error::custom("integer out of range"))
+        }
+        fn serialize_u8(self, v: u8) -> Result<Any, ValueError> {
+            self.serialize_i64(v as i64)
+        }
+        fn serialize_u16(self, v: u16) -> Result<Any, ValueError> {
+            self.serialize_i64(v as i64)
+        }
+        fn serialize_u32(self, v: u32) -> Result<Any, ValueError> {
+            self.serialize_i64(v as i64)
+        }
+        fn serialize_u64(self, v: u64) -> Result<Any, ValueError> {
+            Int::try_from(v)
+                .map(Any::Int)
+                .map_err(|_| ser::Error::custom("integer out of range"))
+        }
+        fn serialize_u128(self, v: u128) -> Result<Any, ValueError> {
+            Int::try_from(v)
+                .map(Any::Int)
+                .map_err(|_| ser::Error::custom("integer out of range"))
+        }
+        fn serialize_f32(self, v: f32) -> Result<Any, ValueError> {
+            self.serialize_f64(v as f64)
+        }
+        fn serialize_f64(self, v: f64) -> Result<Any, ValueError> {
+            Ok(Any::Float(v))
+        }
+        fn serialize_char(self, v: char) -> Result<Any, ValueError> {
+            Ok(Any::String(IpldString::from(v.to_string())))
+        }
+        fn serialize_str(self, v: &str) -> Result<Any, ValueError> {
+            Ok(Any::String(IpldString::from(v)))
+        }
+        fn serialize_bytes(self, v: &[u8]) -> Result<Any, ValueError> {
+            Ok(Any::Bytes(Bytes::copy_from_slice(v)))
+        }
+        fn serialize_none(self) -> Result<Any, ValueError> {
+            Ok(Any::Null(Null))
+        }
+        fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Any, ValueError> {
+            value.serialize(self)
+        }
+        fn serialize_unit(self) -> Result<Any, ValueError> {
+            Ok(Any::Null(Null))
+        }
+        fn serialize_unit_struct(self, _: &'static str) -> Result<Any, ValueError> {
+            Ok(Any::Null(Null))
+        }
+        fn serialize_unit_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            variant: &'static str,
+        ) -> Result<Any, ValueError> {
+            self.serialize_str(variant)
+        }
+        /// [`Cid`]s are serialized as newtypes of their bytes, under a name
+        /// private to the `cid` crate.
+        fn serialize_newtype_struct<T: ?Sized + Serialize>(
+            self,
+            name: &'static str,
+            value: &T,
+        ) -> Result<Any, ValueError> {
+            match (name, value.serialize(self)?) {
+                (cid::serde::CID_SERDE_PRIVATE_IDENTIFIER, Any::Bytes(bytes)) => {
+                    let cid = Cid::try_from(bytes.as_ref()).map_err(ser::Error::custom)?;
+                    Ok(Any::Link(Rc::new(Link::Cid(cid))))
+                }
+                (_, value) => Ok(value),
+            }
+        }
+        fn serialize_newtype_variant<T: ?Sized + Serialize>(
+            self,
+            _: &'static str,
+            _: u32,
+            variant: &'static str,
+            value: &T,
+        ) -> Result<Any, ValueError> {
+            let value = value.serialize(self)?;
+            Ok(Any::Map(Map::from([(IpldString::from(variant), value)])))
+        }
+        fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, ValueError> {
+            Ok(SerializeList(Vec::with_capacity(len.unwrap_or_default())))
+        }
+        fn serialize_tuple(self, len: usize) -> Result<SerializeList, ValueError> {
+            self.serialize_seq(Some(len))
+        }
+        fn serialize_tuple_struct(
+            self,
+            _: &'static str,
+            len: usize,
+        ) -> Result<SerializeList, ValueError> {
+            self.serialize_seq(Some(len))
+        }
+        fn serialize_tuple_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            variant: &'static str,
+            len: usize,
+        ) -> Result<Self::SerializeTupleVariant, ValueError> {
+            Ok(SerializeVariant(variant, self.serialize_seq(Some(len))?))
+        }
+        fn serialize_map(self, _: Option<usize>) -> Result<SerializeMap, ValueError> {
+            Ok(SerializeMap(Map::new(), None))
+        }
+        fn serialize_struct(self, _: &'static str, len: usize) -> Result<SerializeMap, ValueError> {
+            self.serialize_map(Some(len))
+        }
+        fn serialize_struct_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            variant: &'static str,
+            len: usize,
+        ) -> Result<Self::SerializeStructVariant, ValueError> {
+            Ok(SerializeVariant(variant, self.serialize_map(Some(len))?))
+        }
+    }
+
+    pub(super) struct SerializeList(List);
+
+    impl ser::SerializeSeq for SerializeList {
+        type Ok = Any;
+        type Error = ValueError;
+        fn serialize_element<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), ValueError> {
+            self.0.push(v.serialize(AnySerializer)?);
+            Ok(())
+        }
+        fn end(self) -> Result<Any, ValueError> {
+            Ok(Any::List(self.0))
+        }
+    }
+
+    impl ser::SerializeTuple for SerializeList {
+        type Ok = Any;
+        type Error = ValueError;
+        fn serialize_element<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), ValueError> {
+            ser::SerializeSeq::serialize_element(self, v)
+        }
+        fn end(self) -> Result<Any, ValueError> {
+            ser::SerializeSeq::end(self)
+        }
+    }
+
+    impl ser::SerializeTupleStruct for SerializeList {
+        type Ok = Any;
+        type Error = ValueError;
+        fn serialize_field<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), ValueError> {
+            ser::SerializeSeq::serialize_element(self, v)
+        }
+        fn end(self) -> Result<Any, ValueError> {
+            ser::SerializeSeq::end(self)
+        }
+    }
+
+    pub(super) struct SerializeMap(Map, Option<IpldString>);
+
+    impl ser::SerializeMap for SerializeMap {
+        type Ok = Any;
+        type Error = ValueError;
+        fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), ValueError> {
+            match key.serialize(AnySerializer)? {
+                Any::String(key) => {
+                    self.1.replace(key);
+                    Ok(())
+                }
+                _ => Err(ser::Error::custom("map keys must be strings")),
+            }
+        }
+        fn serialize_value<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), ValueError> {
+            let key = self
+                .1
+                .take()
+                .ok_or_else(|| ser::Error::custom("map value serialized before its key"))?;
+            self.0.insert(key, v.serialize(AnySerializer)?);
+            Ok(())
+        }
+        fn end(self) -> Result<Any, ValueError> {
+            Ok(Any::Map(self.0))
+        }
+    }
+
+    impl ser::SerializeStruct for SerializeMap {
+        type Ok = Any;
+        type Error = ValueError;
+        fn serialize_field<T: ?Sized + Serialize>(
+            &mut self,
+            key: &'static str,
+            v: &T,
+        ) -> Result<(), ValueError> {
+            self.0.insert(key.into(), v.serialize(AnySerializer)?);
+            Ok(())
+        }
+        fn end(self) -> Result<Any, ValueError> {
+            Ok(Any::Map(self.0))
+        }
+    }
+
+    /// Enum variants are represented as single-entry maps of the variant name
+    /// to its contents.
+    pub(super) struct SerializeVariant<S>(&'static str, S);
+
+    impl ser::SerializeTupleVariant for SerializeVariant<SerializeList> {
+        type Ok = Any;
+        type Error = ValueError;
+        fn serialize_field<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), ValueError> {
+            ser::SerializeSeq::serialize_element(&mut self.1, v)
+        }
+        fn end(self) -> Result<Any, ValueError> {
+            let value = ser::SerializeSeq::end(self.1)?;
+            Ok(Any::Map(Map::from([(IpldString::from(self.0), value)])))
+        }
+    }
+
+    impl ser::SerializeStructVariant for SerializeVariant<SerializeMap> {
+        type Ok = Any;
+        type Error = ValueError;
+        fn serialize_field<T: ?Sized + Serialize>(
+            &mut self,
+            key: &'static str,
+            v: &T,
+        ) -> Result<(), ValueError> {
+            ser::SerializeStruct::serialize_field(&mut self.1, key, v)
+        }
+        fn end(self) -> Result<Any, ValueError> {
+            let value = ser::SerializeStruct::end(self.1)?;
+            Ok(Any::Map(Map::from([(IpldString::from(self.0), value)])))
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::dev::*;
 use macros::{derive_more::From, impl_selector_seed_serde};
-use std::fmt;
+use std::{fmt, rc::Weak};
 
 ///
 #[derive(
@@ -31,6 +31,26 @@ impl<T: Representation> Link<T> {
         }
     }
 
+    /// Loads and decodes the linked dag from the [`Context`], if not already
+    /// loaded, returning a reference to it.
+    pub fn resolve<C: Context>(&mut self, ctx: &mut C) -> Result<&T, Error> {
+        if let Self::Cid(cid) = self {
+            let cid = *cid;
+            let block = ctx.block_reader(&cid)?;
+            let t = Multicodec::try_from(&cid)?.read(block)?;
+            *self = Self::Inner {
+                cid,
+                t,
+                dirty: false,
+            };
+        }
+
+        match self {
+            Self::Inner { t, .. } => Ok(t),
+            Self::Cid(_) => unreachable!(),
+        }
+    }
+
     /*
     ///
     #[inline]
@@ -89,8 +109,11 @@ impl<T: Representation> Representation for Link<T> {
 }
 
 impl_selector_seed_serde! { @codec_seed_visitor
-    { T: Representation + 'static }
-    { for<'b> CodedSeed<'b, C, Ctx, T>: DeserializeSeed<'de, Value = ()>, }
+    { T: Select<Ctx> + 'static }
+    {
+        for<'b> CodedSeed<'b, C, Ctx, T>: DeserializeSeed<'de, Value = ()>,
+        for<'b> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de>,
+    }
     Link<T>
 {
     #[inline]
@@ -100,8 +123,11 @@ impl_selector_seed_serde! { @codec_seed_visitor
 }}
 
 impl_selector_seed_serde! { @codec_seed_visitor_ext
-    { T: Representation + 'static }
-    { for<'b> CodedSeed<'b, C, Ctx, T>: DeserializeSeed<'de, Value = ()>, }
+    { T: Select<Ctx> + 'static }
+    {
+        for<'b> CodedSeed<'b, C, Ctx, T>: DeserializeSeed<'de, Value = ()>,
+        for<'b> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de>,
+    }
     Link<T>
 {
     #[inline]
@@ -124,7 +150,7 @@ impl_selector_seed_serde! { @codec_seed_visitor_ext
 }}
 
 impl_selector_seed_serde! { @selector_seed_codec_deseed
-    { T: Representation + 'static }
+    { T: Select<Ctx> + 'static }
     { for<'b> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de> }
     Link<T>
 {
@@ -150,6 +176,27 @@ impl_selector_seed_serde! { @selector_seed_codec_deseed
                 }
             }
         }
+        cfg_if::cfg_if!{
+            if #[cfg(feature = "dag-pb")] {
+                if C == DagPb::CODE {
+                    return DagPb::deserialize_link(deserializer, CodecSeed::<C, _>(self));
+                }
+            }
+        }
+        cfg_if::cfg_if!{
+            if #[cfg(feature = "dag-jose")] {
+                if C == DagJose::CODE {
+                    return DagJose::deserialize_link(deserializer, CodecSeed::<C, _>(self));
+                }
+            }
+        }
+        cfg_if::cfg_if!{
+            if #[cfg(feature = "dag-cose")] {
+                if C == DagCose::CODE {
+                    return DagCose::deserialize_link(deserializer, CodecSeed::<C, _>(self));
+                }
+            }
+        }
 
         // TODO:
         Deserialize::deserialize(deserializer)
@@ -157,22 +204,80 @@ impl_selector_seed_serde! { @selector_seed_codec_deseed
 }}
 
 impl_selector_seed_serde! { @selector_seed_select
-    { T: Representation + 'static }
+    { T: Select<Ctx> + 'static }
     { for<'b, 'de> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de> }
     Link<T>
-}
+{
+    /// Matches the link itself if the current selector is a matcher,
+    /// otherwise continues selection within the linked dag, loading its block
+    /// only if the link has yet to be resolved.
+    #[doc(hidden)]
+    fn select_in_seed(&self, mut seed: SelectorSeed<'_, Ctx, Self>) -> Result<(), Error> {
+        if let Some(matcher) = seed.selector.as_matcher() {
+            let cid = *self.cid();
+            return match seed.mode() {
+                SelectionMode::SelectNode => {
+                    seed.select_matched_node(cid.into(), matcher.label.as_deref())
+                }
+                SelectionMode::SelectDag => {
+                    seed.select_matched_dag(Link::Cid(cid), matcher.label.as_deref())
+                }
+                SelectionMode::Patch => unreachable!(),
+            };
+        }
+
+        match self {
+            Self::Cid(cid) => explore_link::<Ctx, T>(*cid, seed),
+            Self::Inner { t, .. } => seed.select_in_inner(t),
+        }
+    }
+
+    /// Patches the link itself if the current selector is a matcher,
+    /// otherwise loads the linked dag (if not already loaded) and patches it,
+    /// marking the link dirty if anything within it was patched.
+    ///
+    /// When flushing, only dirty links are followed, each being flushed and
+    /// updated with its new [`Cid`].
+    #[doc(hidden)]
+    fn patch_seed(&mut self, mut seed: SelectorSeed<'_, Ctx, Self>) -> Result<bool, Error> {
+        if seed.callback.is_flush() {
+            return match self {
+                Self::Inner { cid, t, dirty } if *dirty => {
+                    *cid = seed.flush_inner(cid, t)?;
+                    *dirty = false;
+                    Ok(true)
+                }
+                _ => Ok(false),
+            };
+        }
+        if seed.selector.is_matcher() {
+            return seed.patch_matched(self);
+        }
+
+        self.resolve(seed.ctx)?;
+        match self {
+            Self::Inner { t, dirty, .. } => {
+                let patched = seed.patch_inner(t)?;
+                *dirty |= patched;
+                Ok(patched)
+            }
+            Self::Cid(_) => unreachable!(),
+        }
+    }
+}}
 
 impl<'a, const C: u64, Ctx, T> CodecSeed<C, SelectorSeed<'a, Ctx, Link<T>>>
 where
     Ctx: Context,
-    T: Representation + 'static,
+    T: Select<Ctx> + 'static,
 {
-    ///
-    /// TODO: continue selection if the current selector is not a matcher
+    /// Matches the link itself if the current selector is a matcher,
+    /// otherwise continues selection within the linked block.
     fn visit_link<'de, E>(mut self, cid: Cid) -> Result<(), E>
     where
         E: de::Error,
         for<'b> CodedSeed<'b, C, Ctx, T>: DeserializeSeed<'de, Value = ()>,
+        for<'b> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de>,
     {
         if let Some(matcher) = self.0.selector.as_matcher() {
             match self.0.mode() {
@@ -192,8 +297,63 @@ where
             return Ok(());
         }
 
-        unimplemented!()
+        explore_link::<Ctx, T>(cid, self.0).map_err(E::custom)
+    }
+}
+
+/// Continues selection within the block of the linked `T`, or defers it until
+/// a lazy iterator reaches the link.
+fn explore_link<'de, Ctx, T>(cid: Cid, seed: SelectorSeed<'_, Ctx, Link<T>>) -> Result<(), Error>
+where
+    Ctx: Context,
+    T: Select<Ctx> + 'static,
+    for<'b> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de>,
+{
+    let SelectorSeed {
+        selector,
+        state,
+        callback,
+        ctx,
+    } = seed;
+    if let Callback::MatchDag { .. } = callback {
+        return Err(Error::unsupported_selector::<Link<T>>(selector));
+    }
+
+    // defer selection of the linked block until a lazy iterator reaches it
+    if let Some(queue) = callback.deferred().and_then(Weak::upgrade) {
+        queue.borrow_mut().push(Deferred::Link(DeferredLink {
+            cid,
+            selector: selector.clone(),
+            state: state.clone(),
+            select: select_link::<Ctx, T>,
+        }));
+        return Ok(());
     }
+
+    // link depth is tracked when descending into the link's field
+    select_link::<Ctx, T>(cid, selector, state, callback.cast(), ctx)
+}
+
+/// Selects against the block of the linked `T`.
+fn select_link<'de, Ctx, T>(
+    cid: Cid,
+    selector: &Selector,
+    state: &mut State,
+    callback: Callback<'_, Ctx, Any>,
+    ctx: &mut Ctx,
+) -> Result<(), Error>
+where
+    Ctx: Context,
+    T: Select<Ctx> + 'static,
+    for<'b> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de>,
+{
+    let seed = SelectorSeed::<'_, Ctx, T> {
+        selector,
+        state,
+        callback: callback.cast(),
+        ctx,
+    };
+    seed.select_block(&cid)
 }
 
 // impl<'a, 'de, C, T> Visitor<'de> for ContextSeed<'a, C, Link<T>>
@@ -88,12 +88,13 @@ impl_selector_seed_serde! { @codec_seed_visitor
     {
         match self.0.selector {
             Selector::Matcher(_) => self.match_list(seq),
-            Selector::ExploreIndex(s) => self.explore_list_range(s.index as usize..s.index as usize, seq),
-            Selector::ExploreRange(s) => self.explore_list_range(s.start as usize..s.end as usize, seq),
-            Selector::ExploreAll(_) => self.explore_list_range(0.., seq),
-            _ => Err(A::Error::custom(Error::unsupported_selector::<List<T>>(
-                self.0.selector,
-            ))),
+            selector => match explored_bounds(selector) {
+                Some((start, Some(end))) => self.explore_list_range(start..end, seq),
+                Some((start, None)) => self.explore_list_range(start.., seq),
+                None => Err(A::Error::custom(Error::unsupported_selector::<List<T>>(
+                    selector,
+                ))),
+            },
         }
     }
 }}
@@ -119,10 +120,81 @@ impl_selector_seed_serde! { @selector_seed_codec_deseed
 }}
 
 impl_selector_seed_serde! { @selector_seed_select
-    { T: Representation + 'static }
+    { T: Select<Ctx> + Clone + 'static }
     { for<'b, 'de> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de> }
     List<T>
-}
+{
+    #[doc(hidden)]
+    fn select_in_seed(&self, mut seed: SelectorSeed<'_, Ctx, Self>) -> Result<(), Error> {
+        let selector = seed.selector;
+        let range = match selector {
+            Selector::Matcher(matcher) if seed.is_dag() => {
+                return seed.select_matched_dag(self.clone(), matcher.label.as_deref());
+            }
+            Selector::Matcher(matcher) => {
+                seed.select_matched_node(SelectedNode::List, matcher.label.as_deref())?;
+                0..self.len()
+            }
+            _ => match explored_bounds(selector) {
+                Some((start, end)) => start..end.unwrap_or(self.len()),
+                None => return Err(Error::unsupported_selector::<List<T>>(selector)),
+            },
+        };
+        if seed.is_node() && !selector.is_matcher() {
+            seed.select_node(SelectedNode::List)?;
+        } else if let Some(matcher) = selector.union_matcher() {
+            seed.select_matched_dag(self.clone(), matcher.label.as_deref())?;
+        }
+
+        let (selector, state, mut callback, ctx) = seed.into_parts();
+        for index in range {
+            let field = Field::from(index);
+            let Some(next) = selector.next_selector(&field) else {
+                continue;
+            };
+            let dag = self
+                .get(index)
+                .ok_or_else(|| Error::explore_list_failure(selector, index))?;
+            SelectorSeed::field_select_seed::<T>(&next, state, &mut callback, ctx, field, None)?
+                .select_in_dag(dag)?;
+            state.ascend::<T>()?;
+        }
+
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    fn patch_seed(&mut self, mut seed: SelectorSeed<'_, Ctx, Self>) -> Result<bool, Error> {
+        let range = match seed.selector {
+            Selector::Matcher(_) => return seed.patch_matched(self),
+            selector => match explored_bounds(selector) {
+                Some((start, end)) => start..end.unwrap_or(self.len()),
+                None => return Err(Error::unsupported_selector::<List<T>>(selector)),
+            },
+        };
+        if range.end > self.len() {
+            return Err(Error::explore_list_failure(seed.selector, self.len()));
+        }
+        let mut patched = match seed.selector.union_matcher() {
+            Some(_) => seed.patch_matched(self)?,
+            None => false,
+        };
+
+        let (selector, state, mut callback, ctx) = seed.into_parts();
+        for index in range {
+            let field = Field::from(index);
+            let Some(next) = selector.next_selector(&field) else {
+                continue;
+            };
+            let seed =
+                SelectorSeed::field_select_seed::<T>(&next, state, &mut callback, ctx, field, None)?;
+            patched |= seed.patch_dag(&mut self[index])?;
+            state.ascend::<T>()?;
+        }
+
+        Ok(patched)
+    }
+}}
 
 /*
 impl<'a, 'de, Ctx, T> Visitor<'de> for ContextSeed<'a, C, List<T>>
@@ -221,7 +293,7 @@ where
 
         let (selector, state, mut params, ctx) = self.0.into_parts();
 
-        // select against each child
+        // select against each child, with the matcher as its own next selector
         for index in 0usize.. {
             let is_empty = SelectorSeed::field_select_seed::<T>(
                 selector,
@@ -290,19 +362,26 @@ where
             }
         }
 
-        // explore any/all indices in the range
+        // explore any/all indices in the range, skipping those between the
+        // indices explored by the members of a union
         for index in range {
-            let is_empty = SelectorSeed::field_select_seed::<T>(
-                &selector,
-                state,
-                &mut params,
-                ctx,
-                index.into(),
-                None,
-            )
-            .map_err(A::Error::custom)
-            .and_then(|seed| Ok(seq.next_element_seed(CodecSeed(seed))?.is_none()))?;
-            state.ascend::<T>().map_err(A::Error::custom)?;
+            let field = Field::from(index);
+            let is_empty = if let Some(next) = selector.next_selector(&field) {
+                let is_empty = SelectorSeed::field_select_seed::<T>(
+                    &next,
+                    state,
+                    &mut params,
+                    ctx,
+                    field,
+                    None,
+                )
+                .map_err(A::Error::custom)
+                .and_then(|seed| Ok(seq.next_element_seed(CodecSeed(seed))?.is_none()))?;
+                state.ascend::<T>().map_err(A::Error::custom)?;
+                is_empty
+            } else {
+                seq.next_element::<IgnoredAny>()?.is_none()
+            };
 
             // if unbounded and empty, then we're done exploring
             // if bounded and empty, then we failed to explore everything
@@ -326,6 +405,26 @@ where
     }
 }
 
+/// The bounds of the list indices explored by a selector, spanning those of
+/// each member of an [`ExploreUnion`], with an unbounded end for
+/// [`ExploreAll`].
+fn explored_bounds(selector: &Selector) -> Option<(usize, Option<usize>)> {
+    match selector {
+        Selector::ExploreIndex(s) => Some((s.index as usize, Some(s.index as usize + 1))),
+        Selector::ExploreRange(s) => Some((s.start as usize, Some(s.end as usize))),
+        Selector::ExploreAll(_) => Some((0, None)),
+        Selector::ExploreUnion(inner) => inner.iter().filter_map(explored_bounds).reduce(
+            |(start, end), (other_start, other_end)| {
+                (
+                    start.min(other_start),
+                    end.zip(other_end).map(|(a, b)| a.max(b)),
+                )
+            },
+        ),
+        _ => None,
+    }
+}
+
 /*
 impl<'a, Ctx, T> ContextSeed<'a, C, List<T>>
 where
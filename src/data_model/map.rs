@@ -1,6 +1,6 @@
 use crate::dev::*;
 use macros::impl_selector_seed_serde;
-use std::{cell::RefCell, collections::BTreeMap, fmt};
+use std::{cell::RefCell, collections::BTreeMap, fmt, marker::PhantomData};
 
 ///
 /// TODO: indexmap?
@@ -34,12 +34,11 @@ where
     {
         use ser::SerializeMap;
 
-        // let mut seq = serializer.serialize_seq(Some(self.len()))?;
-        // for elem in self {
-        //     seq.serialize_element(&EncoderElem::<'_, C, _>(elem))?;
-        // }
-        // seq.end()
-        unimplemented!()
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, val) in self {
+            map.serialize_entry(&EncoderElem::<'_, C, _>(key), &EncoderElem::<'_, C, _>(val))?;
+        }
+        map.end()
     }
 
     #[inline]
@@ -48,35 +47,40 @@ where
     where
         D: Deserializer<'de>,
     {
-        // struct ListVisitor<const C: u64, T>(PhantomData<T>);
-        // impl<const C: u64, T> Default for ListVisitor<C, T> {
-        //     fn default() -> Self {
-        //         Self(PhantomData)
-        //     }
-        // }
-        // impl<'de, const C: u64, T: Representation> Visitor<'de> for ListVisitor<C, T> {
-        //     type Value = List<T>;
-        //     #[inline]
-        //     fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        //         write!(f, "A list of `{}`", T::NAME)
-        //     }
-
-        //     #[inline]
-        //     fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-        //     where
-        //         A: SeqAccess<'de>,
-        //     {
-        //         let mut list = List::with_capacity(seq.size_hint().unwrap_or(8));
-        //         while let Some(elem) = seq.next_element_seed(DecoderElem::<C, T>::default())? {
-        //             list.push(elem);
-        //         }
-        //         Ok(list)
-        //     }
-        // }
-
-        // deserializer.deserialize_seq(ListVisitor::<C, T>::default())
-
-        unimplemented!()
+        struct MapVisitor<const C: u64, K, V>(PhantomData<(K, V)>);
+        impl<const C: u64, K, V> Default for MapVisitor<C, K, V> {
+            fn default() -> Self {
+                Self(PhantomData)
+            }
+        }
+        impl<'de, const C: u64, K, V> Visitor<'de> for MapVisitor<C, K, V>
+        where
+            K: Representation + Ord,
+            V: Representation,
+        {
+            type Value = Map<K, V>;
+            #[inline]
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "A map of `{}` to `{}`", K::NAME, V::NAME)
+            }
+
+            #[inline]
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut new_map = Map::new();
+                while let Some((key, val)) = map.next_entry_seed(
+                    DecoderElem::<C, K>::default(),
+                    DecoderElem::<C, V>::default(),
+                )? {
+                    new_map.insert(key, val);
+                }
+                Ok(new_map)
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor::<C, K, V>::default())
     }
 }
 
@@ -101,6 +105,7 @@ impl_selector_seed_serde! { @codec_seed_visitor
             Selector::Matcher(_) => self.match_map(map),
             Selector::ExploreFields(_) => self.explore_map_fields(map),
             Selector::ExploreAll(_) => self.explore_map_all(map),
+            Selector::ExploreUnion(_) => self.explore_map(map),
             _ => Err(A::Error::custom(Error::unsupported_selector::<Map<K, V>>(
                 self.0.selector,
             ))),
@@ -134,11 +139,72 @@ impl_selector_seed_serde! { @selector_seed_codec_deseed
 
 impl_selector_seed_serde! { @selector_seed_select
     { K: Representation + Clone + Ord + AsRef<str> + 'static,
-      V: Representation + 'static }
+      V: Select<Ctx> + Clone + 'static }
     { for<'b, 'de> SelectorSeed<'b, Ctx, K>: CodecDeserializeSeed<'de>,
       for<'b, 'de> SelectorSeed<'b, Ctx, V>: CodecDeserializeSeed<'de>, }
     Map<K, V>
-}
+{
+    #[doc(hidden)]
+    fn select_in_seed(&self, mut seed: SelectorSeed<'_, Ctx, Self>) -> Result<(), Error> {
+        match seed.selector {
+            Selector::Matcher(matcher) if seed.is_dag() => {
+                return seed.select_matched_dag(self.clone(), matcher.label.as_deref());
+            }
+            Selector::Matcher(matcher) => {
+                seed.select_matched_node(SelectedNode::Map, matcher.label.as_deref())?;
+            }
+            Selector::ExploreFields(_) | Selector::ExploreAll(_) | Selector::ExploreUnion(_)
+                if seed.is_node() =>
+            {
+                seed.select_node(SelectedNode::Map)?
+            }
+            selector @ Selector::ExploreUnion(_) => {
+                if let Some(matcher) = selector.union_matcher() {
+                    seed.select_matched_dag(self.clone(), matcher.label.as_deref())?;
+                }
+            }
+            Selector::ExploreFields(_) | Selector::ExploreAll(_) => {}
+            selector => return Err(Error::unsupported_selector::<Map<K, V>>(selector)),
+        };
+
+        let (selector, state, mut callback, ctx) = seed.into_parts();
+        for (key, val) in self.iter() {
+            let field = Field::from(key.as_ref());
+            let Some(next) = selector.next_selector(&field) else {
+                continue;
+            };
+            SelectorSeed::field_select_seed::<V>(&next, state, &mut callback, ctx, field, None)?
+                .select_in_dag(val)?;
+            state.ascend::<V>()?;
+        }
+
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    fn patch_seed(&mut self, mut seed: SelectorSeed<'_, Ctx, Self>) -> Result<bool, Error> {
+        let mut patched = match seed.selector {
+            Selector::Matcher(_) => return seed.patch_matched(self),
+            selector if selector.union_matcher().is_some() => seed.patch_matched(self)?,
+            Selector::ExploreFields(_) | Selector::ExploreAll(_) | Selector::ExploreUnion(_) => false,
+            _ => return Err(Error::unsupported_selector::<Map<K, V>>(seed.selector)),
+        };
+
+        let (selector, state, mut callback, ctx) = seed.into_parts();
+        for (key, val) in self.iter_mut() {
+            let field = Field::from(key.as_ref());
+            let Some(next) = selector.next_selector(&field) else {
+                continue;
+            };
+            let seed =
+                SelectorSeed::field_select_seed::<V>(&next, state, &mut callback, ctx, field, None)?;
+            patched |= seed.patch_dag(val)?;
+            state.ascend::<V>()?;
+        }
+
+        Ok(patched)
+    }
+}}
 
 impl<'a, const C: u64, Ctx, K, V> CodedSeed<'a, C, Ctx, Map<K, V>>
 where
@@ -174,7 +240,7 @@ where
 
         let (selector, state, mut params, ctx) = self.0.into_parts();
 
-        // select against each child
+        // select against each child, with the matcher as its own next selector
         while let Some(key) = map.next_key::<K>()? {
             SelectorSeed::field_select_seed::<V>(
                 selector,
@@ -209,23 +275,55 @@ where
     }
 
     ///
-    pub(crate) fn explore_map_fields<'de, A>(self, mut map: A) -> Result<(), A::Error>
+    pub(crate) fn explore_map_fields<'de, A>(self, map: A) -> Result<(), A::Error>
     where
         A: MapAccess<'de>,
         for<'b> CodedSeed<'b, C, Ctx, K>: DeserializeSeed<'de, Value = ()>,
         for<'b> CodedSeed<'b, C, Ctx, V>: DeserializeSeed<'de, Value = ()>,
     {
-        unimplemented!()
+        self.explore_map(map)
     }
 
     ///
-    pub(crate) fn explore_map_all<'de, A>(self, mut map: A) -> Result<(), A::Error>
+    pub(crate) fn explore_map_all<'de, A>(self, map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+        for<'b> CodedSeed<'b, C, Ctx, K>: DeserializeSeed<'de, Value = ()>,
+        for<'b> CodedSeed<'b, C, Ctx, V>: DeserializeSeed<'de, Value = ()>,
+    {
+        self.explore_map(map)
+    }
+
+    /// Explores each entry whose key is explored by the selector, ignoring
+    /// the rest.
+    fn explore_map<'de, A>(mut self, mut map: A) -> Result<(), A::Error>
     where
         A: MapAccess<'de>,
         for<'b> CodedSeed<'b, C, Ctx, K>: DeserializeSeed<'de, Value = ()>,
         for<'b> CodedSeed<'b, C, Ctx, V>: DeserializeSeed<'de, Value = ()>,
     {
-        unimplemented!()
+        // select the map node
+        if self.0.is_node() {
+            self.0
+                .select_node(SelectedNode::Map)
+                .map_err(A::Error::custom)?;
+        }
+
+        let (selector, state, mut params, ctx) = self.0.into_parts();
+        while let Some(key) = map.next_key::<K>()? {
+            let field = Field::from(key.as_ref());
+            let Some(next) = selector.next_selector(&field) else {
+                map.next_value::<IgnoredAny>()?;
+                continue;
+            };
+
+            SelectorSeed::field_select_seed::<V>(&next, state, &mut params, ctx, field, None)
+                .map_err(A::Error::custom)
+                .and_then(|seed| map.next_value_seed(CodecSeed::<C, _>(seed)))?;
+            state.ascend::<V>().map_err(A::Error::custom)?;
+        }
+
+        Ok(())
     }
 }
 
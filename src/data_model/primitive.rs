@@ -305,6 +305,7 @@ mod num {
 // TODO: unicode normalization? https://ipld.io/docs/data-model/kinds/#string-kind
 mod string {
     use super::*;
+    use std::borrow::Borrow;
     use unicode_normalization::UnicodeNormalization;
 
     ///
@@ -383,6 +384,12 @@ mod string {
 
     impl_selector_seed_serde! { @selector_seed_select {} {} IpldString }
 
+    impl Borrow<str> for IpldString {
+        fn borrow(&self) -> &str {
+            self.0.as_str()
+        }
+    }
+
     impl<'a> From<&'a str> for IpldString {
         #[inline]
         fn from(s: &'a str) -> Self {
@@ -439,20 +446,6 @@ mod string {
         where
             D: Deserializer<'de>,
         {
-            // struct IpldStringVisitor;
-            // impl<'de> Visitor<'de> for IpldStringVisitor {
-            //     type Value = IpldString;
-            //     fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            //         write!(f, "A string or sequence of chars")
-            //     }
-            //     fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
-            //         where
-            //             A: SeqAccess<'de>, {
-            //         let iter = SeqAccessDeserializer::new(seq);
-
-            //     }
-            // }
-
             Ok(Self::from(String::deserialize(deserializer)?))
         }
     }
@@ -539,7 +532,7 @@ mod bytes {
                 }
             }
 
-            Serialize::serialize(self.as_ref(), serializer)
+            serializer.serialize_bytes(self.as_ref())
         }
 
         ///
@@ -617,46 +610,43 @@ mod bytes {
         where
             E: de::Error,
         {
-            match self.0.selector {
-                Selector::Matcher(matcher) => {
-                    let bytes = matcher
-                        .subset
-                        .as_ref()
-                        .map(|Slice { from, to }| &bytes[*from as usize..*to as usize])
-                        .unwrap_or(bytes);
-
-                    match self.0.mode() {
-                        SelectionMode::SelectNode => {
-                            self.0
-                                .select_matched_node(bytes.into(), matcher.label.as_deref())
-                                .map_err(E::custom)?;
-                        }
-                        SelectionMode::SelectDag => {
-                            self.0
-                                .select_matched_dag(
-                                    Bytes::copy_from_slice(bytes),
-                                    matcher.label.as_deref(),
-                                )
-                                .map_err(E::custom)?;
-                        }
-                        _ => unimplemented!(),
+            let selector = self.0.selector;
+            let Some(matcher) = selector.matcher() else {
+                return match selector {
+                    Selector::ExploreInterpretAs(_) => self
+                        .0
+                        .select_interpreted(&Any::Bytes(Bytes::copy_from_slice(bytes)))
+                        .map_err(E::custom),
+                    selector => {
+                        Err(Error::unsupported_selector::<Bytes>(selector)).map_err(E::custom)
                     }
-
-                    // self.nodes.push_back(NodeRow::Match {
-                    //     repr: Adl::Bytes,
-                    //     path: self.path.clone(),
-                    //     value: Value::Blob(bytes.into()),
-                    // });
-
-                    todo!();
-
-                    Ok(())
+                };
+            };
+
+            // slice the borrowed bytes first, so as to only copy the subset
+            let bytes = match &matcher.subset {
+                None => bytes,
+                Some(slice) => match slice.range(bytes.len()) {
+                    Some(range) => &bytes[range],
+                    None => return Ok(()),
+                },
+            };
+
+            match self.0.mode() {
+                SelectionMode::SelectNode => {
+                    self.0
+                        .select_matched_node(bytes.into(), matcher.label.as_deref())
+                        .map_err(E::custom)?;
                 }
-                Selector::ExploreInterpretAs(inner) => {
-                    todo!("what reprs and ADLs are interpreted from byte nodes?")
+                SelectionMode::SelectDag => {
+                    self.0
+                        .select_matched_dag(Bytes::copy_from_slice(bytes), matcher.label.as_deref())
+                        .map_err(E::custom)?;
                 }
-                selector => Err(Error::unsupported_selector::<Bytes>(&selector)).map_err(E::custom),
+                _ => unimplemented!(),
             }
+
+            Ok(())
         }
     }
 
@@ -717,18 +707,18 @@ where
         let matcher = self
             .0
             .selector
-            .as_matcher()
+            .matcher()
             .expect("should know that this is a matcher");
 
         match self.0.mode() {
             SelectionMode::SelectNode => {
                 self.0
-                    .select_matched_node(dag.into(), matcher.label.as_deref())
+                    .select_matched_subset(dag.into(), matcher)
                     .map_err(E::custom)?;
             }
             SelectionMode::SelectDag => {
                 self.0
-                    .select_matched_dag(dag, matcher.label.as_deref())
+                    .select_matched_dag_subset(dag, matcher)
                     .map_err(E::custom)?;
             }
             _ => unimplemented!(),
@@ -834,4 +824,16 @@ mod tests {
     fn test_mull_match() {
         let mut ctx = setup(&Null);
     }
+
+    #[test]
+    fn test_string_from_bytes() {
+        use serde::de::value::{BytesDeserializer, Error as DeError};
+
+        let de = BytesDeserializer::<DeError>::new("héllo".as_bytes());
+        let s = <IpldString as Deserialize>::deserialize(de).expect("should decode utf-8 bytes");
+        assert_eq!(s, IpldString::from("héllo"));
+
+        let de = BytesDeserializer::<DeError>::new(&[0xff, 0xfe]);
+        assert!(<IpldString as Deserialize>::deserialize(de).is_err());
+    }
 }
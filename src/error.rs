@@ -33,12 +33,18 @@ pub enum Error {
     #[error("Unknown multicodec name: {0}")]
     UnknownMulticodecName(String),
 
+    #[error("Multicodec code {0:#x} or name `{1}` is already registered")]
+    DuplicateMulticodec(u64, &'static str),
+
     #[error("IPLD codec encoding error: {0}")]
     Encoder(Box<dyn StdError + Send + Sync + 'static>),
 
     #[error("IPLD codec decoding error: {0}")]
     Decoder(Box<dyn StdError + Send + Sync + 'static>),
 
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
     // #[error("Value error: {0}")]
     // Value(&'static str),
     // #[error("{0}")]
@@ -50,6 +56,14 @@ pub enum Error {
     #[error("Selector Context error: {0}")]
     Context(#[from] anyhow::Error),
 
+    #[error("Block hash mismatch: block does not match its Cid {0:?}")]
+    BlockHashMismatch(Cid),
+
+    /// The block is missing from the [`Context`](crate::selectors::Context), which a
+    /// wrapping context (e.g. a `LayeredContext`) may look for elsewhere.
+    #[error("Block not found for Cid {0:?}")]
+    BlockNotFound(Cid),
+
     #[error("Invalid selection params: {0}")]
     InvalidSelectionParams(&'static str),
 
@@ -87,6 +101,15 @@ pub enum Error {
     #[error("Selector assertion failure")]
     SelectorAssertionFailure,
 
+    #[error("Unknown ADL name: {0}")]
+    UnknownAdl(String),
+
+    #[error("ADL `{0}` is already registered")]
+    DuplicateAdl(&'static str),
+
+    #[error("ADL `{0}` cannot reify node: {1}")]
+    Reification(&'static str, String),
+
     //////////////////////////////////////////////////////////////////////
     // misc
     //////////////////////////////////////////////////////////////////////
@@ -109,13 +132,11 @@ impl Error {
         }
     }
 
-    pub(crate) fn missing_next_selector(selector: &Selector) -> Self {
-        Self::MissingNextSelector(Representation::name(selector))
-    }
-
     pub(crate) fn explore_list_failure(selector: &Selector, current_index: usize) -> Self {
         match selector {
-            Selector::ExploreIndex(_) => Self::ExploreIndexFailure(current_index),
+            Selector::ExploreIndex(_) | Selector::ExploreUnion(_) => {
+                Self::ExploreIndexFailure(current_index)
+            }
             Selector::ExploreRange(s) => Self::ExploreRangeFailure(current_index, s.start, s.end),
             _ => unreachable!(),
         }
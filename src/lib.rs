@@ -12,6 +12,8 @@
 #![warn(rust_2018_idioms, missing_debug_implementations, missing_docs)]
 #[forbid(unsafe_code)]
 //
+#[cfg(feature = "car")]
+mod car;
 #[path = "cid.rs"]
 mod cid_;
 #[path = "codecs/mod.rs"]
@@ -37,12 +39,30 @@ mod specs {
 
     #[cfg(feature = "dag-cbor")]
     pub use crate::codecs_::dag_cbor::DagCbor;
+    #[cfg(feature = "dag-cose")]
+    pub use crate::codecs_::dag_cose::{
+        CoseHeader, CoseLabel, CoseProtectedHeader, CoseSigner, CoseVerifier, DagCose,
+        DagCoseEncrypt0, DagCoseMessage, DagCoseSign1,
+    };
+    #[cfg(feature = "dag-jose")]
+    pub use crate::codecs_::dag_jose::{
+        DagJose, DagJoseEnvelope, DagJoseJwe, DagJoseJws, DagJoseRecipient, DagJoseSignature,
+        JoseKey, JoseKeyResolver, LocalKeys,
+    };
     #[cfg(feature = "dag-json")]
     pub use crate::codecs_::dag_json::DagJson;
-    // #[cfg(feature = "dag-pb")]
-    // pub use crate::codecs_::dag_pb::DagPb;
+    #[cfg(feature = "dag-pb")]
+    pub use crate::codecs_::dag_pb::{DagPb, DagPbLink, DagPbNode};
+    #[cfg(feature = "git")]
+    pub use crate::codecs_::git::{Commit, CommitHeader, GitObject, GitRaw, Tag, Tree, TreeEntry};
+    #[cfg(feature = "raw")]
+    pub use crate::codecs_::raw::Raw;
     #[cfg(feature = "multicodec")]
-    pub use crate::multicodec::Multicodec;
+    pub use crate::multicodec::{CustomCodec, DynCodec, Multicodec};
+
+    // archives
+    #[cfg(feature = "car")]
+    pub use crate::car::{CarContext, CarReader, CarWriter};
 
     // multiformats
     pub use multibase::Base as Multibase;
@@ -62,7 +82,17 @@ mod specs {
     pub use ipld_macros::{ipld_attr, schema};
 
     // selectors
-    pub use crate::selectors::{Context, Params, Select, Selector};
+    pub use crate::selectors::{
+        Adl, BlockLoader, BlockWriter, CacheStats, CachedContext, Context, FlatfsContext,
+        FlatfsWriter, LayeredContext, Params, Reified, Reifier, Select, SelectionIter, Selector,
+        UnknownHashPolicy, VerifyingContext,
+    };
+    #[cfg(feature = "dag-pb")]
+    pub use crate::selectors::UnixFs;
+    #[cfg(feature = "async")]
+    pub use crate::selectors::{AsyncContext, AsyncSelectionContext, SelectionStream};
+    #[cfg(feature = "encryption")]
+    pub use crate::selectors::{CidMode, EncryptedContext};
     pub use ipld_macros::selector;
 }
 
@@ -89,7 +119,12 @@ pub mod dev {
     pub use anyhow;
     #[doc(hidden)]
     pub use bytes;
-    // pub use erased_serde::{Deserializer as ErasedDeserializer, Serializer as ErasedSerializer};
+    #[cfg(feature = "multicodec")]
+    #[doc(hidden)]
+    pub use erased_serde::{
+        self, Deserializer as ErasedDeserializer, Serialize as ErasedSerialize,
+        Serializer as ErasedSerializer,
+    };
     /// Useful macros for aiding in providing bespoke IPLD support.
     pub mod macros {
         pub use crate::impl_selector_seed_serde;
@@ -5,7 +5,7 @@ use std::{
 };
 
 #[cfg(feature = "multicodec")]
-pub use multicodec::Multicodec;
+pub use multicodec::{CustomCodec, DynCodec, Multicodec};
 
 /// An unified trait for all IPLD
 /// [Codec](https://github.com/ipld/specs/blob/master/block-layer/codecs/README.dsmd)s,
@@ -46,6 +46,11 @@ pub trait Codec: TryFrom<u64, Error = Error> {
 #[cfg(feature = "multicodec")]
 mod multicodec {
     use super::*;
+    use std::{
+        collections::BTreeMap,
+        fmt,
+        sync::{Arc, PoisonError, RwLock},
+    };
 
     macro_rules! impl_multicodec {
     ($(
@@ -59,6 +64,8 @@ mod multicodec {
                 $(#[$meta])*
                 $variant($ty),
             )*
+            /// A codec [registered](Multicodec::register) at runtime.
+            Custom(CustomCodec),
         }
 
         impl Multicodec {
@@ -67,6 +74,7 @@ mod multicodec {
             pub const fn name(&self) -> &'static str {
                 match self {
                     $(Self::$variant(_) => $name,)*
+                    Self::Custom(inner) => inner.name,
                 }
             }
 
@@ -75,6 +83,7 @@ mod multicodec {
             pub const fn code(&self) -> u64 {
                 match self {
                     $(Self::$variant(_) => <$ty>::CODE,)*
+                    Self::Custom(inner) => inner.code,
                 }
             }
 
@@ -83,11 +92,12 @@ mod multicodec {
             pub fn from_name(name: &str) -> Result<Self, Error> {
                 match name {
                     $($name => Ok(Self::$variant(<$ty>::new())),)*
-                    name => Err(Error::UnknownMulticodecName(name.to_string()))
+                    name => CustomCodec::from_name(name).map(Self::Custom),
                 }
             }
 
-            ///
+            /// Only finds built-in codecs; use [`Multicodec::try_from`] to
+            /// also find [registered](Multicodec::register) codecs.
             #[inline]
             pub const fn from_code<const C: u64>() -> Result<Self, Error> {
                 match C {
@@ -108,6 +118,7 @@ mod multicodec {
             {
                 match self {
                     $(Self::$variant(inner) => inner.read_with_seed(seed, reader),)*
+                    Self::Custom(inner) => inner.read_with_seed(seed, reader),
                 }
             }
         }
@@ -118,7 +129,7 @@ mod multicodec {
             fn try_from(code: u64) -> Result<Self, Self::Error> {
                 match code {
                     $(<$ty>::CODE => Ok(Self::$variant(<$ty>::default())),)*
-                    _ => Err(Error::UnknownMulticodecCode(code)),
+                    code => CustomCodec::try_from(code).map(Self::Custom),
                 }
             }
         }
@@ -129,7 +140,7 @@ mod multicodec {
             fn try_from(s: &'a str) -> Result<Self, Self::Error> {
                 match s {
                     $($name => Ok(Self::$variant(<$ty>::default())),)*
-                    _ => Err(Error::UnknownMulticodecName(s.into())),
+                    name => CustomCodec::from_name(name).map(Self::Custom),
                 }
             }
         }
@@ -142,6 +153,7 @@ mod multicodec {
             {
                 match self {
                     $(Self::$variant(inner) => inner.write(dag, writer),)*
+                    Self::Custom(inner) => inner.write(dag, writer),
                 }
             }
 
@@ -151,6 +163,7 @@ mod multicodec {
             {
                 match self {
                     $(Self::$variant(inner) => inner.decode(bytes),)*
+                    Self::Custom(inner) => inner.decode(bytes),
                 }
             }
 
@@ -161,19 +174,166 @@ mod multicodec {
             {
                 match self {
                     $(Self::$variant(inner) => inner.read(reader),)*
+                    Self::Custom(inner) => inner.read(reader),
                 }
             }
         }
     }}
 
     impl_multicodec! {
-        // Raw -> Raw as "raw",
+        Raw -> Raw as "raw",
         DagJson -> DagJson as "dag-json",
         DagCbor -> DagCbor as "dag-cbor",
-        // DagJose -> DagJose as "dag-jose",
+        DagPb -> DagPb as "dag-pb",
+        DagJose -> DagJose as "dag-jose",
+        DagCose -> DagCose as "dag-cose",
+        GitRaw -> GitRaw as "git-raw",
         // DagBipf -> DagBipf as "dag-bipf",
         // VerkleDagCbor -> VerkleDagCbor as "verkle-dag-cbor",
-        // Custom(Box<dyn Codec>),
+    }
+
+    /// The codecs registered with [`Multicodec::register`], by code.
+    static REGISTRY: RwLock<BTreeMap<u64, CustomCodec>> = RwLock::new(BTreeMap::new());
+
+    impl Multicodec {
+        /// The codec code that [`Representation`]s are serialized and
+        /// deserialized with when using a [`DynCodec`].
+        pub const DYN_CODE: u64 = u64::MAX;
+
+        /// Registers a [`DynCodec`] under a multicodec code and name, making
+        /// it available to [`Multicodec::try_from`] and
+        /// [`Multicodec::from_name`] (and therefore to [`Cid::multicodec`],
+        /// [`Context`]s and selection).
+        ///
+        /// Fails if the code or name is already in use.
+        pub fn register<D>(code: u64, name: &'static str, codec: D) -> Result<(), Error>
+        where
+            D: DynCodec,
+        {
+            let is_builtin = |codec: Result<Self, Error>| matches!(codec, Ok(codec) if !matches!(codec, Self::Custom(_)));
+            if is_builtin(Self::try_from(code)) || is_builtin(Self::from_name(name)) {
+                return Err(Error::DuplicateMulticodec(code, name));
+            }
+
+            let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+            if registry.contains_key(&code) || registry.values().any(|c| c.name == name) {
+                return Err(Error::DuplicateMulticodec(code, name));
+            }
+
+            let inner = Arc::new(codec);
+            registry.insert(code, CustomCodec { code, name, inner });
+            Ok(())
+        }
+    }
+
+    /// An object-safe IPLD codec, that can be [registered](Multicodec::register)
+    /// at runtime and used wherever a [`Multicodec`] is expected.
+    ///
+    /// Dags are handed to and from the codec as type-erased [`serde`] values,
+    /// (de)serialized with the [`Multicodec::DYN_CODE`] code.
+    pub trait DynCodec: Send + Sync + 'static {
+        /// Given a dag and a `Write`, encode it to the writer.
+        fn write(&self, dag: &dyn ErasedSerialize, writer: &mut dyn Write) -> Result<(), Error>;
+
+        /// Given a `Read`, create a deserializer of its dag and provide it to
+        /// `visit`.
+        fn read<'de>(
+            &self,
+            reader: &mut dyn Read,
+            visit: &mut dyn FnMut(
+                &mut dyn ErasedDeserializer<'de>,
+            ) -> Result<(), erased_serde::Error>,
+        ) -> Result<(), Error>;
+    }
+
+    /// A [`DynCodec`] [registered](Multicodec::register) with a multicodec code
+    /// and name.
+    #[derive(Clone)]
+    pub struct CustomCodec {
+        code: u64,
+        name: &'static str,
+        inner: Arc<dyn DynCodec>,
+    }
+
+    impl CustomCodec {
+        fn from_name(name: &str) -> Result<Self, Error> {
+            REGISTRY
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .values()
+                .find(|codec| codec.name == name)
+                .cloned()
+                .ok_or_else(|| Error::UnknownMulticodecName(name.into()))
+        }
+
+        pub(crate) fn read_with_seed<'de, S, R>(
+            &mut self,
+            seed: S,
+            mut reader: R,
+        ) -> Result<(), Error>
+        where
+            S: CodecDeserializeSeed<'de>,
+            R: Read,
+        {
+            let mut seed = Some(seed);
+            self.inner.read(&mut reader, &mut |de| match seed.take() {
+                Some(seed) => seed.deserialize::<{ Multicodec::DYN_CODE }, _>(de),
+                None => Err(de::Error::custom("dag was already deserialized")),
+            })
+        }
+    }
+
+    impl Codec for CustomCodec {
+        fn write<T, W>(&mut self, dag: &T, mut writer: W) -> Result<(), Error>
+        where
+            T: Representation,
+            W: Write,
+        {
+            let dag = EncoderElem::<'_, { Multicodec::DYN_CODE }, T>(dag);
+            self.inner.write(&dag, &mut writer)
+        }
+
+        fn read<T, R>(&mut self, mut reader: R) -> Result<T, Error>
+        where
+            T: Representation,
+            R: Read,
+        {
+            let mut dag = None;
+            self.inner.read(&mut reader, &mut |de| {
+                dag = Some(<T as Representation>::deserialize::<
+                    { Multicodec::DYN_CODE },
+                    _,
+                >(de)?);
+                Ok(())
+            })?;
+            dag.ok_or_else(|| {
+                Error::decoder(<erased_serde::Error as de::Error>::custom(format!(
+                    "codec `{}` did not deserialize a dag",
+                    self.name
+                )))
+            })
+        }
+    }
+
+    impl TryFrom<u64> for CustomCodec {
+        type Error = Error;
+        fn try_from(code: u64) -> Result<Self, Self::Error> {
+            REGISTRY
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&code)
+                .cloned()
+                .ok_or(Error::UnknownMulticodecCode(code))
+        }
+    }
+
+    impl fmt::Debug for CustomCodec {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("CustomCodec")
+                .field("code", &self.code)
+                .field("name", &self.name)
+                .finish()
+        }
     }
 
     impl<'a, const S: usize> TryFrom<&'a CidGeneric<S>> for Multicodec {
@@ -189,6 +349,90 @@ mod multicodec {
             Multicodec::try_from(cid.multicodec_code())
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Plain JSON, without DAG-JSON's special handling of links and bytes.
+        struct Json;
+
+        impl DynCodec for Json {
+            fn write(
+                &self,
+                dag: &dyn ErasedSerialize,
+                writer: &mut dyn Write,
+            ) -> Result<(), Error> {
+                let mut ser = serde_json::Serializer::new(writer);
+                erased_serde::serialize(dag, &mut ser).map_err(Error::encoder)
+            }
+
+            fn read<'de>(
+                &self,
+                reader: &mut dyn Read,
+                visit: &mut dyn FnMut(
+                    &mut dyn ErasedDeserializer<'de>,
+                ) -> Result<(), erased_serde::Error>,
+            ) -> Result<(), Error> {
+                let mut de = serde_json::Deserializer::from_reader(reader);
+                visit(&mut <dyn ErasedDeserializer<'_>>::erase(&mut de)).map_err(Error::decoder)?;
+                de.end().map_err(Error::decoder)
+            }
+        }
+
+        const JSON: u64 = 0x0200;
+
+        #[test]
+        fn test_register() {
+            Multicodec::register(JSON, "json", Json).unwrap();
+            assert!(Multicodec::register(JSON, "other-json", Json).is_err());
+            assert!(Multicodec::register(0x0201, "json", Json).is_err());
+            assert!(Multicodec::register(DagCbor::CODE, "cbor", Json).is_err());
+            assert!(Multicodec::register(0x0201, "dag-cbor", Json).is_err());
+
+            let mut codec = Multicodec::try_from(JSON).unwrap();
+            assert_eq!(codec.code(), JSON);
+            assert_eq!(codec.name(), "json");
+            assert_eq!(Multicodec::from_name("json").unwrap().code(), JSON);
+            assert_eq!(Multicodec::try_from("json").unwrap().code(), JSON);
+
+            // encoding and decoding
+            let dag = List::from([Any::Int(1), Any::String("a".into()), Any::Bool(true)]);
+            let bytes = codec.encode(&dag).unwrap();
+            assert_eq!(bytes, br#"[1,"a",true]"#);
+            assert_eq!(codec.decode::<List<Any>>(&bytes).unwrap(), dag);
+            assert!(codec.decode::<List<Any>>(b"[1,").is_err());
+
+            // resolving and selecting
+            let mut ctx = MemoryContext::default();
+            let cid = ctx
+                .add_block(Version::V1, JSON, Multihash::SHA2_256, bytes)
+                .unwrap();
+            assert_eq!(cid.multicodec().unwrap().name(), "json");
+
+            let mut link = Link::<List<Any>>::Cid(cid);
+            assert_eq!(link.resolve(&mut ctx).unwrap(), &dag);
+
+            let cid = ctx
+                .add_block(
+                    Version::V1,
+                    JSON,
+                    Multihash::SHA2_256,
+                    br#""leaf""#.to_vec(),
+                )
+                .unwrap();
+            let selected: IpldString = Params::<'_, _, IpldString>::new_select(cid)
+                .into_dag_iter(&mut ctx)
+                .unwrap()
+                .next()
+                .unwrap()
+                .unwrap()
+                .dag
+                .downcast()
+                .unwrap();
+            assert_eq!(selected, IpldString::from("leaf"));
+        }
+    }
 }
 
 #[cfg(feature = "skipped")]
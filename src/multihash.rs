@@ -85,6 +85,8 @@ macro_rules! impl_multihasher {
 }
 
 impl_multihasher! {@multihash
+    ///
+    Sha1 (SHA1: 0x11) -> multihash::Sha1 [20],
     ///
     Sha2_256 (SHA2_256: 0x12) -> multihash::Sha2_256 [32],
     ///
@@ -0,0 +1,147 @@
+//! Advanced Data Layouts (ADLs), which reify a node (their "substrate") into
+//! a logical view of another shape, to be explored by an
+//! [`ExploreInterpretAs`] selector.
+
+#[cfg(feature = "dag-pb")]
+mod unixfs;
+
+#[cfg(feature = "dag-pb")]
+pub use unixfs::UnixFs;
+
+use crate::dev::*;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+/// Loads the bytes of a block, for [`Reifier`]s of ADLs that span more than
+/// one block.
+pub type BlockLoader<'a> = dyn FnMut(&Cid) -> Result<Vec<u8>, Error> + 'a;
+
+/// An object-safe reifier of an ADL, that can be
+/// [registered](Adl::register) by name and used wherever an
+/// [`ExploreInterpretAs`] selector names it.
+pub trait Reifier: Send + Sync + 'static {
+    /// Given the data model view of a substrate node, produce the ADL's
+    /// logical view of it (e.g. a map or bytes), loading any other blocks
+    /// required with `load`.
+    ///
+    /// `next` is the selector that will explore the reified node, so only
+    /// the parts of the node it explores need to be resolved, e.g. only the
+    /// entries of a map named by an [`ExploreFields`], or only the
+    /// [`subset`](Matcher::subset) of bytes matched by a [`Matcher`].
+    ///
+    /// When the substrate is the root of a block, it is provided as an
+    /// [`Any::Link`] to the block, so that the reifier can decode it as it
+    /// sees fit.
+    fn reify(
+        &self,
+        node: &Any,
+        next: &Selector,
+        load: &mut BlockLoader<'_>,
+    ) -> Result<Reified, Error>;
+}
+
+/// The logical view of a substrate node, as produced by a [`Reifier`].
+#[derive(Clone, Debug)]
+pub enum Reified {
+    /// The reified node, which may omit anything that the next selector
+    /// does not explore.
+    Node(Any),
+
+    /// The bytes (or string) of the reified node within the
+    /// [`subset`](Matcher::subset) of the next [`Matcher`], or `None` if the
+    /// subset matches nothing.
+    Subset(Option<Any>),
+}
+
+/// The reifiers registered with [`Adl::register`], by name.
+static REGISTRY: RwLock<BTreeMap<&'static str, Adl>> = RwLock::new(BTreeMap::new());
+
+/// A named [`Reifier`], either built-in or [registered](Adl::register) at
+/// runtime.
+///
+/// Built-in ADLs:
+/// - `"unixfs"`: [`UnixFs`] (requires the `dag-pb` feature), including its
+///   HAMT-sharded directories
+///
+/// Any other ADL, such as a standalone (e.g. dag-cbor) HAMT, is not built-in
+/// and must be [registered](Adl::register) before it can be used.
+#[derive(Clone)]
+pub struct Adl {
+    name: &'static str,
+    inner: Arc<dyn Reifier>,
+}
+
+impl Adl {
+    /// Registers a [`Reifier`] under a name, making it available to
+    /// [`Adl::from_name`] (and therefore to [`ExploreInterpretAs`]
+    /// selectors).
+    ///
+    /// Fails if the name is already in use.
+    pub fn register<R>(name: &'static str, reifier: R) -> Result<(), Error>
+    where
+        R: Reifier,
+    {
+        if Self::builtin(name).is_some() {
+            return Err(Error::DuplicateAdl(name));
+        }
+
+        let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+        if registry.contains_key(name) {
+            return Err(Error::DuplicateAdl(name));
+        }
+
+        let inner = Arc::new(reifier);
+        registry.insert(name, Self { name, inner });
+        Ok(())
+    }
+
+    /// Looks up a built-in or registered ADL by name.
+    pub fn from_name(name: &str) -> Result<Self, Error> {
+        if let Some(adl) = Self::builtin(name) {
+            return Ok(adl);
+        }
+
+        REGISTRY
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownAdl(name.into()))
+    }
+
+    /// The name of the ADL.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Reifies the substrate node into the ADL's logical view, as explored
+    /// by `next`.
+    pub fn reify(
+        &self,
+        node: &Any,
+        next: &Selector,
+        load: &mut BlockLoader<'_>,
+    ) -> Result<Reified, Error> {
+        self.inner.reify(node, next, load)
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            #[cfg(feature = "dag-pb")]
+            UnixFs::NAME => Some(Self {
+                name: UnixFs::NAME,
+                inner: Arc::new(UnixFs),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for Adl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Adl").field("name", &self.name).finish()
+    }
+}
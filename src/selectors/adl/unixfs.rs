@@ -0,0 +1,794 @@
+//! The UnixFS ADL.
+
+use super::*;
+use crate::codecs_::dag_pb::pb;
+use std::{borrow::Cow, ops::Range, rc::Rc};
+
+/// The [UnixFS](https://github.com/ipfs/specs/blob/main/UNIXFS.md) ADL,
+/// which reifies [`DagPb`] (and raw) blocks into:
+/// - files: the bytes of the file, concatenated from all of its chunks,
+/// - directories, including HAMT-sharded directories: a map of entry names to
+///   links,
+/// - symlinks: the string of the link's target.
+///
+/// Only the blocks needed by the next selector are loaded: a [`Matcher`] with
+/// a [`subset`](Matcher::subset) only loads the chunks of a file within the
+/// subset, and an [`ExploreFields`] only loads the shards of a sharded
+/// directory along the hashes of the names it explores. Files and shards are
+/// walked iteratively, and fail to reify if nested more than
+/// [`UnixFs::MAX_DEPTH`] blocks deep.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UnixFs;
+
+/// A UnixFS node, as either a raw block or a [`DagPbNode`].
+enum Substrate {
+    Raw(Bytes),
+    Node(DagPbNode),
+}
+
+/// The relevant fields of a UnixFS `Data` message.
+#[derive(Default)]
+struct Data {
+    r#type: u64,
+    data: Option<Bytes>,
+    filesize: Option<u64>,
+    blocksizes: Vec<u64>,
+    fanout: Option<u64>,
+}
+
+/// The next chunk of a file to read or skip, in order.
+enum Chunk {
+    Load(Cid, usize),
+    Skip(usize),
+}
+
+impl UnixFs {
+    /// The name that [`ExploreInterpretAs`] selectors use for this ADL.
+    pub const NAME: &'static str = "unixfs";
+
+    /// The maximum depth of the chunks of a file, or of the shards of a
+    /// sharded directory.
+    pub const MAX_DEPTH: usize = 64;
+
+    /// The multicodec code of raw leaf blocks.
+    const RAW_CODE: u64 = 0x55;
+
+    const RAW: u64 = 0;
+    const DIRECTORY: u64 = 1;
+    const FILE: u64 = 2;
+    const SYMLINK: u64 = 4;
+    const HAMT_SHARD: u64 = 5;
+
+    fn substrate(node: &Any, load: &mut BlockLoader<'_>) -> Result<Substrate, Error> {
+        match node {
+            Any::Link(link) => match link.as_ref() {
+                Link::Cid(cid) => Self::load(cid, load),
+                Link::Inner { t, .. } => Self::substrate(t, load),
+            },
+            Any::Bytes(bytes) => Ok(Substrate::Raw(bytes.clone())),
+            node => pb_node(node).map(Substrate::Node),
+        }
+    }
+
+    fn load(cid: &Cid, load: &mut BlockLoader<'_>) -> Result<Substrate, Error> {
+        let block = load(cid)?;
+        match cid.multicodec_code() {
+            DagPb::CODE => DagPb::new().decode(&block).map(Substrate::Node),
+            Self::RAW_CODE => Ok(Substrate::Raw(Bytes::from(block))),
+            code => Err(err(format!("unsupported block multicodec {:#x}", code))),
+        }
+    }
+
+    fn reify_node(
+        node: DagPbNode,
+        next: &Selector,
+        load: &mut BlockLoader<'_>,
+    ) -> Result<Reified, Error> {
+        let data = Data::decode(&node)?;
+        match data.r#type {
+            Self::RAW | Self::FILE => Self::reify_file(Substrate::Node(node), next, load),
+            Self::DIRECTORY => Ok(Reified::Node(Any::Map(
+                node.links
+                    .iter()
+                    .map(|link| (link.name.clone().unwrap_or_default(), entry(link)))
+                    .collect(),
+            ))),
+            Self::HAMT_SHARD => {
+                let entries = match next.explored_keys() {
+                    Some(names) => Self::shard_lookup(&node, data.fanout, &names, load)?,
+                    None => Self::shard_entries(node, data.fanout, load)?,
+                };
+                Ok(Reified::Node(Any::Map(entries)))
+            }
+            Self::SYMLINK => {
+                let target = data.data.unwrap_or_default();
+                std::str::from_utf8(&target)
+                    .map(|target| Reified::Node(Any::String(target.into())))
+                    .map_err(|_| err("symlink target is not UTF-8"))
+            }
+            r#type => Err(err(format!("unsupported node type {}", r#type))),
+        }
+    }
+
+    /// Reifies a file as its bytes, or only the subset of them matched by the
+    /// next selector.
+    fn reify_file(
+        file: Substrate,
+        next: &Selector,
+        load: &mut BlockLoader<'_>,
+    ) -> Result<Reified, Error> {
+        let Some(slice) = next.matcher().and_then(|matcher| matcher.subset.as_ref()) else {
+            let bytes = Self::read_file(file, 0..usize::MAX, load)?;
+            return Ok(Reified::Node(Any::Bytes(bytes)));
+        };
+
+        let offset = |offset: Int| usize::try_from(offset).unwrap_or(usize::MAX);
+        let range = if slice.from >= 0 && slice.to >= 0 {
+            // positive offsets are clamped to the end of the file as it's read
+            (slice.from <= slice.to).then(|| offset(slice.from)..offset(slice.to))
+        } else if let Some(len) = Self::file_len(&file)? {
+            slice.range(len)
+        } else {
+            // without its length, the file must be read whole to be sliced
+            let bytes = Self::read_file(file, 0..usize::MAX, load)?;
+            let subset = slice.range(bytes.len()).map(|range| bytes.slice(range));
+            return Ok(Reified::Subset(subset.map(Any::Bytes)));
+        };
+
+        let subset = range
+            .map(|range| Self::read_file(file, range, load))
+            .transpose()?;
+        Ok(Reified::Subset(subset.map(Any::Bytes)))
+    }
+
+    /// The length of a file, if known from its root alone.
+    fn file_len(file: &Substrate) -> Result<Option<usize>, Error> {
+        let node = match file {
+            Substrate::Raw(raw) => return Ok(Some(raw.len())),
+            Substrate::Node(node) => node,
+        };
+
+        let data = Data::decode(node)?;
+        let len = match data.filesize {
+            Some(filesize) => filesize,
+            None if data.blocksizes.len() == node.links.len() => data.blocksizes.iter().fold(
+                data.data.as_ref().map_or(0, Bytes::len) as u64,
+                |len, size| len.saturating_add(*size),
+            ),
+            None => return Ok(None),
+        };
+        Ok(Some(usize::try_from(len).unwrap_or(usize::MAX)))
+    }
+
+    /// Reads the bytes of a file within `range`, walking its chunks in order
+    /// and skipping (without loading) any chunks whose `blocksizes` place
+    /// them outside of the range.
+    fn read_file(
+        file: Substrate,
+        range: Range<usize>,
+        load: &mut BlockLoader<'_>,
+    ) -> Result<Bytes, Error> {
+        let mut bytes = Vec::new();
+        let mut pos = 0;
+        let mut read = |chunk: &[u8], pos: &mut usize| {
+            let start = range.start.saturating_sub(*pos).min(chunk.len());
+            let end = range.end.saturating_sub(*pos).clamp(start, chunk.len());
+            bytes.extend_from_slice(&chunk[start..end]);
+            *pos = pos.saturating_add(chunk.len());
+        };
+
+        // the remaining chunks, in reverse order
+        let mut chunks = Vec::new();
+        let mut next = Some((file, 0));
+        while let Some((substrate, depth)) = next.take() {
+            let node = match substrate {
+                Substrate::Raw(raw) => {
+                    read(&raw, &mut pos);
+                    None
+                }
+                Substrate::Node(node) => Some(node),
+            };
+
+            if let Some(node) = node {
+                let data = Data::decode(&node)?;
+                if !matches!(data.r#type, Self::RAW | Self::FILE) {
+                    return Err(err("file chunk is not a file"));
+                }
+                read(data.data.as_deref().unwrap_or_default(), &mut pos);
+
+                let sizes = match data.blocksizes.len() {
+                    0 => vec![None; node.links.len()],
+                    len if len == node.links.len() => data
+                        .blocksizes
+                        .iter()
+                        .map(|size| Some(usize::try_from(*size).unwrap_or(usize::MAX)))
+                        .collect(),
+                    _ => return Err(err("file chunk's blocksizes do not match its links")),
+                };
+
+                let mut child_pos = pos;
+                let children = node.links.iter().zip(sizes).map(|(link, size)| {
+                    let start = child_pos;
+                    child_pos = child_pos.saturating_add(size.unwrap_or(0));
+                    match size {
+                        Some(size) if start >= range.end || child_pos <= range.start => {
+                            Chunk::Skip(size)
+                        }
+                        _ => Chunk::Load(link.hash, depth + 1),
+                    }
+                });
+                let children = children.collect::<Vec<_>>();
+                chunks.extend(children.into_iter().rev());
+            }
+
+            while next.is_none() && pos < range.end {
+                match chunks.pop() {
+                    Some(Chunk::Skip(size)) => pos = pos.saturating_add(size),
+                    Some(Chunk::Load(_, depth)) if depth > Self::MAX_DEPTH => {
+                        return Err(err("file exceeds the max depth"));
+                    }
+                    Some(Chunk::Load(cid, depth)) => next = Some((Self::load(&cid, load)?, depth)),
+                    None => break,
+                }
+            }
+        }
+
+        Ok(Bytes::from(bytes))
+    }
+
+    /// Collects all entries of a HAMT shard and its child shards, whose link
+    /// names are prefixed with the entry's uppercase hex bucket index.
+    fn shard_entries(
+        root: DagPbNode,
+        fanout: Option<u64>,
+        load: &mut BlockLoader<'_>,
+    ) -> Result<Map, Error> {
+        let mut entries = Map::new();
+        let mut shards = vec![(root, fanout, 0)];
+        while let Some((shard, fanout, depth)) = shards.pop() {
+            let (_, prefix_len) = Self::shard_params(fanout)?;
+            for link in &shard.links {
+                match link_name(link).get(prefix_len..) {
+                    Some("") if depth >= Self::MAX_DEPTH => {
+                        return Err(err("HAMT shard exceeds the max depth"));
+                    }
+                    Some("") => {
+                        let (child, data) = Self::load_shard(&link.hash, load)?;
+                        shards.push((child, data.fanout, depth + 1));
+                    }
+                    Some(name) => {
+                        entries.insert(name.into(), entry(link));
+                    }
+                    None => return Err(err("HAMT shard link name is missing its prefix")),
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Looks up entries of a HAMT shard by name, only loading the child
+    /// shards along the hash of each name.
+    fn shard_lookup(
+        root: &DagPbNode,
+        fanout: Option<u64>,
+        names: &[&str],
+        load: &mut BlockLoader<'_>,
+    ) -> Result<Map, Error> {
+        let mut entries = Map::new();
+        for name in names {
+            let hash = murmur3_x64_64(name.as_bytes());
+            let (mut shard, mut fanout, mut consumed) = (Cow::Borrowed(root), fanout, 0);
+            loop {
+                // each shard indexes its links by the next bits of the hash
+                let (bits, prefix_len) = Self::shard_params(fanout)?;
+                if consumed + bits > u64::BITS {
+                    return Err(err("HAMT shard exceeds the max depth"));
+                }
+                let index = (hash << consumed) >> (u64::BITS - bits);
+                let prefix = format!("{:0width$X}", index, width = prefix_len);
+
+                let link = shard.links.iter().find(|link| {
+                    link_name(link)
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.is_empty() || rest == *name)
+                });
+                match link {
+                    Some(link) if link_name(link).len() == prefix_len => {
+                        let (child, data) = Self::load_shard(&link.hash, load)?;
+                        (shard, fanout, consumed) =
+                            (Cow::Owned(child), data.fanout, consumed + bits);
+                    }
+                    Some(link) => {
+                        entries.insert((*name).into(), entry(link));
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// The number of hash bits consumed by, and the length of the link name
+    /// prefixes of, a shard with the given fanout.
+    fn shard_params(fanout: Option<u64>) -> Result<(u32, usize), Error> {
+        let fanout = fanout
+            .filter(|fanout| *fanout > 1 && fanout.is_power_of_two())
+            .ok_or_else(|| err("HAMT shard has an invalid fanout"))?;
+        Ok((fanout.trailing_zeros(), format!("{:X}", fanout - 1).len()))
+    }
+
+    fn load_shard(cid: &Cid, load: &mut BlockLoader<'_>) -> Result<(DagPbNode, Data), Error> {
+        if let Substrate::Node(node) = Self::load(cid, load)? {
+            let data = Data::decode(&node)?;
+            if data.r#type == Self::HAMT_SHARD {
+                return Ok((node, data));
+            }
+        }
+        Err(err("HAMT shard child is not a shard"))
+    }
+}
+
+impl Reifier for UnixFs {
+    fn reify(
+        &self,
+        node: &Any,
+        next: &Selector,
+        load: &mut BlockLoader<'_>,
+    ) -> Result<Reified, Error> {
+        match Self::substrate(node, load)? {
+            Substrate::Raw(bytes) => Self::reify_file(Substrate::Raw(bytes), next, load),
+            Substrate::Node(node) => Self::reify_node(node, next, load),
+        }
+    }
+}
+
+impl Data {
+    const TYPE: u64 = 1;
+    const DATA: u64 = 2;
+    const FILESIZE: u64 = 3;
+    const BLOCKSIZES: u64 = 4;
+    const FANOUT: u64 = 6;
+
+    /// Decodes the `Data` message of a node, skipping any fields irrelevant
+    /// to reification.
+    fn decode(node: &DagPbNode) -> Result<Self, Error> {
+        let mut bytes = node
+            .data
+            .as_deref()
+            .ok_or_else(|| err("node is missing its `Data`"))?;
+        let mut data = Self::default();
+        let mut has_type = false;
+        while !bytes.is_empty() {
+            match pb::read_key(&mut bytes)? {
+                (Self::TYPE, pb::WIRE_VARINT) => {
+                    data.r#type = pb::read_varint(&mut bytes)?;
+                    has_type = true;
+                }
+                (Self::DATA, pb::WIRE_LEN) => {
+                    let chunk = pb::read_len_delimited(&mut bytes)?;
+                    data.data = Some(Bytes::copy_from_slice(chunk));
+                }
+                (Self::FILESIZE, pb::WIRE_VARINT) => {
+                    data.filesize = Some(pb::read_varint(&mut bytes)?);
+                }
+                (Self::BLOCKSIZES, pb::WIRE_VARINT) => {
+                    data.blocksizes.push(pb::read_varint(&mut bytes)?);
+                }
+                (Self::BLOCKSIZES, pb::WIRE_LEN) => {
+                    let mut packed = pb::read_len_delimited(&mut bytes)?;
+                    while !packed.is_empty() {
+                        data.blocksizes.push(pb::read_varint(&mut packed)?);
+                    }
+                }
+                (Self::FANOUT, pb::WIRE_VARINT) => {
+                    data.fanout = Some(pb::read_varint(&mut bytes)?);
+                }
+                (_, pb::WIRE_VARINT) => {
+                    pb::read_varint(&mut bytes)?;
+                }
+                (_, pb::WIRE_LEN) => {
+                    pb::read_len_delimited(&mut bytes)?;
+                }
+                _ => return Err(err("unexpected wire type in `Data`")),
+            }
+        }
+
+        has_type
+            .then_some(data)
+            .ok_or_else(|| err("`Data` is missing its `Type`"))
+    }
+}
+
+/// Reads a [`DagPbNode`] from its data model view, accepting link `Hash`es
+/// as either links or (as decoded into an [`Any`]) bytes.
+fn pb_node(node: &Any) -> Result<DagPbNode, Error> {
+    let map = match node {
+        Any::Map(map) => map,
+        _ => return Err(err("node is not a dag-pb node")),
+    };
+
+    let data = match map.get("Data") {
+        Some(Any::Bytes(data)) => Some(data.clone()),
+        None | Some(Any::Null(_)) => None,
+        _ => return Err(err("node `Data` is not bytes")),
+    };
+    let links = match map.get("Links") {
+        Some(Any::List(links)) => links.iter().map(pb_link).collect::<Result<_, _>>()?,
+        None => List::new(),
+        _ => return Err(err("node `Links` is not a list")),
+    };
+    Ok(DagPbNode { links, data })
+}
+
+fn pb_link(link: &Any) -> Result<DagPbLink, Error> {
+    let map = match link {
+        Any::Map(map) => map,
+        _ => return Err(err("node link is not a map")),
+    };
+
+    let hash = match map.get("Hash") {
+        Some(Any::Link(link)) => *link.cid(),
+        Some(Any::Bytes(bytes)) => Cid::try_from(bytes.as_ref())?,
+        _ => return Err(err("node link `Hash` is not a link")),
+    };
+    let name = match map.get("Name") {
+        Some(Any::String(name)) => Some(name.clone()),
+        None | Some(Any::Null(_)) => None,
+        _ => return Err(err("node link `Name` is not a string")),
+    };
+    Ok(DagPbLink {
+        hash,
+        name,
+        tsize: None,
+    })
+}
+
+fn link_name(link: &DagPbLink) -> &str {
+    link.name.as_ref().map(IpldString::as_str).unwrap_or("")
+}
+
+/// The entry of a (sharded) directory, as a link to its node.
+fn entry(link: &DagPbLink) -> Any {
+    Any::Link(Rc::new(Link::Cid(link.hash)))
+}
+
+fn err(msg: impl ToString) -> Error {
+    Error::Reification(UnixFs::NAME, msg.to_string())
+}
+
+/// The 64-bit murmur3 hash (i.e. the first half of the x64 128-bit variant,
+/// with a seed of 0), by which HAMT shards index their entries' names.
+fn murmur3_x64_64(bytes: &[u8]) -> u64 {
+    const C1: u64 = 0x87c3_7b91_1142_53d5;
+    const C2: u64 = 0x4cf5_ad43_2745_937f;
+    let fmix = |mut k: u64| {
+        k ^= k >> 33;
+        k = k.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        k ^= k >> 33;
+        k = k.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        k ^ (k >> 33)
+    };
+    let mix_k1 = |k1: u64| k1.wrapping_mul(C1).rotate_left(31).wrapping_mul(C2);
+    let mix_k2 = |k2: u64| k2.wrapping_mul(C2).rotate_left(33).wrapping_mul(C1);
+    let read_u64 = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .rev()
+            .fold(0, |word, byte| (word << 8) | u64::from(*byte))
+    };
+
+    let (mut h1, mut h2) = (0u64, 0u64);
+    let mut blocks = bytes.chunks_exact(16);
+    for block in &mut blocks {
+        h1 ^= mix_k1(read_u64(&block[..8]));
+        h1 = h1
+            .rotate_left(27)
+            .wrapping_add(h2)
+            .wrapping_mul(5)
+            .wrapping_add(0x52dc_e729);
+        h2 ^= mix_k2(read_u64(&block[8..]));
+        h2 = h2
+            .rotate_left(31)
+            .wrapping_add(h1)
+            .wrapping_mul(5)
+            .wrapping_add(0x3849_5ab5);
+    }
+
+    let tail = blocks.remainder();
+    if tail.len() > 8 {
+        h2 ^= mix_k2(read_u64(&tail[8..]));
+    }
+    if !tail.is_empty() {
+        h1 ^= mix_k1(read_u64(&tail[..tail.len().min(8)]));
+    }
+
+    h1 ^= bytes.len() as u64;
+    h2 ^= bytes.len() as u64;
+    h1 = h1.wrapping_add(h2);
+    h2 = h2.wrapping_add(h1);
+    h1 = fmix(h1);
+    h2 = fmix(h2);
+    h1.wrapping_add(h2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        path::PathBuf,
+    };
+
+    /// Encodes a UnixFS `Data` message.
+    fn data(r#type: u8, data: &[u8], blocksizes: &[u8], fanout: Option<u8>) -> Option<Bytes> {
+        let mut bytes = vec![0x08, r#type];
+        if !data.is_empty() {
+            bytes.extend([0x12, data.len() as u8]);
+            bytes.extend_from_slice(data);
+        }
+        for size in blocksizes {
+            bytes.extend([0x20, *size]);
+        }
+        if let Some(fanout) = fanout {
+            bytes.extend([0x30, fanout]);
+        }
+        Some(Bytes::from(bytes))
+    }
+
+    fn link(hash: Cid, name: &str) -> DagPbLink {
+        DagPbLink {
+            name: Some(name.into()),
+            ..DagPbLink::new(hash)
+        }
+    }
+
+    fn add_raw(ctx: &mut MemoryContext, bytes: &[u8]) -> Cid {
+        let mut writer = ctx
+            .block_writer(UnixFs::RAW_CODE, Multihash::SHA2_256)
+            .unwrap();
+        writer.write_all(bytes).unwrap();
+        ctx.flush_block(writer).unwrap()
+    }
+
+    fn add_node(ctx: &mut MemoryContext, links: Vec<DagPbLink>, data: Option<Bytes>) -> Cid {
+        ctx.add_dag(&DagPbNode { links, data }, DagPb::CODE, Multihash::SHA2_256)
+            .unwrap()
+    }
+
+    /// Reifies the block with the next selector, returning the blocks loaded.
+    fn reify(
+        ctx: &mut MemoryContext,
+        root: Cid,
+        next: &Selector,
+    ) -> (Result<Reified, Error>, Vec<Cid>) {
+        let mut loaded = vec![];
+        let reified = UnixFs.reify(&Any::Link(Rc::new(Link::Cid(root))), next, &mut |cid| {
+            loaded.push(*cid);
+            let mut block = vec![];
+            ctx.block_reader(cid)?
+                .read_to_end(&mut block)
+                .map_err(|err| Error::Decoder(err.into()))?;
+            Ok(block)
+        });
+        (reified, loaded)
+    }
+
+    fn interpret_as_unixfs(next: Selector) -> Selector {
+        Selector::ExploreInterpretAs(Rc::new(ExploreInterpretAs {
+            r#as: UnixFs::NAME.into(),
+            next,
+        }))
+    }
+
+    fn subset_matcher(from: Int, to: Int) -> Selector {
+        Selector::Matcher(Matcher {
+            subset: Some(Slice { from, to }),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_murmur3() {
+        assert_eq!(murmur3_x64_64(b""), 0);
+        assert_eq!(murmur3_x64_64(b"hello"), 0xcbd8_a7b3_41bd_9b02);
+        assert_eq!(
+            murmur3_x64_64(b"The quick brown fox jumps over the lazy dog"),
+            0xe34b_bc7b_bc07_1b6c
+        );
+    }
+
+    #[test]
+    fn test_file_subset() {
+        let mut ctx = MemoryContext::default();
+
+        // "ab", followed by chunks "cdef", "ghij" and "kl"
+        let chunks = [
+            add_raw(&mut ctx, b"cdef"),
+            add_node(&mut ctx, vec![], data(2, b"ghij", &[], None)),
+            add_raw(&mut ctx, b"kl"),
+        ];
+        let root = add_node(
+            &mut ctx,
+            chunks.iter().copied().map(DagPbLink::new).collect(),
+            data(2, b"ab", &[4, 4, 2], None),
+        );
+
+        // only the chunks within the subset are loaded
+        for ((from, to), expected, loaded) in [
+            ((3, 7), Some(&b"defg"[..]), &chunks[..2]),
+            ((-3, -1), Some(b"jk"), &chunks[1..]),
+            ((0, 2), Some(b"ab"), &[]),
+            ((20, 30), Some(b""), &[]),
+            ((5, 2), None, &[]),
+        ] {
+            let (reified, cids) = reify(&mut ctx, root, &subset_matcher(from, to));
+            match reified.unwrap() {
+                Reified::Subset(Some(Any::Bytes(bytes))) => {
+                    assert_eq!(Some(bytes.as_ref()), expected)
+                }
+                Reified::Subset(None) => assert_eq!(expected, None),
+                reified => panic!("unexpected reification {:?}", reified),
+            }
+            assert_eq!(cids[0], root);
+            assert_eq!(&cids[1..], loaded);
+        }
+
+        // ... or the entire file without a subset
+        let (reified, cids) = reify(&mut ctx, root, &Selector::DEFAULT);
+        assert!(matches!(
+            reified.unwrap(),
+            Reified::Node(Any::Bytes(bytes)) if bytes.as_ref() == b"abcdefghijkl"
+        ));
+        assert_eq!(cids.len(), 4);
+
+        // the subset is only applied once when selecting
+        let selector = interpret_as_unixfs(subset_matcher(3, 7));
+        let selected = Params::<'_, _, Any>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(true, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| node.node))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(matches!(
+            selected.as_slice(),
+            [SelectedNode::Bytes(bytes)] if bytes.as_ref() == b"defg"
+        ));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut ctx = MemoryContext::default();
+        let mut file = add_raw(&mut ctx, b"x");
+        for _ in 0..=UnixFs::MAX_DEPTH {
+            file = add_node(
+                &mut ctx,
+                vec![DagPbLink::new(file)],
+                data(2, b"", &[1], None),
+            );
+        }
+
+        let (reified, _) = reify(&mut ctx, file, &Selector::DEFAULT);
+        assert!(matches!(reified, Err(Error::Reification(UnixFs::NAME, _))));
+    }
+
+    #[test]
+    fn test_sharded_directory() {
+        let mut ctx = MemoryContext::default();
+
+        // a file of a raw leaf and a dag-pb leaf, and a single-block file
+        let raw_leaf = add_raw(&mut ctx, b"hello ");
+        let leaf = add_node(&mut ctx, vec![], data(2, b"world", &[], None));
+        let file = add_node(
+            &mut ctx,
+            vec![DagPbLink::new(raw_leaf), DagPbLink::new(leaf)],
+            data(2, b"", &[6, 5], None),
+        );
+        let small_file = add_node(&mut ctx, vec![], data(2, b"small", &[], None));
+
+        // a root shard with an entry and a child shard of another entry
+        let child_shard = add_node(
+            &mut ctx,
+            vec![link(small_file, "2b.txt")],
+            data(5, b"", &[], Some(4)),
+        );
+        let root = add_node(
+            &mut ctx,
+            vec![link(file, "0a.txt"), link(child_shard, "1")],
+            data(5, b"", &[], Some(4)),
+        );
+
+        let selector = interpret_as_unixfs(Selector::ExploreAll(Rc::new(ExploreAll {
+            next: interpret_as_unixfs(Selector::DEFAULT),
+        })));
+        let selected = Params::<'_, _, Any>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(true, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| (node.path, node.node)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(selected.len(), 2);
+        assert!(matches!(
+            &selected[0],
+            (path, SelectedNode::Bytes(bytes)) if path == &PathBuf::from("a.txt") && bytes.as_ref() == b"hello world"
+        ));
+        assert!(matches!(
+            &selected[1],
+            (path, SelectedNode::Bytes(bytes)) if path == &PathBuf::from("b.txt") && bytes.as_ref() == b"small"
+        ));
+
+        // malformed shards fail reification
+        let root = add_node(&mut ctx, vec![link(file, "")], data(5, b"", &[], Some(16)));
+        let err = Params::<'_, _, Any>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(true, &mut ctx)
+            .and_then(|nodes| nodes.collect::<Result<Vec<_>, _>>());
+        assert!(matches!(err, Err(Error::Reification(UnixFs::NAME, _))));
+    }
+
+    #[test]
+    fn test_sharded_lookup() {
+        let mut ctx = MemoryContext::default();
+        let file = add_node(&mut ctx, vec![], data(2, b"file", &[], None));
+
+        // with a fanout of 4, each shard indexes by the next 2 bits of a hash
+        let index = |name: &str, depth: u32| (murmur3_x64_64(name.as_bytes()) << (2 * depth)) >> 62;
+        let names = (0..)
+            .map(|i| format!("{}.txt", i))
+            .scan(vec![], |indices, name| {
+                let index = index(&name, 0);
+                let is_new = !indices.contains(&index);
+                indices.push(index);
+                Some(is_new.then_some(name))
+            })
+            .flatten()
+            .take(2)
+            .collect::<Vec<_>>();
+        let (root_name, child_name) = (&names[0], &names[1]);
+
+        // one entry in the root shard, and another in a child shard
+        let child_shard = add_node(
+            &mut ctx,
+            vec![link(
+                file,
+                &format!("{:X}{}", index(child_name, 1), child_name),
+            )],
+            data(5, b"", &[], Some(4)),
+        );
+        let root = add_node(
+            &mut ctx,
+            vec![
+                link(file, &format!("{:X}{}", index(root_name, 0), root_name)),
+                link(child_shard, &format!("{:X}", index(child_name, 0))),
+            ],
+            data(5, b"", &[], Some(4)),
+        );
+
+        // only the shards along the hash of each explored name are loaded
+        let explore = |name: &str| {
+            let fields = [(IpldString::from(name), Selector::DEFAULT)];
+            Selector::ExploreFields(ExploreFields {
+                fields: fields.into_iter().collect(),
+            })
+        };
+        for (name, loaded) in [(root_name, &[root][..]), (child_name, &[root, child_shard])] {
+            let (reified, cids) = reify(&mut ctx, root, &explore(name));
+            match reified.unwrap() {
+                Reified::Node(Any::Map(entries)) => {
+                    assert_eq!(
+                        entries.keys().collect::<Vec<_>>(),
+                        [&IpldString::from(name.as_str())]
+                    );
+                }
+                reified => panic!("unexpected reification {:?}", reified),
+            }
+            assert_eq!(cids, loaded);
+        }
+
+        let (reified, cids) = reify(&mut ctx, root, &explore("missing"));
+        assert!(matches!(reified.unwrap(), Reified::Node(Any::Map(entries)) if entries.is_empty()));
+        assert!(cids.len() <= 2);
+    }
+}
@@ -6,8 +6,26 @@
 //!     - An `impl Context for EncryptedContext` can provide a byte stream that encrypts bytes written by a type/decrypts bytes read into a type. Later, a `Representation` can be provided with an `EncyptedContext` initialized with a key, transparently encrypting/decrypting the provided byte streams.
 //!     - Additionally, we can define an `impl State for Encrypted<R, W>: Context<R, W>` and a type whose `Representation` implementation could derive an encryption/decryption key from within the type, ensuring that the type can only be stored in ciphertext.
 
+#[cfg(feature = "async")]
+mod async_context;
+mod cached;
+#[cfg(feature = "encryption")]
+mod encrypted;
+mod flatfs;
 #[cfg(feature = "ipfs")]
 mod ipfs;
+mod verifying;
+
+#[cfg(feature = "async")]
+pub use async_context::{AsyncContext, AsyncSelectionContext};
+pub use cached::{CacheStats, CachedContext, LayeredContext};
+#[cfg(feature = "encryption")]
+pub use encrypted::{CidMode, EncryptedContext};
+pub use flatfs::{FlatfsContext, FlatfsWriter};
+pub use verifying::{UnknownHashPolicy, VerifyingContext};
+
+#[cfg(test)]
+pub(crate) use tests::{add_leaf_dag, assert_resolves_leaf, CountingContext};
 
 use crate::dev::*;
 use std::{
@@ -28,6 +46,62 @@ pub trait Context: Sized {
     ///
     fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error>;
 
+    /// Opens a writer for a new block, to be hashed with the given multihash
+    /// code.
+    ///
+    /// The default implementation fails, for read-only contexts.
+    fn block_writer(
+        &mut self,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Self::Writer, Error> {
+        Err(Error::Context(anyhow::anyhow!(
+            "context cannot write blocks of codec {} and multihash {}",
+            multicodec_code,
+            multihash_code
+        )))
+    }
+
+    /// Stores a block written to a [`Context::block_writer`], returning its
+    /// [`Cid`].
+    ///
+    /// The default implementation fails, for read-only contexts.
+    fn flush_block(&mut self, writer: Self::Writer) -> Result<Cid, Error> {
+        drop(writer);
+        Err(Error::Context(anyhow::anyhow!(
+            "context cannot write blocks"
+        )))
+    }
+
+    /// Stores a block under the given [`Cid`], *without* verifying that the
+    /// block matches it (e.g. for blocks already verified, or for blocks
+    /// transformed by a wrapping context).
+    ///
+    /// The default implementation fails, for read-only contexts.
+    fn insert_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        Err(Error::Context(anyhow::anyhow!(
+            "context cannot insert block of {} bytes for cid: {:?}",
+            block.len(),
+            cid
+        )))
+    }
+
+    /// Encodes a dag as a new block, returning its [`Cid`].
+    fn add_dag<T>(
+        &mut self,
+        dag: &T,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Cid, Error>
+    where
+        T: Representation,
+    {
+        let mut codec = Multicodec::try_from(multicodec_code)?;
+        let mut writer = self.block_writer(multicodec_code, multihash_code)?;
+        codec.write(dag, &mut writer)?;
+        self.flush_block(writer)
+    }
+
     //
     // fn decoder<'de, 'a: 'de>(&mut self) -> Box<dyn ErasedDeserializer<'de> + 'a> {
     //     unimplemented!()
@@ -40,36 +114,7 @@ pub trait Context: Sized {
     // fn set_decoder<'de, D: Decoder<'de>>(&mut self, de: &mut D) {
     //     unimplemented!()
     // }
-    //
-    // fn block_encoder<Si>(
-    //     &mut self,
-    //     meta: BlockMeta<'_, Si>,
-    // ) -> Result<&'_ mut dyn ErasedSerializer, Error>
-    // where
-    //     Si: MultihashSize,
-    // {
-    //     unimplemented!()
-    // }
-    //
-    // fn path_encoder<P: AsRef<Path>>(
-    //     &mut self,
-    //     meta: P,
-    // ) -> Result<&'_ mut dyn ErasedSerializer, Error> {
-    //     unimplemented!()
-    // }
-    //
-    // fn close_encoder<Si, So>(
-    //     &mut self,
-    //     replacing: Option<BlockMeta<'_, Si, So>>,
-    // ) -> Result<CidGeneric<So>, Error>
-    // where
-    //     Si: MultihashSize,
-    //     So: MultihashSize,
-    // {
-    //     unimplemented!()
-    // }
 
-    //
     //
     //
     // /// Internally, this will:
@@ -97,6 +142,64 @@ impl<'a, C: Context + 'a> Context for &'a mut C {
     fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
         (*self).block_reader(cid)
     }
+
+    fn block_writer(
+        &mut self,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Self::Writer, Error> {
+        (*self).block_writer(multicodec_code, multihash_code)
+    }
+
+    fn flush_block(&mut self, writer: Self::Writer) -> Result<Cid, Error> {
+        (*self).flush_block(writer)
+    }
+
+    fn insert_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        (*self).insert_block(cid, block)
+    }
+}
+
+/// A [`Write`]r of a new block, that hashes the block as it's written.
+#[derive(Debug)]
+pub struct BlockWriter<W = Vec<u8>> {
+    inner: W,
+    multicodec_code: u64,
+    hasher: Multihash,
+}
+
+impl<W: Write> BlockWriter<W> {
+    /// Creates a writer of a block of the given multicodec, hashed with the
+    /// given multihash.
+    pub fn new(inner: W, multicodec_code: u64, multihash_code: u64) -> Result<Self, Error> {
+        Ok(Self {
+            inner,
+            multicodec_code,
+            hasher: Multihash::try_from(multihash_code)?,
+        })
+    }
+
+    /// Flushes the inner writer, returning it alongside the block's [`Cid`].
+    pub fn finish(mut self) -> Result<(Cid, W), Error> {
+        self.inner
+            .flush()
+            .map_err(|err| Error::Encoder(err.into()))?;
+        let mh = self.hasher.finalize()?;
+        let cid = DefaultCid::new_v1(self.multicodec_code, mh);
+        Ok((Cid::from(cid), self.inner))
+    }
+}
+
+impl<W: Write> Write for BlockWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
 }
 
 ///
@@ -119,17 +222,109 @@ impl MemoryContext {
         self.blocks.insert(cid, block);
         Ok(cid)
     }
+
+    /// Iterates over the context's blocks, in no particular order.
+    pub fn blocks(&self) -> impl Iterator<Item = (&Cid, &[u8])> {
+        self.blocks
+            .iter()
+            .map(|(cid, block)| (cid, block.as_slice()))
+    }
 }
 
 impl Context for MemoryContext {
     type Reader = Cursor<Vec<u8>>;
-    type Writer = Vec<u8>;
+    type Writer = BlockWriter;
 
     fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
-        let block = self
-            .blocks
-            .get(cid)
-            .ok_or_else(|| Error::Context(anyhow::anyhow!("missing block for cid: {:?}", cid)))?;
+        let block = self.blocks.get(cid).ok_or(Error::BlockNotFound(*cid))?;
         Ok(Cursor::new(block.to_owned()))
     }
+
+    fn block_writer(
+        &mut self,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Self::Writer, Error> {
+        BlockWriter::new(Vec::new(), multicodec_code, multihash_code)
+    }
+
+    fn flush_block(&mut self, writer: Self::Writer) -> Result<Cid, Error> {
+        let (cid, block) = writer.finish()?;
+        self.blocks.insert(cid, block);
+        Ok(cid)
+    }
+
+    fn insert_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        self.blocks.insert(*cid, block.to_vec());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts the blocks read from the inner context.
+    pub(crate) struct CountingContext(pub MemoryContext, pub usize);
+
+    impl Context for CountingContext {
+        type Reader = Cursor<Vec<u8>>;
+        type Writer = BlockWriter;
+
+        fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
+            self.1 += 1;
+            self.0.block_reader(cid)
+        }
+    }
+
+    /// Adds a raw `leaf` block and a root list of a link to it, returning
+    /// the root and leaf [`Cid`]s.
+    pub(crate) fn add_leaf_dag<C: Context>(ctx: &mut C) -> (Cid, Cid) {
+        let leaf = ctx
+            .add_dag(
+                &Bytes::copy_from_slice(b"leaf"),
+                Raw::CODE,
+                Multihash::SHA2_256,
+            )
+            .unwrap();
+        let root = ctx
+            .add_dag(
+                &vec![Link::<Bytes>::Cid(leaf)],
+                DagCbor::CODE,
+                Multihash::BLAKE3_256,
+            )
+            .unwrap();
+        (root, leaf)
+    }
+
+    /// Asserts that the root added by [`add_leaf_dag`] resolves to its leaf.
+    pub(crate) fn assert_resolves_leaf<C: Context>(mut ctx: C, root: Cid) {
+        let mut root = Link::<List<Link<Bytes>>>::Cid(root);
+        let leaf = &mut root.resolve(&mut ctx).unwrap().clone()[0];
+        assert_eq!(leaf.resolve(&mut ctx).unwrap().as_ref(), b"leaf");
+    }
+
+    #[test]
+    fn test_memory_context() {
+        let mut ctx = MemoryContext::default();
+
+        // writing a block by hand
+        let mut writer = ctx.block_writer(Raw::CODE, Multihash::SHA2_256).unwrap();
+        writer.write_all(b"le").unwrap();
+        writer.write_all(b"af").unwrap();
+        let leaf = ctx.flush_block(writer).unwrap();
+        assert_eq!(
+            leaf,
+            Cid::new(Version::V1, Raw::CODE, Multihash::SHA2_256, &b"leaf"[..]).unwrap()
+        );
+
+        // writing encoded dags
+        let (root, raw_leaf) = add_leaf_dag(&mut ctx);
+        assert_eq!(raw_leaf, leaf);
+        assert_eq!(root.multicodec_code(), DagCbor::CODE);
+        assert_eq!(root.multihash_code(), Multihash::BLAKE3_256);
+        assert_resolves_leaf(&mut ctx, root);
+
+        assert!(ctx.block_writer(Raw::CODE, 0x1234).is_err());
+    }
 }
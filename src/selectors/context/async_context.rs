@@ -0,0 +1,203 @@
+//! Asynchronous block loading, for selecting against blocks fetched over the
+//! network.
+
+use super::{Context, MemoryContext};
+use crate::dev::*;
+use std::{
+    collections::HashMap,
+    future::{ready, Future},
+    io::Cursor,
+};
+
+/// Trait for asynchronously providing blocks, e.g. from remote peers.
+///
+/// Selection against an [`AsyncContext`] is driven by
+/// [`Params::into_node_stream`], whose stream is `!Send`; the futures
+/// returned here need not be `Send` either.
+pub trait AsyncContext {
+    /// Loads the bytes of a block.
+    fn block_bytes(&mut self, cid: &Cid) -> impl Future<Output = Result<Vec<u8>, Error>>;
+}
+
+impl<A: AsyncContext> AsyncContext for &mut A {
+    fn block_bytes(&mut self, cid: &Cid) -> impl Future<Output = Result<Vec<u8>, Error>> {
+        (**self).block_bytes(cid)
+    }
+}
+
+impl AsyncContext for MemoryContext {
+    fn block_bytes(&mut self, cid: &Cid) -> impl Future<Output = Result<Vec<u8>, Error>> {
+        ready(self.block_reader(cid).map(Cursor::into_inner))
+    }
+}
+
+/// The [`Context`] of selections driven by [`Params::into_node_stream`],
+/// which buffers the blocks loaded from an [`AsyncContext`].
+///
+/// Reading a block that has yet to be loaded fails, recording the block's
+/// [`Cid`] so that it can be loaded before the selection is resumed.
+#[derive(Debug, Default)]
+pub struct AsyncSelectionContext {
+    blocks: HashMap<Cid, Vec<u8>>,
+    missing: Option<Cid>,
+}
+
+impl AsyncSelectionContext {
+    /// Returns `true` if the block has been loaded.
+    pub fn is_loaded(&self, cid: &Cid) -> bool {
+        self.blocks.contains_key(cid)
+    }
+
+    /// Takes the [`Cid`] of the last block read before being loaded.
+    pub(crate) fn take_missing(&mut self) -> Option<Cid> {
+        self.missing
+            .take()
+            .filter(|cid| !self.blocks.contains_key(cid))
+    }
+
+    pub(crate) fn load(&mut self, cid: Cid, block: Vec<u8>) {
+        self.blocks.insert(cid, block);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.blocks.clear();
+        self.missing = None;
+    }
+}
+
+impl Context for AsyncSelectionContext {
+    type Reader = Cursor<Vec<u8>>;
+    type Writer = Vec<u8>;
+
+    fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
+        match self.blocks.get(cid) {
+            Some(block) => Ok(Cursor::new(block.clone())),
+            None => {
+                self.missing.replace(*cid);
+                Err(Error::Context(anyhow::anyhow!(
+                    "block not yet loaded for cid: {:?}",
+                    cid
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selectors::CountingContext;
+    use futures::{executor::block_on, StreamExt};
+    use std::rc::Rc;
+
+    impl AsyncContext for CountingContext {
+        async fn block_bytes(&mut self, cid: &Cid) -> Result<Vec<u8>, Error> {
+            self.1 += 1;
+            self.0.block_bytes(cid).await
+        }
+    }
+
+    type Root = List<Link<List<Bytes>>>;
+
+    fn selector() -> Selector {
+        Selector::ExploreAll(Rc::new(ExploreAll {
+            next: Selector::ExploreAll(Rc::new(ExploreAll {
+                next: Selector::DEFAULT,
+            })),
+        }))
+    }
+
+    fn dag() -> (MemoryContext, Cid, Cid) {
+        let mut ctx = MemoryContext::default();
+        let a = ctx
+            .add_dag(
+                &vec![Bytes::copy_from_slice(b"a"), Bytes::copy_from_slice(b"b")],
+                DagCbor::CODE,
+                Multihash::SHA2_256,
+            )
+            .unwrap();
+        let b = ctx
+            .add_dag(
+                &vec![Bytes::copy_from_slice(b"c")],
+                DagCbor::CODE,
+                Multihash::SHA2_256,
+            )
+            .unwrap();
+        let root = ctx
+            .add_dag(
+                &vec![Link::<List<Bytes>>::Cid(a), Link::Cid(b)],
+                DagCbor::CODE,
+                Multihash::SHA2_256,
+            )
+            .unwrap();
+        (ctx, root, b)
+    }
+
+    #[test]
+    fn test_node_stream() {
+        let (mut ctx, root, _) = dag();
+        let selector = selector();
+
+        let expected = Params::<'_, _, Root>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| (node.path, node.matched)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected.len(), 6);
+
+        let mut async_ctx = CountingContext(ctx, 0);
+        let stream = Params::<'_, AsyncSelectionContext, Root>::new_select(root)
+            .with_selector(&selector)
+            .into_node_stream(false, &mut async_ctx);
+        let selected = block_on(stream.collect::<Vec<_>>())
+            .into_iter()
+            .map(|node| node.map(|node| (node.path, node.matched)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(selected, expected);
+        assert_eq!(async_ctx.1, 3);
+
+        // only matched nodes
+        let stream = Params::<'_, AsyncSelectionContext, Root>::new_select(root)
+            .with_selector(&selector)
+            .into_node_stream(true, &mut async_ctx);
+        let matched = block_on(stream.collect::<Vec<_>>());
+        assert_eq!(matched.len(), 3);
+        assert!(matched.iter().all(|node| node.as_ref().unwrap().matched));
+    }
+
+    #[test]
+    fn test_missing_block() {
+        let (ctx, root, b) = dag();
+        let mut partial = MemoryContext::default();
+        for (cid, block) in ctx.blocks().filter(|(cid, _)| *cid != &b) {
+            partial.insert_block(cid, block).unwrap();
+        }
+
+        let selector = selector();
+        let stream = Params::<'_, AsyncSelectionContext, Root>::new_select(root)
+            .with_selector(&selector)
+            .into_node_stream(false, &mut partial);
+        let selected = block_on(stream.collect::<Vec<_>>());
+
+        // nodes selected before the missing block are yielded before the error,
+        // just as when iterating
+        let expected = Params::<'_, _, Root>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut partial)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(selected.len(), 5);
+        assert_eq!(selected[3].as_ref().unwrap().path.to_str(), Some("0/1"));
+        assert!(selected[4].is_err());
+        let ok = |selections: &[Result<NodeSelection, Error>]| {
+            selections
+                .iter()
+                .map(|node| node.as_ref().ok().map(|node| format!("{:?}", node)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ok(&selected), ok(&expected));
+    }
+}
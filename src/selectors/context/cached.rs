@@ -0,0 +1,350 @@
+//! [`Context`] combinators for caching blocks from slower [`Context`]s.
+
+use super::Context;
+use crate::dev::*;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Cursor, Read},
+};
+
+/// Counters of blocks read from a [`CachedContext`] or [`LayeredContext`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    /// Blocks read from the cache (or front context).
+    pub hits: u64,
+    /// Blocks read from the inner (or back) context.
+    pub misses: u64,
+}
+
+/// Wraps a [`Context`] with an in-memory, least-recently-used cache of block
+/// bytes, bounded by their total size.
+///
+/// Blocks written to the context are passed to the inner [`Context`]
+/// uncached.
+#[derive(Clone, Debug)]
+pub struct CachedContext<C> {
+    inner: C,
+    capacity: usize,
+    size: usize,
+    /// Blocks, and the tick at which they were last read.
+    blocks: HashMap<Cid, (Vec<u8>, u64)>,
+    /// Cids by the tick at which they were last read.
+    lru: BTreeMap<u64, Cid>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl<C: Context> CachedContext<C> {
+    /// Wraps a [`Context`], caching up to `capacity` bytes of blocks.
+    pub fn new(inner: C, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity,
+            size: 0,
+            blocks: HashMap::new(),
+            lru: BTreeMap::new(),
+            tick: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// The maximum total size of cached blocks.
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The total size of cached blocks.
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the block is cached.
+    pub fn is_cached(&self, cid: &Cid) -> bool {
+        self.blocks.contains_key(cid)
+    }
+
+    /// The cache's hit and miss counters.
+    pub const fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Empties the cache, leaving the counters untouched.
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.lru.clear();
+        self.size = 0;
+    }
+
+    /// The wrapped [`Context`].
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Unwraps the inner [`Context`].
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Caches a block, evicting the least-recently-used blocks to make room.
+    /// Blocks larger than the capacity are never cached.
+    fn cache(&mut self, cid: &Cid, block: Vec<u8>) {
+        if block.len() > self.capacity || self.blocks.contains_key(cid) {
+            return;
+        }
+
+        while self.size + block.len() > self.capacity {
+            let (_, evicted) = self.lru.pop_first().expect("cache should not be empty");
+            let (evicted, _) = self.blocks.remove(&evicted).unwrap();
+            self.size -= evicted.len();
+        }
+
+        self.tick += 1;
+        self.size += block.len();
+        self.lru.insert(self.tick, *cid);
+        self.blocks.insert(*cid, (block, self.tick));
+    }
+}
+
+impl<C: Context> Context for CachedContext<C> {
+    type Reader = Cursor<Vec<u8>>;
+    type Writer = C::Writer;
+
+    fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
+        self.tick += 1;
+        if let Some((block, tick)) = self.blocks.get_mut(cid) {
+            self.lru.remove(tick);
+            self.lru.insert(self.tick, *cid);
+            *tick = self.tick;
+            self.stats.hits += 1;
+            return Ok(Cursor::new(block.clone()));
+        }
+
+        self.stats.misses += 1;
+        let block = read_block(&mut self.inner, cid)?;
+        self.cache(cid, block.clone());
+        Ok(Cursor::new(block))
+    }
+
+    fn block_writer(
+        &mut self,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Self::Writer, Error> {
+        self.inner.block_writer(multicodec_code, multihash_code)
+    }
+
+    fn flush_block(&mut self, writer: Self::Writer) -> Result<Cid, Error> {
+        self.inner.flush_block(writer)
+    }
+
+    fn insert_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        self.inner.insert_block(cid, block)
+    }
+}
+
+/// A [`Context`] that reads blocks from a front [`Context`] (e.g. a
+/// [`CachedContext`] or [`MemoryContext`]), falling back to a back
+/// [`Context`] for blocks missing from the front (i.e. for which the front
+/// fails with [`Error::BlockNotFound`]).
+///
+/// Blocks are written to the front [`Context`].
+#[derive(Clone, Debug)]
+pub struct LayeredContext<A, B> {
+    front: A,
+    back: B,
+    promote: bool,
+    stats: CacheStats,
+}
+
+impl<A: Context, B: Context> LayeredContext<A, B> {
+    /// Layers a front [`Context`] over a back [`Context`].
+    pub fn new(front: A, back: B) -> Self {
+        Self {
+            front,
+            back,
+            promote: false,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Sets whether or not blocks read from the back [`Context`] are inserted
+    /// into the front [`Context`], via [`Context::insert_block`].
+    pub fn with_promotion(mut self, promote: bool) -> Self {
+        self.promote = promote;
+        self
+    }
+
+    /// Counters of blocks read from the front (hits) and back (misses)
+    /// [`Context`]s.
+    pub const fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// The front [`Context`].
+    pub const fn front(&self) -> &A {
+        &self.front
+    }
+
+    /// The back [`Context`].
+    pub const fn back(&self) -> &B {
+        &self.back
+    }
+
+    /// Unwraps the front and back [`Context`]s.
+    pub fn into_inner(self) -> (A, B) {
+        (self.front, self.back)
+    }
+}
+
+impl<A: Context, B: Context> Context for LayeredContext<A, B> {
+    type Reader = Cursor<Vec<u8>>;
+    type Writer = A::Writer;
+
+    fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
+        match read_block(&mut self.front, cid) {
+            Ok(block) => {
+                self.stats.hits += 1;
+                return Ok(Cursor::new(block));
+            }
+            Err(Error::BlockNotFound(_)) => {}
+            Err(err) => return Err(err),
+        }
+
+        let block = read_block(&mut self.back, cid)?;
+        self.stats.misses += 1;
+        if self.promote {
+            self.front.insert_block(cid, &block)?;
+        }
+        Ok(Cursor::new(block))
+    }
+
+    fn block_writer(
+        &mut self,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Self::Writer, Error> {
+        self.front.block_writer(multicodec_code, multihash_code)
+    }
+
+    fn flush_block(&mut self, writer: Self::Writer) -> Result<Cid, Error> {
+        self.front.flush_block(writer)
+    }
+
+    fn insert_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        self.front.insert_block(cid, block)
+    }
+}
+
+fn read_block<C: Context>(ctx: &mut C, cid: &Cid) -> Result<Vec<u8>, Error> {
+    let mut block = Vec::new();
+    ctx.block_reader(cid)?
+        .read_to_end(&mut block)
+        .map_err(|err| Error::Context(err.into()))?;
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selectors::{add_leaf_dag, assert_resolves_leaf, VerifyingContext};
+
+    fn add(ctx: &mut MemoryContext, block: &[u8]) -> Cid {
+        ctx.add_block(Version::V1, Raw::CODE, Multihash::SHA2_256, block.to_vec())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cached_context() {
+        let mut inner = MemoryContext::default();
+        let (root, leaf) = add_leaf_dag(&mut inner);
+        let big = add(&mut inner, &[0; 100]);
+        let mut ctx = CachedContext::new(inner, 64);
+
+        assert_resolves_leaf(&mut ctx, root);
+        assert_eq!(read_block(&mut ctx, &leaf).unwrap(), b"leaf");
+        assert_eq!(ctx.stats(), CacheStats { hits: 1, misses: 2 });
+        assert!(ctx.is_cached(&root) && ctx.is_cached(&leaf));
+
+        read_block(&mut ctx, &big).unwrap();
+        read_block(&mut ctx, &big).unwrap();
+        assert!(!ctx.is_cached(&big));
+        assert_eq!(ctx.stats(), CacheStats { hits: 1, misses: 4 });
+
+        ctx.clear();
+        assert_eq!(ctx.size(), 0);
+        assert!(read_block(&mut ctx, &Cid::default()).is_err());
+    }
+
+    #[test]
+    fn test_eviction_order() {
+        let mut inner = MemoryContext::default();
+        let [a, b, c, d, e] =
+            [b"aaaa", b"bbbb", b"cccc", b"dddd", b"eeee"].map(|block| add(&mut inner, block));
+        let mut ctx = CachedContext::new(inner, 12);
+        for cid in [a, b, c] {
+            read_block(&mut ctx, &cid).unwrap();
+        }
+        assert_eq!(ctx.size(), 12);
+
+        // reading a block makes it the most-recently-used...
+        read_block(&mut ctx, &a).unwrap();
+        read_block(&mut ctx, &d).unwrap();
+        assert!(!ctx.is_cached(&b));
+
+        // ... so the least-recently-used blocks are evicted first
+        read_block(&mut ctx, &e).unwrap();
+        assert!(!ctx.is_cached(&c));
+        assert!(ctx.is_cached(&a) && ctx.is_cached(&d) && ctx.is_cached(&e));
+        assert_eq!(ctx.size(), 12);
+
+        read_block(&mut ctx, &b).unwrap();
+        assert!(!ctx.is_cached(&a) && ctx.is_cached(&b));
+        assert_eq!(ctx.stats(), CacheStats { hits: 1, misses: 6 });
+    }
+
+    #[test]
+    fn test_layered_context() {
+        let mut back = MemoryContext::default();
+        let (root, leaf) = add_leaf_dag(&mut back);
+        let mut ctx = LayeredContext::new(MemoryContext::default(), back);
+
+        // without promotion, blocks are read from the back layer...
+        assert_resolves_leaf(&mut ctx, root);
+        assert_eq!(ctx.stats(), CacheStats { hits: 0, misses: 2 });
+        assert_eq!(ctx.front().blocks().count(), 0);
+
+        // ... unless written to the front layer
+        let new = ctx
+            .add_dag(
+                &Bytes::copy_from_slice(b"new"),
+                Raw::CODE,
+                Multihash::SHA2_256,
+            )
+            .unwrap();
+        assert_eq!(read_block(&mut ctx, &new).unwrap(), b"new");
+        assert_eq!(ctx.stats(), CacheStats { hits: 1, misses: 2 });
+
+        // with promotion
+        let mut ctx = ctx.with_promotion(true);
+        read_block(&mut ctx, &leaf).unwrap();
+        read_block(&mut ctx, &leaf).unwrap();
+        assert_eq!(ctx.stats(), CacheStats { hits: 2, misses: 3 });
+        assert_eq!(ctx.front().blocks().count(), 2);
+
+        assert!(matches!(
+            read_block(&mut ctx, &Cid::default()),
+            Err(Error::BlockNotFound(_))
+        ));
+        assert_eq!(ctx.stats().misses, 3);
+
+        // only blocks missing from the front fall through to the back
+        let (mut front, back) = ctx.into_inner();
+        front.insert_block(&leaf, b"tampered").unwrap();
+        let mut ctx = LayeredContext::new(VerifyingContext::new(front), back);
+        assert!(matches!(
+            read_block(&mut ctx, &leaf),
+            Err(Error::BlockHashMismatch(cid)) if cid == leaf
+        ));
+        assert_eq!(ctx.stats(), CacheStats::default());
+    }
+}
@@ -0,0 +1,261 @@
+//! A [`Context`] wrapper that encrypts blocks at rest.
+
+use super::{BlockWriter, Context};
+use crate::dev::*;
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use std::{
+    fmt,
+    io::{Cursor, Read, Write},
+};
+
+/// Determines over which bytes an [`EncryptedContext`] computes its blocks'
+/// [`Cid`]s.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CidMode {
+    /// [`Cid`]s are computed over the plaintext, so they are the same as if
+    /// the blocks were stored unencrypted, and ciphertexts are bound to their
+    /// [`Cid`]s. Requires an inner [`Context`] that implements
+    /// [`Context::insert_block`].
+    Plaintext,
+    /// [`Cid`]s are computed over the ciphertext, so they reveal nothing
+    /// about the plaintext and can be verified without the key.
+    Ciphertext,
+}
+
+/// Wraps a [`Context`], transparently encrypting blocks written to it and
+/// decrypting blocks read from it with XChaCha20-Poly1305.
+///
+/// Each block is stored as a random 24-byte nonce followed by the
+/// authenticated ciphertext, so blocks that fail to decrypt (i.e. that were
+/// tampered with or encrypted with another key) fail to be read.
+#[derive(Clone)]
+pub struct EncryptedContext<C> {
+    inner: C,
+    cipher: XChaCha20Poly1305,
+    mode: CidMode,
+}
+
+impl<C: Context> EncryptedContext<C> {
+    const NONCE_LEN: usize = 24;
+
+    /// Wraps a [`Context`], encrypting its blocks with the given 32-byte key.
+    pub fn new(inner: C, key: &[u8; 32], mode: CidMode) -> Self {
+        Self {
+            inner,
+            cipher: XChaCha20Poly1305::new(key.into()),
+            mode,
+        }
+    }
+
+    /// Generates a new random encryption key.
+    pub fn generate_key() -> [u8; 32] {
+        XChaCha20Poly1305::generate_key(&mut OsRng).into()
+    }
+
+    /// Over which bytes [`Cid`]s are computed.
+    pub const fn mode(&self) -> CidMode {
+        self.mode
+    }
+
+    /// The wrapped [`Context`].
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Unwraps the inner [`Context`].
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    /// Ciphertexts are bound to their plaintext [`Cid`]s, if known.
+    fn aad(&self, cid: &Cid) -> Vec<u8> {
+        match self.mode {
+            CidMode::Plaintext => cid.to_bytes(),
+            CidMode::Ciphertext => Vec::new(),
+        }
+    }
+
+    fn encrypt(&self, block: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: block, aad })
+            .map_err(|_| Error::Encoder(anyhow::anyhow!("failed to encrypt block").into()))?;
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend(ciphertext);
+        Ok(encrypted)
+    }
+
+    fn decrypt(&self, cid: &Cid, encrypted: &[u8]) -> Result<Vec<u8>, Error> {
+        let err = || {
+            Error::Context(anyhow::anyhow!(
+                "failed to decrypt block for cid: {:?}",
+                cid
+            ))
+        };
+        if encrypted.len() < Self::NONCE_LEN {
+            return Err(err());
+        }
+
+        let (nonce, msg) = encrypted.split_at(Self::NONCE_LEN);
+        let aad = self.aad(cid);
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), Payload { msg, aad: &aad })
+            .map_err(|_| err())
+    }
+}
+
+impl<C: Context> Context for EncryptedContext<C> {
+    type Reader = Cursor<Vec<u8>>;
+    type Writer = BlockWriter;
+
+    fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
+        let mut encrypted = Vec::new();
+        self.inner
+            .block_reader(cid)?
+            .read_to_end(&mut encrypted)
+            .map_err(|err| Error::Context(err.into()))?;
+        Ok(Cursor::new(self.decrypt(cid, &encrypted)?))
+    }
+
+    fn block_writer(
+        &mut self,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Self::Writer, Error> {
+        BlockWriter::new(Vec::new(), multicodec_code, multihash_code)
+    }
+
+    fn flush_block(&mut self, writer: Self::Writer) -> Result<Cid, Error> {
+        let (cid, block) = writer.finish()?;
+        match self.mode {
+            CidMode::Plaintext => {
+                self.insert_block(&cid, &block)?;
+                Ok(cid)
+            }
+            CidMode::Ciphertext => {
+                let encrypted = self.encrypt(&block, &[])?;
+                let mut writer = self
+                    .inner
+                    .block_writer(cid.multicodec_code(), cid.multihash_code())?;
+                writer
+                    .write_all(&encrypted)
+                    .map_err(|err| Error::Encoder(err.into()))?;
+                self.inner.flush_block(writer)
+            }
+        }
+    }
+
+    /// Encrypts and inserts a plaintext block under its plaintext [`Cid`].
+    ///
+    /// Fails if the [`CidMode`] is [`CidMode::Ciphertext`], as the inserted
+    /// block would not match its [`Cid`].
+    fn insert_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        match self.mode {
+            CidMode::Plaintext => {
+                let encrypted = self.encrypt(block, &self.aad(cid))?;
+                self.inner.insert_block(cid, &encrypted)
+            }
+            CidMode::Ciphertext => Err(Error::Context(anyhow::anyhow!(
+                "cannot insert a block under a ciphertext cid: {:?}",
+                cid
+            ))),
+        }
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for EncryptedContext<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedContext")
+            .field("inner", &self.inner)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selectors::{add_leaf_dag, assert_resolves_leaf};
+
+    fn roundtrip(mode: CidMode) -> (MemoryContext, Cid) {
+        let key = EncryptedContext::<MemoryContext>::generate_key();
+        let mut ctx = EncryptedContext::new(MemoryContext::default(), &key, mode);
+        let (root, leaf) = add_leaf_dag(&mut ctx);
+        assert_resolves_leaf(&mut ctx, root);
+
+        // blocks are unreadable without the key
+        let inner = ctx.into_inner();
+        let (_, stored) = inner.blocks().find(|(cid, _)| *cid == &leaf).unwrap();
+        assert_eq!(stored.len(), 24 + 4 + 16);
+        let mut ctx = EncryptedContext::new(inner, &[0; 32], mode);
+        assert!(ctx.block_reader(&leaf).is_err());
+
+        (ctx.into_inner(), leaf)
+    }
+
+    #[test]
+    fn test_plaintext_mode() {
+        let (mut inner, leaf) = roundtrip(CidMode::Plaintext);
+        assert_eq!(
+            leaf,
+            Cid::new(Version::V1, Raw::CODE, Multihash::SHA2_256, &b"leaf"[..]).unwrap()
+        );
+        assert!(VerifyingContext::new(&mut inner)
+            .block_reader(&leaf)
+            .is_err());
+    }
+
+    #[test]
+    fn test_ciphertext_mode() {
+        let (mut inner, leaf) = roundtrip(CidMode::Ciphertext);
+        assert_eq!(leaf.multicodec_code(), Raw::CODE);
+        assert_ne!(
+            leaf,
+            Cid::new(Version::V1, Raw::CODE, Multihash::SHA2_256, &b"leaf"[..]).unwrap()
+        );
+        assert!(VerifyingContext::new(&mut inner)
+            .block_reader(&leaf)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_tampered_ciphertext() {
+        let key = EncryptedContext::<MemoryContext>::generate_key();
+        let mut ctx = EncryptedContext::new(MemoryContext::default(), &key, CidMode::Plaintext);
+        let (root, leaf) = add_leaf_dag(&mut ctx);
+        let mut inner = ctx.into_inner();
+        let stored = |inner: &MemoryContext, cid: &Cid| {
+            let (_, block) = inner.blocks().find(|(stored, _)| *stored == cid).unwrap();
+            block.to_vec()
+        };
+        let (leaf_block, root_block) = (stored(&inner, &leaf), stored(&inner, &root));
+
+        // a flipped bit fails authentication
+        let mut tampered = leaf_block.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        inner.insert_block(&leaf, &tampered).unwrap();
+        let mut ctx = EncryptedContext::new(inner, &key, CidMode::Plaintext);
+        assert!(ctx.block_reader(&leaf).is_err());
+
+        // as does a ciphertext moved to another block's Cid, or truncated
+        let mut inner = ctx.into_inner();
+        inner.insert_block(&leaf, &root_block).unwrap();
+        let mut ctx = EncryptedContext::new(inner, &key, CidMode::Plaintext);
+        assert!(ctx.block_reader(&leaf).is_err());
+
+        let mut inner = ctx.into_inner();
+        inner.insert_block(&leaf, &leaf_block[..8]).unwrap();
+        let mut ctx = EncryptedContext::new(inner, &key, CidMode::Plaintext);
+        assert!(ctx.block_reader(&leaf).is_err());
+
+        // the untampered ciphertext is still readable
+        let mut inner = ctx.into_inner();
+        inner.insert_block(&leaf, &leaf_block).unwrap();
+        assert_resolves_leaf(EncryptedContext::new(inner, &key, CidMode::Plaintext), root);
+    }
+}
@@ -0,0 +1,408 @@
+//! A [`Context`] storing blocks as files in a sharded directory, compatible
+//! with the layout of [go-ds-flatfs](https://github.com/ipfs/go-ds-flatfs).
+
+use super::{BlockWriter, Context};
+use crate::dev::*;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Temp files are unique per process and writer.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A persistent [`Context`] of blocks stored on local disk, one file per
+/// block.
+///
+/// Each block is stored at `<root>/<shard>/<key>.data`, where `key` is the
+/// unpadded, uppercase base32 encoding of the block's multihash and `shard`
+/// is the two characters preceding the last character of the key (i.e.
+/// flatfs's `next-to-last/2` sharding function). As in go-ipfs, blocks are
+/// keyed by multihash alone, so [`Cid`]s differing only in codec or version
+/// share a block. Blocks are first written to a temp file, then atomically
+/// renamed into place once their [`Cid`] is known.
+#[derive(Clone, Debug)]
+pub struct FlatfsContext {
+    root: PathBuf,
+}
+
+impl FlatfsContext {
+    /// The name of the file describing the directory's sharding function.
+    pub const SHARDING_FILE: &'static str = "SHARDING";
+
+    /// The sharding function used by this context.
+    pub const SHARDING: &'static str = "/repo/flatfs/shard/v1/next-to-last/2";
+
+    /// The extension of block files.
+    pub const EXTENSION: &'static str = "data";
+
+    const SHARD_LEN: usize = 2;
+    const TEMP_PREFIX: &'static str = ".temp-";
+
+    /// Opens the blockstore at the given directory, creating it if it doesn't
+    /// exist.
+    ///
+    /// Fails if the directory is sharded with any other sharding function.
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self, Error> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root).map_err(context_err)?;
+
+        let sharding_path = root.join(Self::SHARDING_FILE);
+        match fs::read_to_string(&sharding_path) {
+            Ok(sharding) if sharding.trim() == Self::SHARDING => {}
+            Ok(sharding) => {
+                return Err(Error::Context(anyhow::anyhow!(
+                    "unsupported flatfs sharding function: {}",
+                    sharding.trim()
+                )))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                fs::write(&sharding_path, format!("{}\n", Self::SHARDING)).map_err(context_err)?;
+            }
+            Err(err) => return Err(context_err(err)),
+        }
+
+        Ok(Self { root })
+    }
+
+    /// The root directory of the blockstore.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns `true` if the blockstore contains the block.
+    pub fn has(&self, cid: &Cid) -> Result<bool, Error> {
+        match fs::metadata(self.block_path(cid)) {
+            Ok(metadata) => Ok(metadata.is_file()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(context_err(err)),
+        }
+    }
+
+    /// Removes a block from the blockstore, returning `false` if it was
+    /// absent.
+    pub fn delete(&mut self, cid: &Cid) -> Result<bool, Error> {
+        match fs::remove_file(self.block_path(cid)) {
+            Ok(_) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(context_err(err)),
+        }
+    }
+
+    /// Iterates over the multihashes of every block in the blockstore, in no
+    /// particular order.
+    ///
+    /// Files that aren't blocks (e.g. temp files of in-progress writes) are
+    /// skipped.
+    pub fn multihashes(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<DefaultMultihash, Error>>, Error> {
+        let shards = fs::read_dir(&self.root).map_err(context_err)?;
+        Ok(shards
+            .map(
+                |shard| -> Result<Vec<Result<DefaultMultihash, Error>>, Error> {
+                    let shard = shard.map_err(context_err)?;
+                    if !shard.file_type().map_err(context_err)?.is_dir() {
+                        return Ok(Vec::new());
+                    }
+
+                    let mut multihashes = Vec::new();
+                    for entry in fs::read_dir(shard.path()).map_err(context_err)? {
+                        let path = entry.map_err(context_err)?.path();
+                        if let Some(multihash) = Self::parse_path(&path) {
+                            multihashes.push(Ok(multihash));
+                        }
+                    }
+                    Ok(multihashes)
+                },
+            )
+            .flat_map(|res| match res {
+                Ok(multihashes) => multihashes,
+                Err(err) => vec![Err(err)],
+            }))
+    }
+
+    fn key(cid: &Cid) -> String {
+        Multibase::Base32Upper.encode(cid.multihash().to_bytes())
+    }
+
+    /// Implements `next-to-last/2`, padding short keys with `_`.
+    fn shard(key: &str) -> String {
+        let padded = format!("{:_>width$}", key, width = Self::SHARD_LEN + 1);
+        let end = padded.len() - 1;
+        padded[end - Self::SHARD_LEN..end].to_string()
+    }
+
+    fn block_path(&self, cid: &Cid) -> PathBuf {
+        let key = Self::key(cid);
+        self.root
+            .join(Self::shard(&key))
+            .join(key)
+            .with_extension(Self::EXTENSION)
+    }
+
+    fn temp_file(&self) -> Result<(PathBuf, File), Error> {
+        let temp_path = self.root.join(format!(
+            "{}{}-{}",
+            Self::TEMP_PREFIX,
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+            .map_err(context_err)?;
+        Ok((temp_path, file))
+    }
+
+    /// Moves a fully-written temp file into place, unless the block is
+    /// already present.
+    fn persist(&self, temp_path: &Path, cid: &Cid) -> Result<(), Error> {
+        let path = self.block_path(cid);
+        if path.is_file() {
+            // blocks are immutable, so the existing block is identical
+            return Ok(());
+        }
+
+        fs::create_dir_all(path.parent().unwrap()).map_err(context_err)?;
+        fs::rename(temp_path, &path).map_err(context_err)
+    }
+
+    fn parse_path(path: &Path) -> Option<DefaultMultihash> {
+        if path.extension()? != Self::EXTENSION {
+            return None;
+        }
+        let bytes = Multibase::Base32Upper
+            .decode(path.file_stem()?.to_str()?)
+            .ok()?;
+        DefaultMultihash::from_bytes(&bytes).ok()
+    }
+}
+
+impl Context for FlatfsContext {
+    type Reader = BufReader<File>;
+    type Writer = FlatfsWriter;
+
+    fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
+        match File::open(self.block_path(cid)) {
+            Ok(file) => Ok(BufReader::new(file)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Err(Error::BlockNotFound(*cid)),
+            Err(err) => Err(context_err(err)),
+        }
+    }
+
+    fn block_writer(
+        &mut self,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Self::Writer, Error> {
+        let (temp_path, file) = self.temp_file()?;
+
+        // ensures the temp file is removed if the multihash is unsupported
+        let mut writer = FlatfsWriter {
+            inner: None,
+            temp_path,
+        };
+        writer.inner = Some(BlockWriter::new(
+            BufWriter::new(file),
+            multicodec_code,
+            multihash_code,
+        )?);
+        Ok(writer)
+    }
+
+    fn flush_block(&mut self, mut writer: Self::Writer) -> Result<Cid, Error> {
+        let (cid, file) = writer
+            .inner
+            .take()
+            .expect("writer should only be taken when flushed")
+            .finish()?;
+        file.into_inner()
+            .map_err(|err| context_err(err.into_error()))?
+            .sync_all()
+            .map_err(context_err)?;
+
+        self.persist(&writer.temp_path, &cid)?;
+        Ok(cid)
+    }
+
+    fn insert_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        let (temp_path, mut file) = self.temp_file()?;
+        let res = file
+            .write_all(block)
+            .and_then(|_| file.sync_all())
+            .map_err(context_err)
+            .and_then(|_| self.persist(&temp_path, cid));
+        fs::remove_file(&temp_path).ok();
+        res
+    }
+}
+
+/// A [`Write`]r of a new block to a [`FlatfsContext`], whose temp file is
+/// removed if dropped before being flushed.
+#[derive(Debug)]
+pub struct FlatfsWriter {
+    inner: Option<BlockWriter<BufWriter<File>>>,
+    temp_path: PathBuf,
+}
+
+impl Write for FlatfsWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.as_mut().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl Drop for FlatfsWriter {
+    fn drop(&mut self) {
+        // the temp file has already been renamed if the block was flushed
+        drop(self.inner.take());
+        fs::remove_file(&self.temp_path).ok();
+    }
+}
+
+fn context_err(err: io::Error) -> Error {
+    Error::Context(err.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selectors::{add_leaf_dag, assert_resolves_leaf, VerifyingContext};
+    use std::{collections::BTreeSet, io::Read};
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("ipld-{}-{}", name, std::process::id()));
+        fs::remove_dir_all(&root).ok();
+        root
+    }
+
+    #[test]
+    fn test_shard() {
+        assert_eq!(FlatfsContext::shard("CIQABCD"), "BC");
+        assert_eq!(FlatfsContext::shard("AB"), "_A");
+        assert_eq!(FlatfsContext::shard(""), "__");
+    }
+
+    #[test]
+    fn test_flatfs_context() {
+        let root = temp_root("flatfs");
+        let mut ctx = FlatfsContext::open(&root).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join(FlatfsContext::SHARDING_FILE)).unwrap(),
+            "/repo/flatfs/shard/v1/next-to-last/2\n"
+        );
+
+        // writing blocks
+        let (root_cid, leaf) = add_leaf_dag(&mut ctx);
+        assert_eq!(
+            leaf,
+            Cid::new(Version::V1, Raw::CODE, Multihash::SHA2_256, &b"leaf"[..]).unwrap()
+        );
+
+        // rewriting blocks, and abandoning a write
+        assert_eq!(add_leaf_dag(&mut ctx), (root_cid, leaf));
+        let mut writer = ctx.block_writer(Raw::CODE, Multihash::SHA2_256).unwrap();
+        writer.write_all(b"abandoned").unwrap();
+        drop(writer);
+
+        // inserting a block under a known cid
+        let inserted = Cid::new(Version::V1, Raw::CODE, Multihash::SHA2_256, &b"a"[..]).unwrap();
+        ctx.insert_block(&inserted, b"a").unwrap();
+        assert!(ctx.has(&inserted).unwrap());
+        assert!(ctx.delete(&inserted).unwrap());
+
+        // layout, keyed by multihash alone
+        let key = Multibase::Base32Upper.encode(leaf.multihash().to_bytes());
+        assert!(key.starts_with("CIQ"));
+        let shard = &key[key.len() - 3..key.len() - 1];
+        assert!(root.join(shard).join(format!("{}.data", key)).is_file());
+        assert!(fs::read_dir(&root).unwrap().all(|entry| !entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with(FlatfsContext::TEMP_PREFIX)));
+
+        // reading blocks from a reopened context
+        let mut ctx = FlatfsContext::open(&root).unwrap();
+        assert!(ctx.has(&leaf).unwrap() && ctx.has(&root_cid).unwrap());
+        let leaf_v0 = Cid::try_from(leaf.multihash().to_bytes().as_slice()).unwrap();
+        assert!(ctx.has(&leaf_v0).unwrap());
+        let mut block = Vec::new();
+        ctx.block_reader(&leaf)
+            .unwrap()
+            .read_to_end(&mut block)
+            .unwrap();
+        assert_eq!(block, b"leaf");
+
+        assert_resolves_leaf(&mut ctx, root_cid);
+
+        // enumerating and deleting blocks
+        let multihashes = ctx
+            .multihashes()
+            .unwrap()
+            .collect::<Result<BTreeSet<_>, _>>();
+        assert_eq!(
+            multihashes.unwrap(),
+            [*leaf.multihash(), *root_cid.multihash()]
+                .into_iter()
+                .collect()
+        );
+
+        assert!(ctx.delete(&leaf).unwrap());
+        assert!(!ctx.delete(&leaf).unwrap());
+        assert!(!ctx.has(&leaf).unwrap());
+        assert!(ctx.block_reader(&leaf).is_err());
+        assert!(ctx.block_writer(Raw::CODE, 0x1234).is_err());
+        assert_eq!(ctx.multihashes().unwrap().count(), 1);
+
+        // other sharding functions are rejected
+        fs::write(
+            root.join(FlatfsContext::SHARDING_FILE),
+            "/repo/flatfs/shard/v1/prefix/2\n",
+        )
+        .unwrap();
+        assert!(FlatfsContext::open(&root).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_and_missing_blocks() {
+        let root = temp_root("flatfs-corrupt");
+        let mut ctx = FlatfsContext::open(&root).unwrap();
+        let (root_cid, leaf) = add_leaf_dag(&mut ctx);
+
+        // a block file removed out from under the context is missing...
+        fs::remove_file(ctx.block_path(&leaf)).unwrap();
+        assert!(!ctx.has(&leaf).unwrap());
+        let mut root_link = Link::<List<Link<Bytes>>>::Cid(root_cid);
+        let leaf_link = &mut root_link.resolve(&mut ctx).unwrap().clone()[0];
+        assert!(leaf_link.resolve(&mut ctx).is_err());
+
+        // ... and is restored by writing it again
+        assert_eq!(add_leaf_dag(&mut ctx), (root_cid, leaf));
+        assert_resolves_leaf(&mut ctx, root_cid);
+
+        // a corrupted block file is read as-is, failing to decode...
+        fs::write(ctx.block_path(&root_cid), b"corrupt").unwrap();
+        assert!(ctx.has(&root_cid).unwrap());
+        let mut root_link = Link::<List<Link<Bytes>>>::Cid(root_cid);
+        assert!(root_link.resolve(&mut ctx).is_err());
+
+        // ... or to verify
+        let mut ctx = VerifyingContext::new(ctx);
+        assert!(matches!(
+            ctx.block_reader(&root_cid),
+            Err(Error::BlockHashMismatch(cid)) if cid == root_cid
+        ));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+//! A [`Context`] wrapper that verifies blocks against their [`Cid`]s.
+
+use super::Context;
+use crate::dev::*;
+use multihash::Hasher;
+use std::io::{Cursor, Read};
+
+/// How a [`VerifyingContext`] treats blocks hashed with an unknown or
+/// unsupported multihash.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum UnknownHashPolicy {
+    /// Fails with an [`Error::Multihash`].
+    #[default]
+    Reject,
+    /// Provides the block unverified.
+    PassThrough,
+}
+
+/// Wraps a [`Context`] whose blocks may be untrusted (e.g. those from peers
+/// or caches), re-hashing each block read from it and failing with an
+/// [`Error::BlockHashMismatch`] if it doesn't match its [`Cid`], before the
+/// block can be decoded.
+///
+/// Blocks are written unchanged to the inner [`Context`].
+#[derive(Clone, Debug, Default)]
+pub struct VerifyingContext<C> {
+    inner: C,
+    unknown_hashes: UnknownHashPolicy,
+}
+
+impl<C: Context> VerifyingContext<C> {
+    /// Wraps a [`Context`], rejecting blocks with unknown multihashes.
+    pub const fn new(inner: C) -> Self {
+        Self {
+            inner,
+            unknown_hashes: UnknownHashPolicy::Reject,
+        }
+    }
+
+    /// Sets the policy for blocks with unknown multihashes.
+    pub const fn with_unknown_hashes(mut self, policy: UnknownHashPolicy) -> Self {
+        self.unknown_hashes = policy;
+        self
+    }
+
+    /// The wrapped [`Context`].
+    pub const fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// The wrapped [`Context`], mutably.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Unwraps the inner [`Context`].
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Context> Context for VerifyingContext<C> {
+    type Reader = Cursor<Vec<u8>>;
+    type Writer = C::Writer;
+
+    fn block_reader(&mut self, cid: &Cid) -> Result<Self::Reader, Error> {
+        let mut block = Vec::new();
+        self.inner
+            .block_reader(cid)?
+            .read_to_end(&mut block)
+            .map_err(|err| Error::Context(err.into()))?;
+
+        let mut hasher = match Multihash::try_from(cid.multihash_code()) {
+            Ok(hasher) => hasher,
+            Err(_) if self.unknown_hashes == UnknownHashPolicy::PassThrough => {
+                return Ok(Cursor::new(block))
+            }
+            Err(err) => return Err(err),
+        };
+        hasher.update(&block);
+        if hasher.finalize()?.digest() != cid.digest() {
+            return Err(Error::BlockHashMismatch(*cid));
+        }
+
+        Ok(Cursor::new(block))
+    }
+
+    fn block_writer(
+        &mut self,
+        multicodec_code: u64,
+        multihash_code: u64,
+    ) -> Result<Self::Writer, Error> {
+        self.inner.block_writer(multicodec_code, multihash_code)
+    }
+
+    fn flush_block(&mut self, writer: Self::Writer) -> Result<Cid, Error> {
+        self.inner.flush_block(writer)
+    }
+
+    fn insert_block(&mut self, cid: &Cid, block: &[u8]) -> Result<(), Error> {
+        self.inner.insert_block(cid, block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selectors::{add_leaf_dag, assert_resolves_leaf};
+
+    #[test]
+    fn test_verifying_context() {
+        let mut inner = MemoryContext::default();
+        let (root, _) = add_leaf_dag(&mut inner);
+        let mut ctx = VerifyingContext::new(inner);
+        assert_resolves_leaf(&mut ctx, root);
+
+        // writes are passed through
+        let cid = ctx
+            .add_dag(
+                &Bytes::copy_from_slice(b"new"),
+                Raw::CODE,
+                Multihash::SHA2_256,
+            )
+            .unwrap();
+        assert!(ctx.into_inner().blocks().any(|(block, _)| block == &cid));
+    }
+
+    #[test]
+    fn test_tampered_blocks() {
+        let mut inner = MemoryContext::default();
+        let (root, leaf) = add_leaf_dag(&mut inner);
+
+        // a leaf tampered with after being linked to
+        inner.insert_block(&leaf, b"tampered").unwrap();
+        let mut ctx = VerifyingContext::new(&mut inner);
+        let mut root_link = Link::<List<Link<Bytes>>>::Cid(root);
+        let leaf_link = &mut root_link.resolve(&mut ctx).unwrap().clone()[0];
+        assert!(matches!(
+            leaf_link.resolve(&mut ctx),
+            Err(Error::BlockHashMismatch(cid)) if cid == leaf
+        ));
+
+        // a block with an unknown multihash can't be verified, unless passed
+        // through
+        let mh = DefaultMultihash::wrap(0x1234, b"digest").unwrap();
+        let unknown = Cid::from(DefaultCid::new_v1(Raw::CODE, mh));
+        inner.insert_block(&unknown, b"leaf").unwrap();
+        let mut ctx = VerifyingContext::new(&mut inner);
+        assert!(matches!(
+            ctx.block_reader(&unknown),
+            Err(Error::Multihash(_))
+        ));
+
+        let mut ctx = ctx.with_unknown_hashes(UnknownHashPolicy::PassThrough);
+        let mut link = Link::<Bytes>::Cid(unknown);
+        assert_eq!(link.resolve(&mut ctx).unwrap().as_ref(), b"leaf");
+        assert!(ctx.block_reader(&leaf).is_err());
+    }
+}
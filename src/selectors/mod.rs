@@ -7,14 +7,17 @@
 #![allow(non_camel_case_types)]
 
 // mod path;
+mod adl;
 mod context;
 #[macro_use]
 mod seed;
 mod selectors;
 mod state;
 
+pub use adl::*;
 pub use context::*;
 pub use field::*;
+pub use iter::*;
 pub use params::*;
 pub use seed::*;
 pub use selection::*;
@@ -26,8 +29,9 @@ use macros::derive_more::From;
 use serde::de::DeserializeSeed;
 use std::{
     cell::RefCell,
+    collections::VecDeque,
     path::{Path, PathBuf},
-    vec::IntoIter,
+    rc::Rc,
 };
 
 ///
@@ -48,25 +52,70 @@ pub trait Select<C: Context>: Representation {
 
     /// Selects against the dag, loading more blocks from `C` if required.
     ///
-    /// TODO
-    fn select_in<T>(&self, params: Params<'_, C, Self>, ctx: &mut C) -> Result<(), Error> {
-        unimplemented!()
+    /// Selections are made against the in-memory dag as they would be against
+    /// its block, with blocks only being loaded from `C` when crossing a
+    /// [`Link`] that has yet to be resolved.
+    fn select_in(&self, params: Params<'_, C, Self>, ctx: &mut C) -> Result<(), Error>
+    where
+        Self: 'static,
+    {
+        SelectorSeed::select_in(params, self, ctx)
+    }
+
+    /// Selects against the in-memory dag with the seed's selector.
+    ///
+    /// By default, no selectors are supported, as selecting in-memory nodes
+    /// requires them to be cloned into [`SelectedNode`]s.
+    #[doc(hidden)]
+    fn select_in_seed(&self, seed: SelectorSeed<'_, C, Self>) -> Result<(), Error>
+    where
+        Self: 'static,
+    {
+        Err(Error::unsupported_selector::<Self>(seed.selector))
     }
 
     /// Patches the dag according to the selector, loading more blocks from `C`
     /// if required.
     ///
-    /// TODO
-    fn patch_in<T>(&mut self, params: Params<'_, C, Self>, ctx: &mut C) -> Result<(), Error> {
-        unimplemented!()
+    /// Each dag matched by the selector is updated in-place by the patch op
+    /// provided with [`Params::with_patch_op`], and each [`Link`] along the
+    /// path to a patched dag is marked dirty.
+    fn patch_in(&mut self, params: Params<'_, C, Self>, ctx: &mut C) -> Result<(), Error>
+    where
+        Self: 'static,
+    {
+        SelectorSeed::patch(params, self, ctx)
+    }
+
+    /// Patches the dag against the seed's selector, returning whether or not
+    /// any of it was patched.
+    ///
+    /// By default, only matchers are supported, as there is nothing to
+    /// explore (though flushing, which explores the entire dag, ends here).
+    #[doc(hidden)]
+    fn patch_seed(&mut self, mut seed: SelectorSeed<'_, C, Self>) -> Result<bool, Error>
+    where
+        Self: 'static,
+    {
+        match seed.selector {
+            selector if selector.matcher().is_some() => seed.patch_matched(self),
+            _ if seed.is_flush() => Ok(false),
+            _ => Err(Error::unsupported_selector::<Self>(seed.selector)),
+        }
     }
 
     /// Flushes the dag according to the selector, writing blocks to `C` if
     /// flushing linked dags.
     ///
-    /// TODO
-    fn flush(&mut self, params: Params<'_, C, Self>, ctx: &mut C) -> Result<(), Error> {
-        unimplemented!()
+    /// Each dirty [`Link`] along the selector is re-encoded bottom-up with the
+    /// codec and multihash of its original [`Cid`], then the dag itself is
+    /// re-encoded like the root [`Cid`] provided in the [`Params`]. Returns
+    /// the new root [`Cid`] and the [`Cid`]s of the newly written blocks.
+    fn flush(&mut self, params: Params<'_, C, Self>, ctx: &mut C) -> Result<(Cid, Vec<Cid>), Error>
+    where
+        Self: 'static,
+    {
+        SelectorSeed::flush(params, self, ctx)
     }
 
     // fn patch<S: Select<C>>(seed: ContextSeed<'_, C, Self, S>) -> Result<(), Error> {
@@ -134,6 +183,7 @@ mod params {
         pub(crate) selector: Option<&'a Selector>,
         pub(crate) max_path_depth: Option<usize>,
         pub(crate) max_link_depth: Option<usize>,
+        pub(crate) max_recursion_depth: Option<usize>,
         pub(crate) callback: Callback<'a, C, T>,
     }
 
@@ -148,6 +198,7 @@ mod params {
                 selector: None,
                 max_path_depth: None,
                 max_link_depth: None,
+                max_recursion_depth: None,
                 callback: Default::default(),
             }
         }
@@ -190,50 +241,69 @@ mod params {
             self
         }
 
-        ///
+        /// Sets the maximum number of times an [`ExploreRecursive`] selector
+        /// without a depth limit may recurse along any path, defaulting to
+        /// 1024.
+        pub fn with_max_recursion_depth(mut self, max_recursion_depth: usize) -> Self {
+            self.max_recursion_depth.replace(max_recursion_depth);
+            self
+        }
+
+        /// Patches each matched dag in-place with the provided op, which
+        /// expects matched dags to be of type `U`.
+        pub fn with_patch_op<U, F>(mut self, mut op: F) -> Self
+        where
+            C: 'a,
+            U: Representation + 'static,
+            F: FnMut(&mut U, &mut C) -> Result<(), Error> + 'a,
+        {
+            self.callback = Callback::Patch {
+                op: Box::new(move |dag: &mut dyn std::any::Any, ctx: &mut C| {
+                    let dag = dag.downcast_mut::<U>().ok_or_else(|| {
+                        Error::downcast_failure::<U>("incorrect type of matched dag")
+                    })?;
+                    op(dag, ctx)
+                }),
+            };
+            self
+        }
+
+        /// Selects nodes lazily, selecting against each linked block only
+        /// once the returned iterator reaches the link.
         pub fn into_node_iter(
             self,
             only_matched: bool,
             ctx: &mut C,
-        ) -> Result<IntoIter<NodeSelection>, Error>
+        ) -> Result<SelectionIter<'_, C, NodeSelection>, Error>
         where
+            C: 'a,
             T: Select<C>,
         {
-            let vec = RefCell::new(Vec::new());
+            let queue = Rc::default();
             let params = Params {
-                callback: Callback::SelectNode {
-                    only_matched,
-                    cb: Box::new(|node, _| {
-                        vec.borrow_mut().push(node);
-                        Ok(())
-                    }),
-                },
+                callback: node_callback(Rc::downgrade(&queue), only_matched),
                 ..self
             };
 
             T::select(params, ctx)?;
-            Ok(vec.into_inner().into_iter())
+            Ok(SelectionIter::new(queue, only_matched, ctx))
         }
 
-        ///
-        /// TODO: make this more like an actual iterator, that can pause across links
-        pub fn into_dag_iter(self, ctx: &mut C) -> Result<IntoIter<DagSelection>, Error>
+        /// Selects matched dags lazily, selecting against each linked block
+        /// only once the returned iterator reaches the link.
+        pub fn into_dag_iter(self, ctx: &mut C) -> Result<SelectionIter<'_, C, DagSelection>, Error>
         where
+            C: 'a,
             T: Select<C>,
         {
-            let vec = RefCell::new(Vec::new());
+            let queue = Rc::default();
             let params = Params {
-                callback: Callback::SelectDag {
-                    cb: Box::new(|node, _| {
-                        vec.borrow_mut().push(node);
-                        Ok(())
-                    }),
-                },
+                callback: dag_callback(Rc::downgrade(&queue)),
                 ..self
             };
 
             T::select(params, ctx)?;
-            Ok(vec.into_inner().into_iter())
+            Ok(SelectionIter::new(queue, true, ctx))
         }
 
         /*
@@ -257,6 +327,290 @@ mod params {
         }
          */
     }
+
+    #[cfg(feature = "async")]
+    impl<'a, T> Params<'a, AsyncSelectionContext, T>
+    where
+        T: Representation,
+    {
+        /// Selects nodes like [`Params::into_node_iter`], but yields them as a
+        /// [`SelectionStream`], awaiting blocks from an [`AsyncContext`] as
+        /// they're needed.
+        ///
+        /// Selection against a linked block is deferred until the stream
+        /// reaches the link, at which point the block is awaited and selected
+        /// against once. Blocks are dropped once selected against, so only
+        /// the blocks of the current link are held at a time.
+        ///
+        /// The stream is `!Send`, so must be polled on a single thread (see
+        /// [`SelectionStream`]).
+        pub fn into_node_stream<'b, A>(
+            self,
+            only_matched: bool,
+            ctx: &'b mut A,
+        ) -> SelectionStream<'b>
+        where
+            'a: 'b,
+            A: AsyncContext,
+            T: Select<AsyncSelectionContext> + 'b,
+        {
+            let Params {
+                cid,
+                selector,
+                max_path_depth,
+                max_link_depth,
+                max_recursion_depth,
+                ..
+            } = self;
+
+            /// The block to select against next.
+            enum Job<C> {
+                Root,
+                Link(Box<DeferredLink<C>>),
+            }
+
+            struct StreamState<'b, A> {
+                ctx: &'b mut A,
+                blocks: AsyncSelectionContext,
+                queue: Rc<RefCell<Vec<Deferred<AsyncSelectionContext>>>>,
+                pending: VecDeque<Deferred<AsyncSelectionContext>>,
+                started: bool,
+                error: Option<Error>,
+            }
+
+            let state = StreamState {
+                ctx,
+                blocks: AsyncSelectionContext::default(),
+                queue: Rc::default(),
+                pending: VecDeque::new(),
+                started: false,
+                error: None,
+            };
+
+            SelectionStream::from(futures::stream::unfold(
+                state,
+                move |mut state| async move {
+                    loop {
+                        let job = if !state.started {
+                            state.started = true;
+                            Job::Root
+                        } else {
+                            match state.pending.pop_front() {
+                                Some(Deferred::Node(node)) => return Some((Ok(node), state)),
+                                Some(Deferred::Link(link)) => Job::Link(Box::new(link)),
+                                Some(Deferred::Dag(_)) => unreachable!(),
+                                None => return state.error.take().map(|err| (Err(err), state)),
+                            }
+                        };
+
+                        // select against the job's block once it's loaded,
+                        // only retrying if selection reads another block
+                        // (e.g. to evaluate a condition or reify an ADL)
+                        let mut missing = match &job {
+                            Job::Root => cid,
+                            Job::Link(link) => Some(link.cid),
+                        };
+                        let res = loop {
+                            if let Some(cid) = missing.filter(|cid| !state.blocks.is_loaded(cid)) {
+                                match state.ctx.block_bytes(&cid).await {
+                                    Ok(block) => state.blocks.load(cid, block),
+                                    Err(err) => break Err(err),
+                                }
+                            }
+
+                            let queue = Rc::downgrade(&state.queue);
+                            let res = match &job {
+                                Job::Root => T::select(
+                                    Params {
+                                        cid,
+                                        selector,
+                                        max_path_depth,
+                                        max_link_depth,
+                                        max_recursion_depth,
+                                        callback: node_callback(queue, only_matched),
+                                    },
+                                    &mut state.blocks,
+                                ),
+                                Job::Link(link) => (**link)
+                                    .clone()
+                                    .select(node_callback(queue, only_matched), &mut state.blocks),
+                            };
+                            match (res, state.blocks.take_missing()) {
+                                (Err(_), Some(cid)) => {
+                                    state.queue.take();
+                                    missing = Some(cid);
+                                }
+                                (res, _) => break res,
+                            }
+                        };
+
+                        // blocks are only kept while selecting against them
+                        state.blocks.clear();
+                        if let Err(err) = res {
+                            state.pending.clear();
+                            state.error.replace(err);
+                        }
+
+                        // selections within the block precede the rest
+                        for selection in state.queue.take().into_iter().rev() {
+                            state.pending.push_front(selection);
+                        }
+                    }
+                },
+            ))
+        }
+    }
+}
+
+mod iter {
+    use super::*;
+    use std::{fmt, marker::PhantomData};
+
+    /// A lazy iterator of selections, returned by [`Params::into_node_iter`]
+    /// and [`Params::into_dag_iter`].
+    ///
+    /// Selection against a linked block is deferred until the iterator
+    /// reaches the link, so blocks are only read from the [`Context`] as the
+    /// iterator is advanced, and dropping the iterator stops the selection.
+    /// Should selection fail, the selections made before the failure are
+    /// yielded, followed by the error.
+    #[must_use = "iterators are lazy and do nothing unless consumed"]
+    pub struct SelectionIter<'a, C, S> {
+        ctx: &'a mut C,
+        queue: Rc<RefCell<Vec<Deferred<C>>>>,
+        pending: VecDeque<Deferred<C>>,
+        only_matched: bool,
+        error: Option<Error>,
+        _selection: PhantomData<S>,
+    }
+
+    impl<'a, C, S> SelectionIter<'a, C, S>
+    where
+        C: Context,
+    {
+        pub(crate) fn new(
+            queue: Rc<RefCell<Vec<Deferred<C>>>>,
+            only_matched: bool,
+            ctx: &'a mut C,
+        ) -> Self {
+            let pending = queue.take().into();
+            Self {
+                ctx,
+                queue,
+                pending,
+                only_matched,
+                error: None,
+                _selection: PhantomData,
+            }
+        }
+
+        /// Pops the next selection, selecting against deferred links until
+        /// one is reached.
+        fn next_selection<F>(&mut self, callback: F) -> Option<Result<Deferred<C>, Error>>
+        where
+            F: Fn(DeferredQueue<C>, bool) -> Callback<'a, C, Any>,
+        {
+            loop {
+                match self.pending.pop_front() {
+                    Some(Deferred::Link(link)) => {
+                        let callback = callback(Rc::downgrade(&self.queue), self.only_matched);
+                        let res = link.select(callback, self.ctx);
+                        if let Err(err) = res {
+                            self.pending.clear();
+                            self.error.replace(err);
+                        }
+
+                        // selections within the block precede the rest
+                        for selection in self.queue.take().into_iter().rev() {
+                            self.pending.push_front(selection);
+                        }
+                    }
+                    Some(selection) => return Some(Ok(selection)),
+                    None => return self.error.take().map(Err),
+                }
+            }
+        }
+    }
+
+    impl<'a, C> Iterator for SelectionIter<'a, C, NodeSelection>
+    where
+        C: Context + 'a,
+    {
+        type Item = Result<NodeSelection, Error>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.next_selection(node_callback)
+                .map(|selection| match selection? {
+                    Deferred::Node(node) => Ok(node),
+                    _ => unreachable!(),
+                })
+        }
+    }
+
+    impl<'a, C> Iterator for SelectionIter<'a, C, DagSelection>
+    where
+        C: Context + 'a,
+    {
+        type Item = Result<DagSelection, Error>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.next_selection(|queue, _| dag_callback(queue))
+                .map(|selection| match selection? {
+                    Deferred::Dag(dag) => Ok(dag),
+                    _ => unreachable!(),
+                })
+        }
+    }
+
+    impl<'a, C, S> fmt::Debug for SelectionIter<'a, C, S> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SelectionIter")
+                .field("pending", &self.pending.len())
+                .field("only_matched", &self.only_matched)
+                .field("error", &self.error)
+                .finish_non_exhaustive()
+        }
+    }
+
+    /// A callback pushing selected nodes and links to the queue.
+    pub(crate) fn node_callback<'a, C, T>(
+        queue: DeferredQueue<C>,
+        only_matched: bool,
+    ) -> Callback<'a, C, T>
+    where
+        C: Context + 'a,
+    {
+        let deferred = Some(queue.clone());
+        Callback::SelectNode {
+            cb: Box::new(move |node, _| {
+                push(&queue, Deferred::Node(node));
+                Ok(())
+            }),
+            only_matched,
+            deferred,
+        }
+    }
+
+    /// A callback pushing selected dags and links to the queue.
+    pub(crate) fn dag_callback<'a, C, T>(queue: DeferredQueue<C>) -> Callback<'a, C, T>
+    where
+        C: Context + 'a,
+    {
+        let deferred = Some(queue.clone());
+        Callback::SelectDag {
+            cb: Box::new(move |dag, _| {
+                push(&queue, Deferred::Dag(dag));
+                Ok(())
+            }),
+            deferred,
+        }
+    }
+
+    fn push<C>(queue: &DeferredQueue<C>, selection: Deferred<C>) {
+        if let Some(queue) = queue.upgrade() {
+            queue.borrow_mut().push(selection);
+        }
+    }
 }
 
 mod selection {
@@ -451,6 +805,24 @@ mod selection {
                 Self::Link(_) => Kind::Link,
             }
         }
+
+        /// Narrows a selected string or bytes node to the given [`Slice`],
+        /// returning `None` if the slice matches nothing, or splits one of a
+        /// string's characters. Nodes of any other kind are returned whole.
+        ///
+        /// Bytes are sliced without copying.
+        pub fn subset(self, slice: &Slice) -> Option<Self> {
+            match self {
+                Self::String(s) => slice
+                    .range(s.len())
+                    .and_then(|range| s.as_str().get(range))
+                    .map(|s| Self::String(s.into())),
+                Self::Bytes(bytes) => slice
+                    .range(bytes.len())
+                    .map(|range| Self::Bytes(bytes.slice(range))),
+                node => Some(node),
+            }
+        }
     }
 
     impl From<Null> for SelectedNode {
@@ -645,7 +1017,8 @@ mod field {
 
 #[cfg(test)]
 mod tests {
-    use crate::prelude::*;
+    use crate::{dev::*, prelude::*};
+    use std::{cell::RefCell, path::PathBuf, rc::Rc};
 
     schema! {
         #[ipld_attr(internal)]
@@ -655,18 +1028,27 @@ mod tests {
 
     schema! {
         #[ipld_attr(internal)]
-        #[derive(Debug, PartialEq)]
+        #[derive(Clone, Debug, PartialEq)]
         type Test struct {
             field1 Int,
             field2 String,
         };
     }
 
+    schema! {
+        #[ipld_attr(internal)]
+        #[derive(Clone, Debug, PartialEq)]
+        type Entry struct {
+            name IpldString,
+            value Int (rename "v"),
+        };
+    }
+
     #[test]
     fn it_works() {
         let t = Test {
             field1: Int::from(0),
-            field2: String::default(),
+            field2: IpldString::default(),
         };
 
         // let executor = Executor
@@ -686,4 +1068,1119 @@ mod tests {
 
         assert_eq!(true, true);
     }
+
+    #[test]
+    fn test_lazy_iter() {
+        let mut ctx = MemoryContext::default();
+        let leaves = ["a", "b", "c"]
+            .iter()
+            .map(|leaf| {
+                let leaf = vec![Bytes::copy_from_slice(leaf.as_bytes())];
+                ctx.add_dag(&leaf, DagCbor::CODE, Multihash::SHA2_256)
+                    .map(Link::<List<Bytes>>::Cid)
+            })
+            .collect::<Result<List<_>, _>>()
+            .unwrap();
+        let root = ctx
+            .add_dag(&leaves, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let selector = Selector::ExploreAll(Rc::new(ExploreAll {
+            next: Selector::ExploreAll(Rc::new(ExploreAll {
+                next: Selector::DEFAULT,
+            })),
+        }));
+
+        // only the root block is read before iterating
+        let mut ctx = CountingContext(ctx, 0);
+        let mut iter = Params::<'_, _, List<Link<List<Bytes>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut ctx)
+            .unwrap();
+        let first = iter.next().unwrap().unwrap();
+        assert_eq!(first.path.to_str(), Some(""));
+        drop(iter);
+        assert_eq!(ctx.1, 1);
+
+        // linked blocks are read as the iterator reaches them
+        let mut iter = Params::<'_, _, List<Link<List<Bytes>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(true, &mut ctx)
+            .unwrap();
+        let first = iter.next().unwrap().unwrap();
+        assert_eq!(first.path.to_str(), Some("0/0"));
+        drop(iter);
+        assert_eq!(ctx.1, 3);
+
+        let selected = Params::<'_, _, List<Link<List<Bytes>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(true, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| node.path))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(selected, ["0/0", "1/0", "2/0"].map(PathBuf::from).to_vec());
+        assert_eq!(ctx.1, 7);
+    }
+
+    #[test]
+    fn test_patch_in() {
+        let mut ctx = MemoryContext::default();
+        let mut dag = [[1, 2], [3, 4]]
+            .iter()
+            .map(|ints| {
+                let ints = ints.iter().copied().map(Int::from).collect::<List<_>>();
+                ctx.add_dag(&ints, DagCbor::CODE, Multihash::SHA2_256)
+                    .map(Link::<List<Int>>::Cid)
+            })
+            .collect::<Result<List<_>, _>>()
+            .unwrap();
+        let unpatched = dag[0].clone();
+        let selector = Selector::ExploreIndex(Rc::new(ExploreIndex {
+            index: 1,
+            next: Selector::ExploreAll(Rc::new(ExploreAll {
+                next: Selector::DEFAULT,
+            })),
+        }));
+
+        // the op's state is shared across every patched dag
+        let mut patched = 0;
+        let params = Params::<'_, _, List<Link<List<Int>>>>::default()
+            .with_selector(&selector)
+            .with_patch_op(|int: &mut Int, _: &mut MemoryContext| {
+                patched += 1;
+                *int += 10 * patched;
+                Ok(())
+            });
+        dag.patch_in(params, &mut ctx).unwrap();
+        assert_eq!(patched, 2);
+
+        // only the selected link is loaded, patched and marked dirty
+        assert_eq!(dag[0], unpatched);
+        match &dag[1] {
+            Link::Inner { t, dirty, .. } => {
+                assert_eq!(t, &[13, 24].map(Int::from).to_vec());
+                assert!(dirty);
+            }
+            Link::Cid(_) => panic!("patched link should be loaded"),
+        }
+
+        // scalars cannot be explored
+        let mut int = Int::from(1);
+        let params = Params::<'_, _, Int>::default()
+            .with_selector(&selector)
+            .with_patch_op(|_: &mut Int, _: &mut MemoryContext| Ok(()));
+        assert!(matches!(
+            int.patch_in(params, &mut ctx),
+            Err(Error::UnsupportedSelector { .. })
+        ));
+    }
+
+    #[test]
+    fn test_flush() {
+        let mut ctx = MemoryContext::default();
+        let mut dag = [[1, 2], [3, 4]]
+            .iter()
+            .map(|ints| {
+                let ints = ints.iter().copied().map(Int::from).collect::<List<_>>();
+                ctx.add_dag(&ints, DagCbor::CODE, Multihash::SHA2_256)
+                    .map(Link::<List<Int>>::Cid)
+            })
+            .collect::<Result<List<_>, _>>()
+            .unwrap();
+        let root = ctx
+            .add_dag(&dag, DagJson::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let selector = Selector::ExploreAll(Rc::new(ExploreAll {
+            next: Selector::ExploreIndex(Rc::new(ExploreIndex {
+                index: 0,
+                next: Selector::DEFAULT,
+            })),
+        }));
+
+        let params = Params::<'_, _, List<Link<List<Int>>>>::default()
+            .with_selector(&selector)
+            .with_patch_op(|int: &mut Int, _: &mut MemoryContext| {
+                *int *= 10;
+                Ok(())
+            });
+        dag.patch_in(params, &mut ctx).unwrap();
+        let params = Params::<'_, _, List<Link<List<Int>>>>::new_select(root);
+        let (new_root, written) = dag.flush(params, &mut ctx).unwrap();
+
+        // both links and the root are re-encoded like the originals
+        assert_eq!(written.len(), 3);
+        assert_eq!(written[2], new_root);
+        assert_ne!(new_root, root);
+        assert_eq!(new_root.multicodec_code(), DagJson::CODE);
+        for link in &dag {
+            assert!(matches!(link, Link::Inner { dirty: false, .. }));
+            assert_eq!(link.cid().multicodec_code(), DagCbor::CODE);
+        }
+
+        let block = ctx.block_reader(&new_root).unwrap();
+        let mut flushed: List<Link<List<Int>>> = Multicodec::try_from(&new_root)
+            .and_then(|mut codec| codec.read(block))
+            .unwrap();
+        let ints = flushed
+            .iter_mut()
+            .map(|link| link.resolve(&mut ctx).cloned())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            ints,
+            [[10, 2], [30, 4]].map(|ints| ints.map(Int::from).to_vec())
+        );
+    }
+
+    #[test]
+    fn test_select_in() {
+        let mut ctx = MemoryContext::default();
+        let mut dag = [[1, 2], [3, 4]]
+            .iter()
+            .map(|ints| {
+                let ints = ints.iter().copied().map(Int::from).collect::<List<_>>();
+                ctx.add_dag(&ints, DagCbor::CODE, Multihash::SHA2_256)
+                    .map(Link::<List<Int>>::Cid)
+            })
+            .collect::<Result<List<_>, _>>()
+            .unwrap();
+        let root = ctx
+            .add_dag(&dag, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let selector = Selector::ExploreAll(Rc::new(ExploreAll {
+            next: Selector::ExploreRange(Rc::new(ExploreRange {
+                start: 1,
+                end: 2,
+                next: Selector::DEFAULT,
+            })),
+        }));
+
+        let expected = Params::<'_, _, List<Link<List<Int>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| format!("{:?}", node)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        // only the unresolved link's block is read
+        dag[0].resolve(&mut ctx).unwrap();
+        let mut ctx = CountingContext(ctx, 0);
+        let selected = select_in_nodes(&dag, &selector, &mut ctx);
+
+        assert_eq!(ctx.1, 1);
+        assert_eq!(selected, expected);
+        assert_eq!(expected.len(), 5);
+    }
+
+    fn explore_fields(fields: &[(&str, Selector)]) -> Selector {
+        Selector::ExploreFields(ExploreFields {
+            fields: fields
+                .iter()
+                .map(|(field, next)| (IpldString::from(*field), next.clone()))
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_explore_fields_dag_json() {
+        let selector = explore_fields(&[(
+            "foo",
+            explore_fields(&[("bar", explore_fields(&[("baz", Selector::DEFAULT)]))]),
+        )]);
+        let json = r#"{"f":{"f>":{"foo":{"f":{"f>":{"bar":{"f":{"f>":{"baz":{".":{}}}}}}}}}}}"#;
+
+        let bytes = DagJson.encode(&selector).unwrap();
+        assert_eq!(std::str::from_utf8(&bytes).unwrap(), json);
+        let decoded: Selector = DagJson.decode(json.as_bytes()).unwrap();
+        assert_eq!(DagJson.encode(&decoded).unwrap(), bytes);
+    }
+
+    /// Selects nodes from an in-memory dag with the params' selector and
+    /// limits, returning the selected nodes or the error that stopped
+    /// selection.
+    fn try_select_in_nodes<C, T>(
+        dag: &T,
+        params: Params<'_, C, T>,
+        only_matched: bool,
+        ctx: &mut C,
+    ) -> Result<Vec<NodeSelection>, Error>
+    where
+        C: Context,
+        T: Select<C> + 'static,
+    {
+        let nodes = Rc::new(RefCell::new(Vec::new()));
+        let selected = nodes.clone();
+        let params = Params {
+            callback: Callback::SelectNode {
+                cb: Box::new(move |node: NodeSelection, _: &mut C| {
+                    selected.borrow_mut().push(node);
+                    Ok(())
+                }),
+                only_matched,
+                deferred: None,
+            },
+            ..params
+        };
+        dag.select_in(params, ctx)?;
+        let nodes = nodes.take();
+        Ok(nodes)
+    }
+
+    /// Selects nodes from an in-memory dag, returning their debug strings.
+    fn select_in_nodes<C, T>(dag: &T, selector: &Selector, ctx: &mut C) -> Vec<String>
+    where
+        C: Context,
+        T: Select<C> + 'static,
+    {
+        let params = Params::default().with_selector(selector);
+        try_select_in_nodes(dag, params, false, ctx)
+            .unwrap()
+            .iter()
+            .map(|node| format!("{:?}", node))
+            .collect()
+    }
+
+    #[test]
+    fn test_explore_fields() {
+        let mut ctx = MemoryContext::default();
+        let selector = explore_fields(&[("a", Selector::DEFAULT), ("c", Selector::DEFAULT)]);
+        let map = [("a", 1), ("b", 2), ("c", 3)]
+            .into_iter()
+            .map(|(key, val)| (IpldString::from(key), Int::from(val)))
+            .collect::<Map<_, _>>();
+        let root = ctx
+            .add_dag(&map, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+
+        // only the listed fields are selected from the block...
+        let expected = Params::<'_, _, Map<IpldString, Int>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| node.path))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected, ["", "a", "c"].map(PathBuf::from).to_vec());
+        let expected = Params::<'_, _, Map<IpldString, Int>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| format!("{:?}", node)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        // ... and from the same map in-memory, typed or untyped
+        assert_eq!(select_in_nodes(&map, &selector, &mut ctx), expected);
+        let any = Any::Map(map.into_iter().map(|(k, v)| (k, Any::Int(v))).collect());
+        assert_eq!(select_in_nodes(&any, &selector, &mut ctx), expected);
+    }
+
+    #[test]
+    fn test_explore_struct_fields() {
+        let mut ctx = MemoryContext::default();
+        let entry = Entry {
+            name: IpldString::from("entry"),
+            value: Int::from(1),
+        };
+        let root = ctx
+            .add_dag(&entry, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let selector = explore_fields(&[("v", Selector::DEFAULT), ("missing", Selector::DEFAULT)]);
+
+        let expected = Params::<'_, _, Entry>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| format!("{:?}", node)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected.len(), 2);
+        assert!(expected[1].contains(r#"path: "v""#));
+        assert_eq!(select_in_nodes(&entry, &selector, &mut ctx), expected);
+
+        // matching the struct itself
+        let selected = Params::<'_, _, Entry>::new_select(root)
+            .into_dag_iter(&mut ctx)
+            .unwrap()
+            .map(|dag| dag.and_then(|dag| dag.dag.downcast::<Entry>()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(selected, [entry]);
+    }
+
+    #[test]
+    fn test_explore_union_dag_json() {
+        let selector = Selector::ExploreUnion(ExploreUnion::from_iter([
+            explore_fields(&[("a", Selector::DEFAULT)]),
+            Selector::ExploreIndex(Rc::new(ExploreIndex {
+                index: 1,
+                next: Selector::DEFAULT,
+            })),
+        ]));
+        let json = r#"{"|":[{"f":{"f>":{"a":{".":{}}}}},{"i":{"i":1,">":{".":{}}}}]}"#;
+
+        let bytes = DagJson.encode(&selector).unwrap();
+        assert_eq!(std::str::from_utf8(&bytes).unwrap(), json);
+        let decoded: Selector = DagJson.decode(json.as_bytes()).unwrap();
+        assert_eq!(DagJson.encode(&decoded).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_explore_union() {
+        let mut ctx = MemoryContext::default();
+        let map = [("a", 1), ("b", 2), ("c", 3)]
+            .into_iter()
+            .map(|(key, val)| (IpldString::from(key), Int::from(val)))
+            .collect::<Map<_, _>>();
+        let root = ctx
+            .add_dag(&map, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let select = |selector: &Selector, ctx: &mut MemoryContext| {
+            Params::<'_, _, Map<IpldString, Int>>::new_select(root)
+                .with_selector(selector)
+                .into_node_iter(true, ctx)
+                .unwrap()
+                .map(|node| node.map(|node| node.path))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+
+        // members are merged, matching each path once
+        let selector = Selector::ExploreUnion(ExploreUnion::from_iter([
+            explore_fields(&[("a", Selector::DEFAULT), ("b", Selector::DEFAULT)]),
+            explore_fields(&[("b", Selector::DEFAULT), ("c", Selector::DEFAULT)]),
+        ]));
+        assert_eq!(
+            select(&selector, &mut ctx),
+            ["a", "b", "c"].map(PathBuf::from)
+        );
+
+        // a matcher already matches everything beneath it
+        let selector = Selector::ExploreUnion(ExploreUnion::from_iter([
+            explore_fields(&[("a", Selector::DEFAULT)]),
+            Selector::DEFAULT,
+        ]));
+        assert_eq!(
+            select(&selector, &mut ctx),
+            ["", "a", "b", "c"].map(PathBuf::from)
+        );
+
+        // ... as does selecting the same map in-memory
+        let expected = Params::<'_, _, Map<IpldString, Int>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| format!("{:?}", node)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(select_in_nodes(&map, &selector, &mut ctx), expected);
+
+        // indices between those explored by each member are skipped
+        let list = (1..=4).map(Int::from).collect::<List<_>>();
+        let root = ctx
+            .add_dag(&list, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let explore_index = |index| {
+            Selector::ExploreIndex(Rc::new(ExploreIndex {
+                index,
+                next: Selector::DEFAULT,
+            }))
+        };
+        let selector = Selector::ExploreUnion(ExploreUnion::from_iter([
+            explore_index(2),
+            explore_index(0),
+        ]));
+        let expected = Params::<'_, _, List<Int>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| format!("{:?}", node)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected.len(), 3);
+        assert!(expected[1].contains(r#"path: "0""#) && expected[2].contains(r#"path: "2""#));
+        assert_eq!(select_in_nodes(&list, &selector, &mut ctx), expected);
+    }
+
+    #[test]
+    fn test_explore_union_recursive() {
+        // a chain of commits, each nesting its parent
+        let mut commit = None;
+        for header in ["c0", "c1", "c2"] {
+            let parents = commit.take().into_iter().collect::<List<_>>();
+            commit = Some(Any::Map(
+                [
+                    (IpldString::from("header"), Any::String(header.into())),
+                    (IpldString::from("parents"), Any::List(parents)),
+                ]
+                .into_iter()
+                .collect(),
+            ));
+        }
+
+        // take each commit's header, while recursively walking its parents
+        let edge = Selector::ExploreRecursiveEdge(ExploreRecursiveEdge {});
+        let selector = Selector::ExploreRecursive(Rc::new(ExploreRecursive {
+            sequence: Selector::ExploreUnion(ExploreUnion::from_iter([
+                explore_fields(&[("header", Selector::DEFAULT)]),
+                explore_fields(&[(
+                    "parents",
+                    Selector::ExploreAll(Rc::new(ExploreAll { next: edge })),
+                )]),
+            ])),
+            limit: RecursionLimit_None {}.into(),
+            stopAt: None,
+        }));
+
+        let params = Params::default().with_selector(&selector);
+        let selected = try_select_in_nodes(
+            &commit.unwrap(),
+            params,
+            true,
+            &mut MemoryContext::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            selected
+                .into_iter()
+                .map(|node| node.path)
+                .collect::<Vec<_>>(),
+            ["header", "parents/0/header", "parents/0/parents/0/header"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_explore_union_across_links() {
+        let mut ctx = MemoryContext::default();
+        let leaves = vec![Bytes::copy_from_slice(b"a"), Bytes::copy_from_slice(b"b")];
+        let leaves = ctx
+            .add_dag(&leaves, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let dag: List<Link<List<Bytes>>> = vec![Link::Cid(leaves)];
+        let root = ctx
+            .add_dag(&dag, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+
+        // match every node, while recursively exploring beneath each of them
+        let edge = Selector::ExploreRecursiveEdge(ExploreRecursiveEdge {});
+        let selector = Selector::ExploreRecursive(Rc::new(ExploreRecursive {
+            sequence: Selector::ExploreUnion(ExploreUnion::from_iter([
+                Selector::DEFAULT,
+                Selector::ExploreAll(Rc::new(ExploreAll { next: edge })),
+            ])),
+            limit: RecursionLimit_None {}.into(),
+            stopAt: None,
+        }));
+        let expected = ["", "0", "0/0", "0/1"].map(PathBuf::from);
+
+        let selected = Params::<'_, _, List<Link<List<Bytes>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(true, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| node.path))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(selected, expected);
+
+        // ... as when selecting the same dag in-memory
+        let params = Params::default().with_selector(&selector);
+        let selected = try_select_in_nodes(&dag, params, true, &mut ctx).unwrap();
+        assert_eq!(
+            selected
+                .into_iter()
+                .map(|node| node.path)
+                .collect::<Vec<_>>(),
+            expected
+        );
+
+        // ... or when selecting each matched dag
+        let selected = Params::<'_, _, List<Link<List<Bytes>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_dag_iter(&mut ctx)
+            .unwrap()
+            .map(|dag| dag.map(|dag| dag.path))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(selected, expected);
+    }
+
+    fn matcher_if(condition: Condition) -> Selector {
+        Selector::Matcher(Matcher {
+            onlyIf: Some(condition),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_conditions_dag_json() {
+        let selector = Selector::ExploreConditional(Rc::new(ExploreConditional {
+            condition: Condition::Condition_HasKind(Condition_HasKind {
+                kind: IpldString::from("map"),
+            }),
+            next: matcher_if(Condition::Condition_And(Condition_And {
+                conditions: vec![
+                    Condition::Condition_HasField(Rc::new(Condition_HasField {
+                        key: IpldString::from("a"),
+                        condition: Some(Condition::Condition_GreaterThan(Rc::new(
+                            Condition_GreaterThan { value: Any::Int(1) },
+                        ))),
+                    })),
+                    Condition::Condition_IsLink(Rc::new(Condition_IsLink { cid: None })),
+                ],
+            })),
+        }));
+        let json = r#"{"&":{"&":{"%":{"kind":"map"}},">":{".":{"onlyIf":{"and":{"conditions":[{"hasField":{"key":"a","condition":{"greaterThan":{"value":1}}}},{"/":{}}]}}}}}}"#;
+
+        let bytes = DagJson.encode(&selector).unwrap();
+        assert_eq!(std::str::from_utf8(&bytes).unwrap(), json);
+        let decoded: Selector = DagJson.decode(json.as_bytes()).unwrap();
+        assert_eq!(DagJson.encode(&decoded).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_conditions() {
+        let mut ctx = MemoryContext::default();
+        let list = (1..=4).map(Int::from).collect::<List<_>>();
+        let root = ctx
+            .add_dag(&list, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+
+        // only nodes satisfying a matcher's condition are matched...
+        let selector = Selector::ExploreAll(Rc::new(ExploreAll {
+            next: matcher_if(Condition::Condition_GreaterThan(Rc::new(
+                Condition_GreaterThan { value: Any::Int(2) },
+            ))),
+        }));
+        let expected = Params::<'_, _, List<Int>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(false, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| format!("{:?}", node)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(expected.len(), 3);
+        assert!(expected[1].contains(r#"path: "2""#) && expected[2].contains(r#"path: "3""#));
+
+        // ... in-memory as well
+        assert_eq!(select_in_nodes(&list, &selector, &mut ctx), expected);
+
+        // only links satisfying the condition are explored further
+        let leaves = (0..3)
+            .map(|i| {
+                ctx.add_dag(&vec![Int::from(i)], DagCbor::CODE, Multihash::SHA2_256)
+                    .map(Link::<List<Int>>::Cid)
+            })
+            .collect::<Result<List<_>, _>>()
+            .unwrap();
+        let root = ctx
+            .add_dag(&leaves, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let selector = Selector::ExploreAll(Rc::new(ExploreAll {
+            next: Selector::ExploreConditional(Rc::new(ExploreConditional {
+                condition: Condition::Condition_IsLink(Rc::new(Condition_IsLink {
+                    cid: Some(Link::Cid(*leaves[1].cid())),
+                })),
+                next: Selector::ExploreAll(Rc::new(ExploreAll {
+                    next: Selector::DEFAULT,
+                })),
+            })),
+        }));
+        let selected = Params::<'_, _, List<Link<List<Int>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(true, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| node.path))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(selected, [PathBuf::from("1/0")]);
+    }
+
+    #[test]
+    fn test_conditions_within_blocks() {
+        let mut ctx = MemoryContext::default();
+        let entry = |a: Option<i64>, b: &str| {
+            Any::Map(
+                a.map(|a| (IpldString::from("a"), Any::Int(a)))
+                    .into_iter()
+                    .chain([(IpldString::from("b"), Any::String(IpldString::from(b)))])
+                    .collect(),
+            )
+        };
+        let dag = [
+            vec![entry(Some(1), "x"), entry(None, "y")],
+            vec![entry(Some(3), "z")],
+        ]
+        .iter()
+        .map(|entries| {
+            ctx.add_dag(entries, DagCbor::CODE, Multihash::SHA2_256)
+                .map(Link::<List<Any>>::Cid)
+        })
+        .collect::<Result<List<_>, _>>()
+        .unwrap();
+        let root = ctx
+            .add_dag(&dag, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+
+        // conditions are evaluated against the root of each linked block, and
+        // against each entry within it
+        let selector = Selector::ExploreAll(Rc::new(ExploreAll {
+            next: Selector::ExploreConditional(Rc::new(ExploreConditional {
+                condition: Condition::Condition_HasKind(Condition_HasKind {
+                    kind: IpldString::from("list"),
+                }),
+                next: Selector::ExploreAll(Rc::new(ExploreAll {
+                    next: Selector::ExploreConditional(Rc::new(ExploreConditional {
+                        condition: Condition::Condition_HasField(Rc::new(Condition_HasField {
+                            key: IpldString::from("a"),
+                            condition: None,
+                        })),
+                        next: explore_fields(&[("b", Selector::DEFAULT)]),
+                    })),
+                })),
+            })),
+        }));
+        let expected = ["0/0/b", "1/0/b"].map(PathBuf::from);
+
+        let selected = Params::<'_, _, List<Link<List<Any>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(true, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| node.path))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(selected, expected);
+
+        // ... as when selecting the same dag in-memory
+        let params = Params::default().with_selector(&selector);
+        let selected = try_select_in_nodes(&dag, params, true, &mut ctx).unwrap();
+        assert_eq!(
+            selected
+                .into_iter()
+                .map(|node| node.path)
+                .collect::<Vec<_>>(),
+            expected
+        );
+
+        // ... or when selecting each matched dag
+        let selected = Params::<'_, _, List<Link<List<Any>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_dag_iter(&mut ctx)
+            .unwrap()
+            .map(|dag| dag.map(|dag| dag.path))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(selected, expected);
+    }
+
+    #[test]
+    fn test_matcher_subset() {
+        let mut ctx = MemoryContext::default();
+        let list: List<Bytes> = vec![Bytes::copy_from_slice(b"hello world")];
+        let root = ctx
+            .add_dag(&list, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let subset_selector = |from: Int, to: Int| {
+            Selector::ExploreAll(Rc::new(ExploreAll {
+                next: Selector::Matcher(Matcher {
+                    subset: Some(Slice { from, to }),
+                    ..Default::default()
+                }),
+            }))
+        };
+
+        for ((from, to), expected) in [
+            ((0, 5), Some(&b"hello"[..])),
+            ((6, 100), Some(b"world")),
+            ((-5, -1), Some(b"worl")),
+            ((-100, 2), Some(b"he")),
+            ((3, 3), Some(b"")),
+            ((20, 30), Some(b"")),
+            ((5, 2), None),
+            ((-2, -5), None),
+        ] {
+            let selector = subset_selector(from, to);
+            let selected = Params::<'_, _, List<Bytes>>::new_select(root)
+                .with_selector(&selector)
+                .into_node_iter(true, &mut ctx)
+                .unwrap()
+                .map(|node| node.map(|node| format!("{:?}", node)))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let expected = expected
+                .map(|bytes| {
+                    NodeSelection::new_match(
+                        std::path::Path::new("0"),
+                        SelectedNode::Bytes(Bytes::copy_from_slice(bytes)),
+                        None,
+                    )
+                })
+                .map(|node| format!("{:?}", node))
+                .into_iter()
+                .collect::<Vec<_>>();
+            assert_eq!(selected, expected);
+
+            // ... in-memory as well
+            let selected = select_in_nodes(&list, &selector, &mut ctx);
+            assert_eq!(&selected[1..], &expected);
+        }
+
+        // in-memory bytes are sliced without copying
+        let selector = subset_selector(6, 11);
+        let ptr = Rc::new(std::cell::Cell::new(None));
+        let params = Params::<'_, _, List<Bytes>> {
+            selector: Some(&selector),
+            callback: Callback::SelectNode {
+                cb: Box::new(|node: NodeSelection, _: &mut MemoryContext| {
+                    if let SelectedNode::Bytes(bytes) = node.node {
+                        ptr.set(Some(bytes.as_ptr()));
+                    }
+                    Ok(())
+                }),
+                only_matched: true,
+                deferred: None,
+            },
+            ..Default::default()
+        };
+        list.select_in(params, &mut ctx).unwrap();
+        assert_eq!(ptr.get(), Some(list[0][6..].as_ptr()));
+
+        // strings are sliced by bytes, but only along character boundaries,
+        // skipping the strings whose characters would be split
+        let list: List<IpldString> = vec![IpldString::from("héllo"), IpldString::from("hello")];
+        let selected = select_in_nodes(&list, &subset_selector(3, 6), &mut ctx);
+        assert!(selected[1].contains(r#"String("llo")"#));
+        assert!(selected[2].contains(r#"String("lo")"#));
+        let selected = select_in_nodes(&list, &subset_selector(0, 2), &mut ctx);
+        assert_eq!(selected.len(), 2);
+        assert!(selected[1].contains(r#"path: "1""#) && selected[1].contains(r#"String("he")"#));
+
+        // matched dags are narrowed the same, from blocks and in-memory
+        fn select_dags<T>(
+            list: List<T>,
+            selector: &Selector,
+            ctx: &mut MemoryContext,
+        ) -> [List<T>; 2]
+        where
+            T: Representation + Clone + 'static,
+            List<T>: Select<MemoryContext>,
+        {
+            let root = ctx
+                .add_dag(&list, DagCbor::CODE, Multihash::SHA2_256)
+                .unwrap();
+            let from_block = Params::<'_, _, List<T>>::new_select(root)
+                .with_selector(selector)
+                .into_dag_iter(ctx)
+                .unwrap()
+                .map(|dag| dag.and_then(|dag| dag.dag.downcast::<T>()))
+                .collect::<Result<List<_>, _>>()
+                .unwrap();
+
+            let dags = Rc::new(RefCell::new(List::new()));
+            let params = Params::<'_, _, List<T>> {
+                selector: Some(selector),
+                callback: Callback::SelectDag {
+                    cb: Box::new(|dag: DagSelection, _: &mut MemoryContext| {
+                        dags.borrow_mut().push(dag.dag.downcast::<T>()?);
+                        Ok(())
+                    }),
+                    deferred: None,
+                },
+                ..Default::default()
+            };
+            list.select_in(params, ctx).unwrap();
+            let in_memory = dags.take();
+            [from_block, in_memory]
+        }
+
+        let selector = subset_selector(1, 4);
+        let list = vec![IpldString::from("héllo"), IpldString::from("hi")];
+        let expected = vec![IpldString::from("él"), IpldString::from("i")];
+        assert_eq!(
+            select_dags::<IpldString>(list, &selector, &mut ctx),
+            [expected.clone(), expected]
+        );
+        let list = vec![IpldString::from("héllo"), IpldString::from("hello")];
+        let expected = vec![IpldString::from("he")];
+        assert_eq!(
+            select_dags::<IpldString>(list, &subset_selector(0, 2), &mut ctx),
+            [expected.clone(), expected]
+        );
+        let list = vec![Bytes::copy_from_slice(b"hello"), Bytes::new()];
+        let expected = vec![Bytes::copy_from_slice(b"ell"), Bytes::new()];
+        assert_eq!(
+            select_dags::<Bytes>(list, &selector, &mut ctx),
+            [expected.clone(), expected]
+        );
+    }
+
+    /// Reifies a list of bytes chunks as their concatenation.
+    struct Chunks;
+    impl Reifier for Chunks {
+        fn reify(
+            &self,
+            node: &Any,
+            _: &Selector,
+            load: &mut BlockLoader<'_>,
+        ) -> Result<Reified, Error> {
+            let chunks: List<Bytes> = match node {
+                Any::Link(link) => {
+                    let block = load(link.cid())?;
+                    Multicodec::try_from(link.cid())?.decode(&block)?
+                }
+                Any::List(list) => list
+                    .iter()
+                    .map(|chunk| chunk.clone().try_into().map_err(|_| Error::MismatchedAny))
+                    .collect::<Result<_, _>>()?,
+                _ => return Err(Error::MismatchedAny),
+            };
+            Ok(Reified::Node(Any::Bytes(Bytes::from(
+                chunks
+                    .iter()
+                    .flat_map(|c| c.iter().copied())
+                    .collect::<Vec<_>>(),
+            ))))
+        }
+    }
+
+    #[test]
+    fn test_adl_register() {
+        // standalone HAMTs aren't built-in, and must be registered
+        assert!(matches!(
+            Adl::from_name("hamt"),
+            Err(Error::UnknownAdl(name)) if name == "hamt"
+        ));
+
+        Adl::register("concat", Chunks).unwrap();
+        assert_eq!(Adl::from_name("concat").unwrap().name(), "concat");
+
+        // names are unique, among both registered and built-in ADLs
+        assert!(matches!(
+            Adl::register("concat", Chunks),
+            Err(Error::DuplicateAdl("concat"))
+        ));
+        assert!(matches!(
+            Adl::register("unixfs", Chunks),
+            Err(Error::DuplicateAdl("unixfs"))
+        ));
+    }
+
+    #[test]
+    fn test_explore_interpret_as() {
+        Adl::register("chunks", Chunks).unwrap();
+
+        let interpret_as = |name: &str, next: Selector| {
+            Selector::ExploreInterpretAs(Rc::new(ExploreInterpretAs {
+                r#as: IpldString::from(name),
+                next,
+            }))
+        };
+        let selector = interpret_as(
+            "chunks",
+            Selector::Matcher(Matcher {
+                subset: Some(Slice { from: 3, to: 8 }),
+                ..Default::default()
+            }),
+        );
+        let json = r#"{"~":{"as":"chunks",">":{".":{"subset":{"[":3,"]":8}}}}}"#;
+        assert_eq!(
+            std::str::from_utf8(&DagJson.encode(&selector).unwrap()).unwrap(),
+            json
+        );
+        let decoded: Selector = DagJson.decode(json.as_bytes()).unwrap();
+        assert!(decoded.is_explore_interpret_as());
+
+        // reifying the chunks behind a link...
+        let mut ctx = MemoryContext::default();
+        let chunks: List<Bytes> = ["hel", "lo wo", "rld"]
+            .iter()
+            .map(|chunk| Bytes::copy_from_slice(chunk.as_bytes()))
+            .collect();
+        let link = ctx
+            .add_dag(&chunks, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let dag: Map<IpldString, Link<List<Bytes>>> =
+            [(IpldString::from("video"), Link::Cid(link))].into();
+        let root = ctx
+            .add_dag(&dag, DagCbor::CODE, Multihash::SHA2_256)
+            .unwrap();
+        let selector = explore_fields(&[("video", selector)]);
+        let selected = Params::<'_, _, Map<IpldString, Link<List<Bytes>>>>::new_select(root)
+            .with_selector(&selector)
+            .into_node_iter(true, &mut ctx)
+            .unwrap()
+            .map(|node| node.map(|node| format!("{:?}", node)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected = format!(
+            "{:?}",
+            NodeSelection::new_match(
+                std::path::Path::new("video"),
+                SelectedNode::Bytes(Bytes::copy_from_slice(b"lo wo")),
+                None,
+            )
+        );
+        assert_eq!(selected, std::slice::from_ref(&expected));
+
+        // ... or in-memory
+        let dag: Any = Any::Map(
+            [(
+                IpldString::from("video"),
+                Any::List(chunks.into_iter().map(Any::Bytes).collect()),
+            )]
+            .into(),
+        );
+        assert_eq!(select_in_nodes(&dag, &selector, &mut ctx)[1..], [expected]);
+
+        // unknown ADLs fail selection
+        let selector = interpret_as("missing", Selector::DEFAULT);
+        let params = Params::<'_, _, Any> {
+            selector: Some(&selector),
+            ..Default::default()
+        };
+        assert!(matches!(
+            dag.select_in(params, &mut ctx),
+            Err(Error::UnknownAdl(_))
+        ));
+    }
+
+    #[test]
+    fn test_explore_recursive_stop_at() {
+        // a chain of loaded commits, each newer than its parent
+        let mut ctx = MemoryContext::default();
+        let mut commit: Option<Any> = None;
+        let mut cids = vec![];
+        for date in 1..=3 {
+            let parents = commit
+                .take()
+                .map(|parent| {
+                    let cid = ctx
+                        .add_dag(&parent, DagCbor::CODE, Multihash::SHA2_256)
+                        .unwrap();
+                    cids.push(cid);
+                    Any::Link(Rc::new(Link::Inner {
+                        cid,
+                        t: parent,
+                        dirty: false,
+                    }))
+                })
+                .into_iter()
+                .collect::<List<_>>();
+            commit = Some(Any::Map(
+                [
+                    (IpldString::from("date"), Any::Int(date)),
+                    (IpldString::from("parents"), Any::List(parents)),
+                ]
+                .into_iter()
+                .collect(),
+            ));
+        }
+        let commit = commit.unwrap();
+
+        // take each commit's date, while recursively walking its parents
+        let select = |stop_at, ctx: &mut MemoryContext| {
+            let edge = Selector::ExploreRecursiveEdge(ExploreRecursiveEdge {});
+            let selector = Selector::ExploreRecursive(Rc::new(ExploreRecursive {
+                sequence: Selector::ExploreUnion(ExploreUnion::from_iter([
+                    explore_fields(&[("date", Selector::DEFAULT)]),
+                    explore_fields(&[(
+                        "parents",
+                        Selector::ExploreAll(Rc::new(ExploreAll { next: edge })),
+                    )]),
+                ])),
+                limit: RecursionLimit_None {}.into(),
+                stopAt: Some(stop_at),
+            }));
+
+            let params = Params::default().with_selector(&selector);
+            try_select_in_nodes(&commit, params, true, ctx)
+                .unwrap()
+                .into_iter()
+                .map(|node| node.path)
+                .collect::<Vec<_>>()
+        };
+
+        // stopping at the commits older than a date, past the links to them...
+        let older_than = Condition::Condition_HasField(Rc::new(Condition_HasField {
+            key: IpldString::from("date"),
+            condition: Some(Condition::Condition_LessThan(Rc::new(Condition_LessThan {
+                value: Any::Int(2),
+            }))),
+        }));
+        assert_eq!(
+            select(older_than, &mut ctx),
+            ["date", "parents/0/date"].map(PathBuf::from)
+        );
+
+        // ... or at the link to a commit
+        let is_link = Condition::Condition_IsLink(Rc::new(Condition_IsLink {
+            cid: Some(Link::Cid(cids[1])),
+        }));
+        assert_eq!(select(is_link, &mut ctx), [PathBuf::from("date")]);
+    }
+
+    #[test]
+    fn test_explore_recursive_limit() {
+        // a chain of commits, each nesting its parent
+        let mut commit = None;
+        for date in 1..=3 {
+            let parents = commit.take().into_iter().collect::<List<_>>();
+            commit = Some(Any::Map(
+                [
+                    (IpldString::from("date"), Any::Int(date)),
+                    (IpldString::from("parents"), Any::List(parents)),
+                ]
+                .into_iter()
+                .collect(),
+            ));
+        }
+        let commit = commit.unwrap();
+
+        let select = |selector: &Selector, max_recursion_depth| {
+            let params = Params::default()
+                .with_selector(selector)
+                .with_max_recursion_depth(max_recursion_depth);
+            try_select_in_nodes(&commit, params, true, &mut MemoryContext::default())
+                .map(|nodes| nodes.into_iter().map(|node| node.path).collect::<Vec<_>>())
+        };
+
+        // an unbounded recursion recurses once per commit...
+        let edge = Selector::ExploreRecursiveEdge(ExploreRecursiveEdge {});
+        let selector = Selector::ExploreRecursive(Rc::new(ExploreRecursive {
+            sequence: Selector::ExploreUnion(ExploreUnion::from_iter([
+                explore_fields(&[("date", Selector::DEFAULT)]),
+                explore_fields(&[(
+                    "parents",
+                    Selector::ExploreAll(Rc::new(ExploreAll { next: edge.clone() })),
+                )]),
+            ])),
+            limit: RecursionLimit_None {}.into(),
+            stopAt: None,
+        }));
+        assert_eq!(
+            select(&selector, 3).unwrap(),
+            ["date", "parents/0/date", "parents/0/parents/0/date"].map(PathBuf::from)
+        );
+
+        // ... and fails if that exceeds the max recursion depth
+        assert!(matches!(
+            select(&selector, 2),
+            Err(Error::SelectorDepth(_, 2))
+        ));
+
+        // as does a recursion that never descends
+        let selector = Selector::ExploreRecursive(Rc::new(ExploreRecursive {
+            sequence: Selector::ExploreUnion(ExploreUnion::from_iter([
+                explore_fields(&[("date", Selector::DEFAULT)]),
+                edge,
+            ])),
+            limit: RecursionLimit_None {}.into(),
+            stopAt: None,
+        }));
+        assert!(matches!(
+            select(&selector, 16),
+            Err(Error::SelectorDepth(_, 16))
+        ));
+    }
 }
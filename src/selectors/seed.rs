@@ -1,6 +1,6 @@
 use super::*;
 use crate::dev::{macros::derive_more::From, *};
-use std::fmt;
+use std::{borrow::Cow, fmt, io::Read};
 
 /// A helper type for guided decoding of a dag, using a selector to direct
 /// and/or ignore fields or entire blocks, and a linked context to fetch more
@@ -23,9 +23,17 @@ pub struct SelectorSeed<'a, Ctx, T = Any> {
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct CodecSeed<const C: u64, S>(pub S);
-impl<'de, const C: u64, S> DeserializeSeed<'de> for CodecSeed<C, S>
+
+/// Normalizes the selector before selecting against the value, ignoring the
+/// value entirely if there is nothing left to select.
+///
+/// A value is only decoded in full if the selector has [`Condition`]s to
+/// evaluate against it or an [`Adl`] to interpret it with.
+impl<'a, 'de, const C: u64, Ctx, T> DeserializeSeed<'de> for CodecSeed<C, SelectorSeed<'a, Ctx, T>>
 where
-    S: CodecDeserializeSeed<'de>,
+    Ctx: Context,
+    T: Representation + 'static,
+    for<'b> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de>,
 {
     type Value = ();
     #[inline(always)]
@@ -33,7 +41,71 @@ where
     where
         D: Deserializer<'de>,
     {
-        CodecDeserializeSeed::<'_>::deserialize::<C, _>(self.0, deserializer)
+        if self.0.selector.is_conditional() {
+            let dag = <T as Representation>::deserialize::<C, _>(deserializer)?;
+            return self.select_decoded(dag).map_err(D::Error::custom);
+        }
+
+        let SelectorSeed {
+            selector,
+            state,
+            callback,
+            ctx,
+        } = self.0;
+        match selector.normalize(None, state).map_err(D::Error::custom)? {
+            Some(selector) if selector.is_explore_interpret_as() => {
+                let dag = <T as Representation>::deserialize::<C, _>(deserializer)?;
+                let node = any_node(&dag).map_err(D::Error::custom)?;
+                SelectorSeed::from(&selector, state, callback, ctx)
+                    .select_interpreted(&node)
+                    .map_err(D::Error::custom)
+            }
+            Some(selector) => {
+                let seed = SelectorSeed {
+                    selector: &selector,
+                    state,
+                    callback,
+                    ctx,
+                };
+                CodecDeserializeSeed::<'_>::deserialize::<C, _>(seed, deserializer)
+            }
+            None => {
+                IgnoredAny::deserialize(deserializer)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a, 'de, const C: u64, Ctx, T> CodecSeed<C, SelectorSeed<'a, Ctx, T>>
+where
+    Ctx: Context,
+    T: Representation + 'static,
+    for<'b> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de>,
+{
+    /// Evaluates the selector's [`Condition`]s against a decoded value, then
+    /// selects against the re-encoded value with the normalized selector.
+    fn select_decoded(self, dag: T) -> Result<(), Error> {
+        let (selector, state, callback, ctx) = self.0.into_parts();
+        let node = any_node(&dag)?;
+        match selector.normalize(Some(&node), state)? {
+            Some(selector) if selector.is_explore_interpret_as() => {
+                SelectorSeed::from(&selector, state, callback, ctx).select_interpreted(&node)
+            }
+            // the node is only borrowed if the value is itself an `Any`, whose
+            // (kinded union) representation is only selected against in-memory
+            Some(selector) if matches!(node, Cow::Borrowed(_)) => {
+                node.select_in_seed(SelectorSeed::from(&selector, state, callback.cast(), ctx))
+            }
+            Some(selector) => {
+                let mut codec = Multicodec::from_code::<C>()?;
+                let mut block = Vec::new();
+                codec.write(&dag, &mut block)?;
+                let seed = SelectorSeed::from(&selector, state, callback, ctx);
+                codec.read_with_seed(seed, block.as_slice())
+            }
+            None => Ok(()),
+        }
     }
 }
 
@@ -341,6 +413,11 @@ where
         self.callback.is_dag()
     }
 
+    #[inline]
+    pub(crate) const fn is_flush(&self) -> bool {
+        self.callback.is_flush()
+    }
+
     ///
     #[inline]
     pub(crate) fn from(
@@ -399,6 +476,7 @@ where
     pub fn select(params: Params<'_, Ctx, T>, mut ctx: &mut Ctx) -> Result<(), Error>
     where
         Ctx: 'a,
+        T: Select<Ctx> + 'static,
         // for<'b, 'de> SelectorSeed<'b, Ctx, T>: DeserializeSeed<'de, Value = ()>,
         for<'b, 'de> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de>,
         // for<'b, 'de> BlockSelectorSeed<0, SelectorSeed<'b, Ctx, T>>:
@@ -410,32 +488,70 @@ where
             selector,
             max_path_depth,
             max_link_depth,
+            max_recursion_depth,
             callback,
         } = params;
         let mut state = State {
             max_path_depth,
             max_link_depth,
+            max_recursion_depth,
             ..Default::default()
         };
 
         let root = cid.ok_or_else(|| {
             Error::InvalidSelectionParams("selection must start against some cid")
         })?;
-        let mut codec = Multicodec::try_from(&root)?;
-        let block = ctx.block_reader(&root)?;
+        if let Callback::Patch { .. } | Callback::Flush { .. } = callback {
+            return Err(Error::InvalidSelectionParams(
+                "patching requires a dag to patch in-place",
+            ));
+        }
         let default_selector = Self::DEFAULT_SELECTOR;
         let seed = SelectorSeed {
-            selector: &selector.unwrap_or(&default_selector),
+            selector: selector.unwrap_or(&default_selector),
             state: &mut state,
             callback,
             ctx: &mut ctx,
         };
+        seed.select_block(&root)
+    }
 
-        codec.read_with_seed(seed, block)
+    /// Selects against a block, streaming it unless the selector has
+    /// [`Condition`]s to evaluate against its root.
+    pub(crate) fn select_block<'de>(self, cid: &Cid) -> Result<(), Error>
+    where
+        T: Select<Ctx> + 'static,
+        for<'b> SelectorSeed<'b, Ctx, T>: CodecDeserializeSeed<'de>,
+    {
+        // a dag matched by a union is selected whole before being explored by
+        // the union's other members, and so is selected against in-memory
+        if self.selector.is_conditional() || (self.is_dag() && self.selector.has_matching_unions())
+        {
+            return self.select_block_in(cid);
+        }
+
+        let (selector, state, callback, ctx) = self.into_parts();
+        match selector.normalize(None, state)? {
+            Some(selector) if selector.is_explore_interpret_as() => {
+                SelectorSeed::from(&selector, state, callback, ctx)
+                    .select_interpreted(&Any::Link(Rc::new(Link::Cid(*cid))))
+            }
+            Some(selector) => {
+                let mut codec = Multicodec::try_from(cid)?;
+                let block = ctx.block_reader(cid)?;
+                codec.read_with_seed(SelectorSeed::from(&selector, state, callback, ctx), block)
+            }
+            None => Ok(()),
+        }
     }
 
-    ///
+    /// Selects an explored node, which is matched if the current selector is
+    /// a union with a [`Matcher`] member.
     pub(crate) fn select_node(&mut self, node: SelectedNode) -> Result<(), Error> {
+        let selector = self.selector;
+        if let Some(matcher) = selector.union_matcher() {
+            return self.select_matched_subset(node, matcher);
+        }
         self.callback
             .select_node(NodeSelection::new(self.state.path(), node), self.ctx)
     }
@@ -452,6 +568,41 @@ where
         )
     }
 
+    /// Selects a matched node, narrowed to the matcher's
+    /// [`subset`](Matcher::subset), if any.
+    pub(crate) fn select_matched_subset(
+        &mut self,
+        node: SelectedNode,
+        matcher: &Matcher,
+    ) -> Result<(), Error> {
+        let node = match &matcher.subset {
+            None => node,
+            Some(slice) => match node.subset(slice) {
+                Some(node) => node,
+                None => return Ok(()),
+            },
+        };
+        self.select_matched_node(node, matcher.label.as_deref())
+    }
+
+    /// Selects a matched dag, narrowed to the matcher's
+    /// [`subset`](Matcher::subset), if any.
+    pub(crate) fn select_matched_dag_subset(
+        &mut self,
+        mut dag: T,
+        matcher: &Matcher,
+    ) -> Result<(), Error>
+    where
+        T: Representation + 'static,
+    {
+        if let Some(slice) = &matcher.subset {
+            if !subset_dag(&mut dag, slice) {
+                return Ok(());
+            }
+        }
+        self.select_matched_dag(dag, matcher.label.as_deref())
+    }
+
     ///
     pub(crate) fn select_matched_dag(&mut self, dag: T, label: Option<&str>) -> Result<(), Error>
     where
@@ -461,11 +612,49 @@ where
             .select_dag(DagSelection::new(self.state.path(), dag, label), self.ctx)
     }
 
+    /// Reifies the node with the current [`ExploreInterpretAs`] selector's
+    /// [`Adl`], then selects against the reified node with the next selector.
+    pub(crate) fn select_interpreted(self, node: &Any) -> Result<(), Error> {
+        let (selector, state, callback, ctx) = self.into_parts();
+        let inner = selector
+            .as_explore_interpret_as()
+            .ok_or_else(|| Error::unsupported_selector::<T>(selector))?;
+
+        let adl = Adl::from_name(inner.r#as.as_str())?;
+        let reified = adl.reify(node, &inner.next, &mut |cid| {
+            let mut block = Vec::new();
+            ctx.block_reader(cid)?
+                .read_to_end(&mut block)
+                .map_err(|err| Error::Decoder(err.into()))?;
+            Ok(block)
+        })?;
+
+        match reified {
+            Reified::Node(node) => {
+                SelectorSeed::from(&inner.next, state, callback.cast(), ctx).select_in_dag(&node)
+            }
+            Reified::Subset(None) => Ok(()),
+            // the reifier has already narrowed the node to the matcher's subset
+            Reified::Subset(Some(node)) => {
+                let matcher = inner.next.matcher().ok_or_else(|| {
+                    Error::Reification(adl.name(), "reified a subset without a matcher".into())
+                })?;
+                let next = Selector::Matcher(Matcher {
+                    subset: None,
+                    ..matcher.clone()
+                });
+                SelectorSeed::from(&next, state, callback.cast(), ctx).select_in_dag(&node)
+            }
+        }
+    }
+
+    /// Produces a seed for selecting against the given field with its next
+    /// selector, as produced by [`Selector::next_selector`].
     #[inline]
     pub(crate) fn field_select_seed<'b, U>(
-        selector: &'b Selector,
+        next: &'b Selector,
         state: &'b mut State,
-        callback: &mut Callback<'a, Ctx, T>,
+        callback: &'b mut Callback<'a, Ctx, T>,
         ctx: &'b mut Ctx,
         field: Field<'b>,
         match_cb: Option<Box<dyn MatchDagOp<U, Ctx> + 'b>>,
@@ -476,17 +665,33 @@ where
         U: Representation,
         // F: FnOnce(U, Ctx) -> Result<(), Error>,
     {
-        let next = selector
-            .next(Some(&field))
-            .ok_or_else(|| Error::missing_next_selector(selector))?;
         let callback = match (match_cb, callback) {
             //
-            (None, Callback::SelectNode { cb, only_matched }) => Callback::SelectNode {
+            (
+                None,
+                Callback::SelectNode {
+                    cb,
+                    only_matched,
+                    deferred,
+                },
+            ) => Callback::SelectNode {
                 cb: cb.clone(),
                 only_matched: *only_matched,
+                deferred: deferred.clone(),
+            },
+            //
+            (None, Callback::SelectDag { cb, deferred }) => Callback::SelectDag {
+                cb: cb.clone(),
+                deferred: deferred.clone(),
             },
             //
-            (None, Callback::SelectDag { cb }) => Callback::SelectDag { cb: cb.clone() },
+            (None, Callback::Patch { op }) => Callback::Patch {
+                op: Box::new(move |dag: &mut dyn std::any::Any, ctx: &mut Ctx| op(dag, ctx)),
+            },
+            //
+            (None, Callback::Flush { written }) => Callback::Flush {
+                written: written.clone(),
+            },
             // matching the field
             (Some(field_cb), _) => Callback::MatchDag { cb: field_cb },
             _ => unreachable!(),
@@ -501,64 +706,330 @@ where
 impl<'a, Ctx, T> SelectorSeed<'a, Ctx, T>
 where
     Ctx: Context,
-    T: Representation,
+    T: Select<Ctx> + 'static,
 {
-    /*
+    ///
+    #[doc(hidden)]
+    #[inline]
+    pub fn patch(params: Params<'_, Ctx, T>, dag: &mut T, ctx: &mut Ctx) -> Result<(), Error>
+    where
+        Ctx: 'a,
+    {
+        let Params {
+            selector,
+            max_path_depth,
+            max_link_depth,
+            max_recursion_depth,
+            callback,
+            ..
+        } = params;
+        let mut state = State {
+            max_path_depth,
+            max_link_depth,
+            max_recursion_depth,
+            ..Default::default()
+        };
+
+        if !matches!(callback, Callback::Patch { .. }) {
+            return Err(Error::InvalidSelectionParams(
+                "patching requires a patch op",
+            ));
+        }
+
+        let default_selector = Self::DEFAULT_SELECTOR;
+        let seed = SelectorSeed {
+            selector: selector.unwrap_or(&default_selector),
+            state: &mut state,
+            callback,
+            ctx,
+        };
+
+        seed.patch_dag(dag)?;
+        Ok(())
+    }
 
     ///
+    #[doc(hidden)]
     #[inline]
-    pub fn to_field_patch_seed<'b, V, P>(
-        self,
-        field: P,
-        parent_dag: &'b mut V,
-    ) -> Result<(SelectorSeed<'b, Ctx, V, U>, &'b Selector), Error>
+    pub fn flush(
+        params: Params<'_, Ctx, T>,
+        dag: &mut T,
+        ctx: &mut Ctx,
+    ) -> Result<(Cid, Vec<Cid>), Error>
     where
-        'a: 'b,
-        P: AsRef<Path>,
-        V: Representation,
-        U: Representation + 'b,
+        Ctx: 'a,
     {
+        let Params {
+            cid,
+            selector,
+            max_path_depth,
+            max_link_depth,
+            max_recursion_depth,
+            ..
+        } = params;
+        let mut state = State {
+            max_path_depth,
+            max_link_depth,
+            max_recursion_depth,
+            ..Default::default()
+        };
+
+        let root = cid.ok_or_else(|| {
+            Error::InvalidSelectionParams("flushing must start against the dag's original cid")
+        })?;
+        let written = Rc::<RefCell<Vec<Cid>>>::default();
+        let default_selector = Self::DEFAULT_SELECTOR;
+        let seed = SelectorSeed {
+            selector: selector.unwrap_or(&default_selector),
+            state: &mut state,
+            callback: Callback::Flush {
+                written: written.clone(),
+            },
+            ctx: &mut *ctx,
+        };
+        seed.patch_dag(dag)?;
+
+        // the root dag is always re-encoded, as it may have been patched
+        // directly, without any links to mark dirty
+        let new_root = ctx.add_dag(dag, root.multicodec_code(), root.multihash_code())?;
+        let mut written = written.take();
+        if new_root != root {
+            written.push(new_root);
+        }
+
+        Ok((new_root, written))
+    }
+
+    /// Patches the dag against the current selector, expanding any
+    /// [`ExploreRecursive`] and merging any [`ExploreUnion`] selector,
+    /// returning whether or not any of the dag was patched.
+    ///
+    /// When flushing, a matcher flushes every dirty link within the matched
+    /// dag.
+    #[doc(hidden)]
+    #[inline]
+    pub fn patch_dag(self, dag: &mut T) -> Result<bool, Error> {
         let (selector, state, callback, ctx) = self.into_parts();
+        let node = condition_node(selector, dag)?;
+        let Some(selector) = selector.normalize(node.as_deref(), state)? else {
+            return Ok(false);
+        };
+
+        if selector.matcher().is_some() && callback.is_flush() {
+            let selector = Selector::ExploreRecursive(Rc::new(ExploreRecursive {
+                sequence: Selector::ExploreAll(Rc::new(ExploreAll {
+                    next: Selector::ExploreRecursiveEdge(ExploreRecursiveEdge {}),
+                })),
+                limit: RecursionLimit_None {}.into(),
+                stopAt: None,
+            }));
+            return SelectorSeed::from(&selector, state, callback, ctx).patch_dag(dag);
+        }
 
-        state.descend(field.as_ref(), T::IS_LINK)?;
-        let next = selector
-            .next(Some(field.as_ref()))
-            .ok_or_else(|| Error::MissingNextSelector(""))?;
-        let callback = {
-            // callback.to_patch::<V>(parent_dag)
-            unimplemented!()
+        dag.patch_seed(SelectorSeed::from(&selector, state, callback, ctx))
+    }
+
+    /// Patches a dag of another type, such as the variant of a union or the
+    /// dag behind a link, against the current selector.
+    #[doc(hidden)]
+    #[inline]
+    pub fn patch_inner<U>(self, dag: &mut U) -> Result<bool, Error>
+    where
+        U: Select<Ctx> + 'static,
+    {
+        let (selector, state, callback, ctx) = self.into_parts();
+        SelectorSeed::from(selector, state, callback.cast(), ctx).patch_dag(dag)
+    }
+
+    /// Flushes the dirty links within a linked dag, then writes the dag to
+    /// the context with the codec and multihash of its original [`Cid`],
+    /// returning its new [`Cid`].
+    pub(crate) fn flush_inner<U>(self, cid: &Cid, dag: &mut U) -> Result<Cid, Error>
+    where
+        U: Select<Ctx> + 'static,
+    {
+        let (selector, state, callback, ctx) = self.into_parts();
+        let written = match &callback {
+            Callback::Flush { written } => written.clone(),
+            _ => unreachable!(),
         };
+        SelectorSeed::from(selector, state, callback.cast(), &mut *ctx).patch_dag(dag)?;
 
-        let seed = SelectorSeed {
+        let new_cid = ctx.add_dag(dag, cid.multicodec_code(), cid.multihash_code())?;
+        written.borrow_mut().push(new_cid);
+        Ok(new_cid)
+    }
+
+    /// Applies the patch op to a matched dag.
+    #[doc(hidden)]
+    #[inline]
+    pub fn patch_matched(&mut self, dag: &mut T) -> Result<bool, Error> {
+        self.callback.patch(dag, self.ctx)?;
+        Ok(true)
+    }
+}
+
+// in-memory selection methods
+impl<'a, Ctx, T> SelectorSeed<'a, Ctx, T>
+where
+    Ctx: Context,
+    T: Select<Ctx> + 'static,
+{
+    ///
+    #[doc(hidden)]
+    #[inline]
+    pub fn select_in(params: Params<'_, Ctx, T>, dag: &T, ctx: &mut Ctx) -> Result<(), Error>
+    where
+        Ctx: 'a,
+    {
+        let Params {
             selector,
-            state,
+            max_path_depth,
+            max_link_depth,
+            max_recursion_depth,
+            callback,
+            ..
+        } = params;
+        let mut state = State {
+            max_path_depth,
+            max_link_depth,
+            max_recursion_depth,
+            ..Default::default()
+        };
+
+        if let Callback::Patch { .. } | Callback::Flush { .. } = callback {
+            return Err(Error::InvalidSelectionParams(
+                "patching requires a mutable dag to patch in-place",
+            ));
+        }
+
+        let default_selector = Self::DEFAULT_SELECTOR;
+        let seed = SelectorSeed {
+            selector: selector.unwrap_or(&default_selector),
+            state: &mut state,
             callback,
             ctx,
         };
 
-        Ok((seed, next))
+        seed.select_in_dag(dag)
     }
 
-    ///
+    /// Selects against an in-memory dag with the current selector, expanding
+    /// any [`ExploreRecursive`] and merging any [`ExploreUnion`] selector.
+    #[doc(hidden)]
     #[inline]
-    pub fn patch(&mut self, dag: &mut U) -> Result<(), Error> {
-        match &self.callback {
-            Params::Patch { op, flush, .. } => {
-                // op(dag, self.ctx)?;
-                op(self.ctx)?;
-
-                if *flush {
-                    // self.encode(&dag)?;
-                }
-                Ok(())
+    pub fn select_in_dag(self, dag: &T) -> Result<(), Error> {
+        let (selector, state, callback, ctx) = self.into_parts();
+        let node = condition_node(selector, dag)?;
+        match selector.normalize(node.as_deref(), state)? {
+            Some(selector) if selector.is_explore_interpret_as() => {
+                let node = match node {
+                    Some(node) => node,
+                    None => any_node(dag)?,
+                };
+                SelectorSeed::from(&selector, state, callback, ctx).select_interpreted(&node)
             }
-            _ => Err(Error::InvalidSelectionMode(
-                "`Params` not in patch mode",
-            )),
+            Some(selector) => {
+                dag.select_in_seed(SelectorSeed::from(&selector, state, callback, ctx))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Selects against an in-memory dag of another type, such as the variant
+    /// of a union or the dag behind a link, with the current selector.
+    #[doc(hidden)]
+    #[inline]
+    pub fn select_in_inner<U>(self, dag: &U) -> Result<(), Error>
+    where
+        U: Select<Ctx> + 'static,
+    {
+        let (selector, state, callback, ctx) = self.into_parts();
+        SelectorSeed::from(selector, state, callback.cast(), ctx).select_in_dag(dag)
+    }
+
+    /// Selects against a block in-memory, so that the selector's
+    /// [`Condition`]s can be evaluated against each of its nodes.
+    pub(crate) fn select_block_in(self, cid: &Cid) -> Result<(), Error> {
+        if self.selector.is_explore_interpret_as() {
+            return self.select_interpreted(&Any::Link(Rc::new(Link::Cid(*cid))));
+        }
+
+        let mut codec = Multicodec::try_from(cid)?;
+        let block = self.ctx.block_reader(cid)?;
+        let dag: T = codec.read(block)?;
+        self.select_in_dag(&dag)
+    }
+
+    /// Selects an in-memory dag as a matched node or dag, if the current
+    /// selector is a matcher.
+    #[doc(hidden)]
+    #[inline]
+    pub fn select_in_matched(&mut self, dag: &T) -> Result<(), Error>
+    where
+        T: Clone + Into<SelectedNode>,
+    {
+        let matcher = self
+            .selector
+            .matcher()
+            .ok_or_else(|| Error::unsupported_selector::<T>(self.selector))?;
+
+        match self.mode() {
+            SelectionMode::SelectNode => self.select_matched_subset(dag.clone().into(), matcher),
+            SelectionMode::SelectDag => self.select_matched_dag_subset(dag.clone(), matcher),
+            SelectionMode::Patch => unreachable!(),
         }
     }
+}
+
+/// Narrows a matched string or bytes dag (or an [`Any`] of either) in-place
+/// to the given [`Slice`], as [`SelectedNode::subset`] does for nodes,
+/// returning `false` if the slice matches nothing.
+fn subset_dag(dag: &mut dyn std::any::Any, slice: &Slice) -> bool {
+    if let Some(any) = dag.downcast_mut::<Any>() {
+        return match any {
+            Any::String(s) => subset_dag(s, slice),
+            Any::Bytes(bytes) => subset_dag(bytes, slice),
+            _ => true,
+        };
+    }
 
-     */
+    if let Some(s) = dag.downcast_mut::<IpldString>() {
+        match SelectedNode::String(std::mem::take(s)).subset(slice) {
+            Some(SelectedNode::String(subset)) => *s = subset,
+            _ => return false,
+        }
+    } else if let Some(bytes) = dag.downcast_mut::<Bytes>() {
+        match SelectedNode::Bytes(std::mem::take(bytes)).subset(slice) {
+            Some(SelectedNode::Bytes(subset)) => *bytes = subset,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Produces the data model view of a dag, if the selector needs it to
+/// evaluate its [`Condition`]s.
+fn condition_node<'d, T>(selector: &Selector, dag: &'d T) -> Result<Option<Cow<'d, Any>>, Error>
+where
+    T: Representation + 'static,
+{
+    if !selector.is_conditional() {
+        return Ok(None);
+    }
+    any_node(dag).map(Some)
+}
+
+/// Produces the data model view of a dag, borrowing it if it is already an
+/// [`Any`].
+fn any_node<'d, T>(dag: &'d T) -> Result<Cow<'d, Any>, Error>
+where
+    T: Representation + 'static,
+{
+    match (dag as &dyn std::any::Any).downcast_ref::<Any>() {
+        Some(node) => Ok(Cow::Borrowed(node)),
+        None => Any::from_dag(dag).map(Cow::Owned),
+    }
 }
 
 /// Provides skeletons for conveniently implementing serde-compatibility for
@@ -688,10 +1159,11 @@ macro_rules! impl_selector_seed_serde {
 
     // Select
 
-        // impl Select for T, using the seed's select
+        // impl Select for T, using the seed's select and any provided methods
         (@selector_seed_select
             { $($generics:tt)* } { $($bounds:tt)* }
             $ty:ty
+            { $($select_fns:tt)* }
         ) => {
             impl<Ctx, $($generics)*> $crate::dev::Select<Ctx> for $ty
             where
@@ -707,8 +1179,29 @@ macro_rules! impl_selector_seed_serde {
                     $crate::dev::SelectorSeed::<'_, Ctx, Self>::select(params, ctx)
                     // unimplemented!()
                 }
+
+                $($select_fns)*
             }
         };
+        // impl Select for T, using the seed's select, and selecting in-memory
+        // only with matchers
+        (@selector_seed_select
+            { $($generics:tt)* } { $($bounds:tt)* }
+            $ty:ty
+        ) => {
+            $crate::dev::macros::impl_selector_seed_serde! {
+                @selector_seed_select { $($generics)* } { $($bounds)* } $ty
+            {
+                #[doc(hidden)]
+                #[inline]
+                fn select_in_seed(
+                    &self,
+                    mut seed: $crate::dev::SelectorSeed<'_, Ctx, Self>,
+                ) -> Result<(), $crate::dev::Error> {
+                    seed.select_in_matched(self)
+                }
+            }}
+        };
 
     // newtype impls
 
@@ -751,13 +1244,16 @@ macro_rules! impl_selector_seed_serde {
 
             let (selector, state, callback, ctx) = $seed.into_parts();
             let callback = match callback {
-                SelectNode { cb, only_matched } => SelectNode { cb, only_matched },
-                SelectDag { mut cb } => SelectDag {
+                SelectNode { cb, only_matched, deferred } => SelectNode { cb, only_matched, deferred },
+                // links within the inner dag are selected immediately, as
+                // deferring them would lose the wrapping of the callback
+                SelectDag { mut cb, .. } => SelectDag {
                     cb: Box::new(move |selection, ctx| {
                         let inner_dag = selection.dag.downcast::<$inner_ty>()?;
                         let dag = $constructor(inner_dag.into());
                         cb(DagSelection { dag: dag.into(), ..selection }, ctx)
                     }),
+                    deferred: None,
                 },
                 _ => unreachable!(),
             };
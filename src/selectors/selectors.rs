@@ -1,6 +1,8 @@
 use crate::dev::*;
 use macros::derive_more::{AsMut, AsRef, From, TryInto};
 use std::{
+    borrow::Cow,
+    cmp::Ordering,
     fmt,
     path::{Path, PathBuf},
     rc::Rc,
@@ -47,8 +49,8 @@ schema! {
         // #[ipld_attr(wrapper = "Rc")]
         | ExploreUnion "|"
 
-        // #[ipld_attr(wrapper = "Rc")]
-        // | ExploreConditional "&"
+        #[ipld_attr(wrapper = "Rc")]
+        | ExploreConditional "&"
 
         | ExploreRecursiveEdge "@"
 
@@ -79,7 +81,7 @@ schema! {
     #[ipld_attr(internal)]
     #[derive(Clone, Debug, From)]
     pub type ExploreFields struct {
-        // pub fields {String:Selector} (rename "f>"),
+        pub fields {String:Selector} (rename "f>"),
     };
 }
 
@@ -205,8 +207,7 @@ schema! {
     /// another selector, for example.
     #[ipld_attr(internal)]
     #[derive(Clone, Debug)]
-    pub type ExploreUnion null;
-    // pub type ExploreUnion [Selector];
+    pub type ExploreUnion [Selector];
 }
 
 schema! {
@@ -228,6 +229,8 @@ schema! {
     /// agreed-upon strings, similar to libp2p protocols. The ExploreInterpretAs
     /// reification process may introduce a data-dependant amount of budget on
     /// evaluation based on the specific traversal and ADL implementation.
+    ///
+    /// ADLs are looked up by name with [`Adl::from_name`].
     #[ipld_attr(internal)]
     #[derive(Clone, Debug, From)]
     pub type ExploreInterpretAs struct {
@@ -279,16 +282,17 @@ schema! {
     /// Condition clauses are used several places:
     ///   - in Matcher, to determine if a node is selected.
     ///   - in ExploreRecursive, to halt exploration.
-    ///   - in ExploreConditional,
+    ///   - in ExploreConditional, to determine if a node is explored.
     ///
-    /// TODO -- Condition is very skeletal and incomplete.
-    /// The place where Condition appears in other structs is correct; the rest
-    /// of the details inside it are not final nor even completely drafted.
+    /// Conditions are evaluated against the data model view of a node (its
+    /// representation, as seen by a codec).
     #[ipld_attr(internal)]
     #[derive(Clone, Debug, From)]
     pub type Condition union {
+        #[ipld_attr(wrapper = "Rc")]
         | Condition_HasField "hasField"
         /// # will need to contain a kinded union, lol.  these conditions are gonna get deep.)
+        #[ipld_attr(wrapper = "Rc")]
         | Condition_HasValue "="
         /// will ideally want to refer to the DataModel ReprKind enum...!  will
         /// we replicate that here?  don't want to block on cross-schema
@@ -296,8 +300,11 @@ schema! {
         /// example wanting it.
         | Condition_HasKind "%"
         /// will need this so we can use it in recursions to say "stop at CID QmFoo".
+        #[ipld_attr(wrapper = "Rc")]
         | Condition_IsLink "/"
+        #[ipld_attr(wrapper = "Rc")]
         | Condition_GreaterThan "greaterThan"
+        #[ipld_attr(wrapper = "Rc")]
         | Condition_LessThan "lessThan"
         | Condition_And "and"
         | Condition_Or "or"
@@ -305,52 +312,84 @@ schema! {
 }
 
 schema! {
-    ///
+    /// Condition_HasField is true if the node is a map (or struct) with the
+    /// given key, and if given, the key's value satisfies the inner condition.
     #[ipld_attr(internal)]
     #[derive(Clone, Debug)]
-    pub type Condition_HasField struct {};
+    pub type Condition_HasField struct {
+        /// The key of the field.
+        pub key String,
+        /// The condition the field's value must satisfy, if any.
+        pub condition optional Condition,
+    };
 }
 schema! {
-    ///
+    /// Condition_HasValue is true if the node is equal to the given value.
     #[ipld_attr(internal)]
     #[derive(Clone, Debug)]
-    pub type Condition_HasValue struct {};
+    pub type Condition_HasValue struct {
+        /// The value the node must be equal to.
+        pub value Any,
+    };
 }
 schema! {
-    ///
+    /// Condition_HasKind is true if the node is of the given data model kind,
+    /// one of `null`, `bool`, `int`, `float`, `string`, `bytes`, `list`,
+    /// `map` or `link`.
     #[ipld_attr(internal)]
     #[derive(Clone, Debug)]
-    pub type Condition_HasKind struct {};
+    pub type Condition_HasKind struct {
+        /// The name of the data model kind.
+        pub kind String,
+    };
 }
 schema! {
-    ///
+    /// Condition_IsLink is true if the node is a link, and if given, a link
+    /// to the given [`Cid`].
     #[ipld_attr(internal)]
     #[derive(Clone, Debug)]
-    pub type Condition_IsLink struct {};
+    pub type Condition_IsLink struct {
+        /// The link the node must be equal to, if any.
+        pub cid optional Link,
+    };
 }
 schema! {
-    ///
+    /// Condition_GreaterThan is true if the node is a number greater than the
+    /// given number, or a string (or bytes) that sorts after the given value.
     #[ipld_attr(internal)]
     #[derive(Clone, Debug)]
-    pub type Condition_GreaterThan struct {};
+    pub type Condition_GreaterThan struct {
+        /// The value the node must be greater than.
+        pub value Any,
+    };
 }
 schema! {
-    ///
+    /// Condition_LessThan is true if the node is a number less than the
+    /// given number, or a string (or bytes) that sorts before the given value.
     #[ipld_attr(internal)]
     #[derive(Clone, Debug)]
-    pub type Condition_LessThan struct {};
+    pub type Condition_LessThan struct {
+        /// The value the node must be less than.
+        pub value Any,
+    };
 }
 schema! {
-    ///
+    /// Condition_And is true if all of its conditions are true.
     #[ipld_attr(internal)]
     #[derive(Clone, Debug)]
-    pub type Condition_And struct {};
+    pub type Condition_And struct {
+        /// The conditions to evaluate.
+        pub conditions [Condition],
+    };
 }
 schema! {
-    ///
+    /// Condition_Or is true if any of its conditions are true.
     #[ipld_attr(internal)]
     #[derive(Clone, Debug)]
-    pub type Condition_Or struct {};
+    pub type Condition_Or struct {
+        /// The conditions to evaluate.
+        pub conditions [Condition],
+    };
 }
 
 /// Sealed marker trait for types that can be used as `Selector`s.
@@ -479,7 +518,7 @@ impl Selector {
         match (self, field) {
             (Self::Matcher(_), _) => Some(self),
             (Self::ExploreAll(inner), _) => Some(&inner.next),
-            (Self::ExploreFields { .. }, _) => todo!(),
+            (Self::ExploreFields(inner), Some(f)) => inner.fields.get(f.as_ref() as &str),
             // TODO assert that provided field/index matches what the selector defines, otherwise return None
             (Self::ExploreIndex(inner), Some(f))
                 if inner.index as usize == *(f.as_ref() as &usize) =>
@@ -493,32 +532,370 @@ impl Selector {
             }
             (Self::ExploreRecursive(inner), _) => Some(&inner.sequence),
             (Self::ExploreRecursiveEdge(_), _) => None,
-            (Self::ExploreUnion { .. }, _) => todo!(),
+            // unions merge the next selectors of their members, see `next_selector`
+            (Self::ExploreUnion(_), _) => None,
             (Self::ExploreInterpretAs(inner), _) => Some(&inner.next),
             _ => None,
         }
     }
 
+    /// Produces the selector to apply to the given field (map key or list
+    /// index) of the current node, or `None` if the field isn't explored.
+    ///
+    /// The next selectors of each member of an [`ExploreUnion`] are merged
+    /// into a single selector, so that each child is only visited once.
+    pub(crate) fn next_selector(&self, field: &Field<'_>) -> Option<Cow<'_, Selector>> {
+        match (self, field) {
+            (Self::ExploreUnion(inner), _) => {
+                let mut nexts: Vec<_> = inner
+                    .iter()
+                    .filter_map(|selector| selector.next_selector(field))
+                    .collect();
+                match nexts.len() {
+                    0 | 1 => nexts.pop(),
+                    _ => Some(Cow::Owned(Self::ExploreUnion(
+                        nexts.into_iter().map(Cow::into_owned).collect(),
+                    ))),
+                }
+            }
+            (Self::ExploreFields(_), Field::Index(_))
+            | (Self::ExploreIndex(_) | Self::ExploreRange(_), Field::Key(_)) => None,
+            _ => self.next(Some(field)).map(Cow::Borrowed),
+        }
+    }
+
+    /// Normalizes the selector before it is applied to a node, expanding any
+    /// [`ExploreRecursive`] selector and flattening any [`ExploreUnion`],
+    /// returning `None` if there is nothing left to select.
+    ///
+    /// A union keeps only its first [`Matcher`] member, placed before the
+    /// members it explores with, so that the node is matched at most once
+    /// while selection continues beneath it (see [`Selector::matcher`]).
+    ///
+    /// Any [`Condition`]s are evaluated against the data model view of the
+    /// node, which must be provided if the selector [`is_conditional`]:
+    ///   - a [`Matcher`] whose `onlyIf` is false is dropped,
+    ///   - an [`ExploreConditional`] is replaced by its next selector if its
+    ///     condition is true, and dropped otherwise,
+    ///   - an [`ExploreRecursive`] whose `stopAt` is true is dropped, neither
+    ///     matching nor exploring the node.
+    ///
+    /// Expanding an [`ExploreRecursive`] without a depth limit fails once it
+    /// has recursed more than the max recursion depth along the current path.
+    ///
+    /// A link and the block it links to are treated as one node, so a
+    /// condition of an [`ExploreConditional`] or [`ExploreRecursive`] that is
+    /// false for the link is evaluated again against the linked block.
+    ///
+    /// [`is_conditional`]: Selector::is_conditional
+    pub(crate) fn normalize(
+        &self,
+        node: Option<&Any>,
+        state: &mut State,
+    ) -> Result<Option<Cow<'_, Selector>>, Error> {
+        let is_met = |condition: &Condition| node.is_some_and(|n| condition.matches(n));
+        let is_link = matches!(node, Some(Any::Link(_)));
+        let owned = |selector: Option<Cow<'_, Selector>>| {
+            selector.map(|selector| Cow::Owned(selector.into_owned()))
+        };
+        Ok(match self {
+            Self::Matcher(inner) => match &inner.onlyIf {
+                None => Some(Cow::Borrowed(self)),
+                Some(condition) if is_met(condition) => Some(Cow::Owned(Self::Matcher(Matcher {
+                    onlyIf: None,
+                    ..inner.clone()
+                }))),
+                Some(_) => None,
+            },
+            Self::ExploreConditional(inner) if is_met(&inner.condition) => {
+                owned(inner.next.normalize(node, state)?)
+            }
+            Self::ExploreConditional(_) if is_link => Some(Cow::Borrowed(self)),
+            Self::ExploreConditional(_) => None,
+            Self::ExploreRecursiveEdge(_) => None,
+            Self::ExploreRecursive(inner) if inner.sequence.is_explore_recursive_edge() => None,
+            Self::ExploreRecursive(inner) => match &inner.stopAt {
+                Some(condition) if is_met(condition) => None,
+                Some(_) if is_link => Some(Cow::Borrowed(self)),
+                _ => {
+                    if let RecursionLimit::RecursionLimit_None(_) = inner.limit {
+                        state.recurse()?;
+                    }
+                    owned(inner.expand().normalize(node, state)?)
+                }
+            },
+            Self::ExploreUnion(inner) => {
+                let mut changed = false;
+                let mut matcher = None;
+                let mut members = Vec::with_capacity(inner.len());
+                for member in inner.iter() {
+                    let Some(member) = member.normalize(node, state)? else {
+                        changed = true;
+                        continue;
+                    };
+                    let nested = match member.as_ref() {
+                        Self::ExploreUnion(nested) => {
+                            changed = true;
+                            nested.iter().cloned().map(Cow::Owned).collect()
+                        }
+                        _ => vec![member],
+                    };
+                    for member in nested {
+                        changed |= matches!(member, Cow::Owned(_));
+                        match member.as_ref() {
+                            Self::Matcher(_) if matcher.is_some() => changed = true,
+                            Self::Matcher(_) => {
+                                changed |= !members.is_empty();
+                                matcher = Some(member);
+                            }
+                            _ => members.push(member),
+                        }
+                    }
+                }
+
+                members.splice(0..0, matcher);
+                match members.len() {
+                    0 | 1 => members.pop(),
+                    _ if !changed => Some(Cow::Borrowed(self)),
+                    _ => Some(Cow::Owned(Self::ExploreUnion(
+                        members.into_iter().map(Cow::into_owned).collect(),
+                    ))),
+                }
+            }
+            _ => Some(Cow::Borrowed(self)),
+        })
+    }
+
+    /// The [`Matcher`] to apply to the current node, if the selector is
+    /// either a matcher or a (normalized) [`ExploreUnion`] whose first member
+    /// is a matcher.
+    pub(crate) fn matcher(&self) -> Option<&Matcher> {
+        self.as_matcher().or_else(|| self.union_matcher())
+    }
+
+    /// The [`Matcher`] of a (normalized) [`ExploreUnion`], which matches the
+    /// current node while the union's other members explore beneath it.
+    pub(crate) fn union_matcher(&self) -> Option<&Matcher> {
+        self.as_explore_union()
+            .and_then(|inner| inner.iter().next())
+            .and_then(Self::as_matcher)
+    }
+
+    /// Whether or not normalizing the selector against a node requires
+    /// evaluating a [`Condition`] against it.
+    pub(crate) fn is_conditional(&self) -> bool {
+        match self {
+            Self::Matcher(inner) => inner.onlyIf.is_some(),
+            Self::ExploreConditional(_) => true,
+            Self::ExploreRecursive(inner) => {
+                inner.stopAt.is_some() || inner.sequence.is_conditional()
+            }
+            Self::ExploreUnion(inner) => inner.iter().any(Self::is_conditional),
+            _ => false,
+        }
+    }
+
+    /// Whether or not the selector, or any selector within it, is an
+    /// [`ExploreUnion`] that may both match and explore a node.
+    pub(crate) fn has_matching_unions(&self) -> bool {
+        match self {
+            Self::Matcher(_) | Self::ExploreRecursiveEdge(_) => false,
+            Self::ExploreAll(inner) => inner.next.has_matching_unions(),
+            Self::ExploreFields(inner) => inner.fields.values().any(Self::has_matching_unions),
+            Self::ExploreIndex(inner) => inner.next.has_matching_unions(),
+            Self::ExploreRange(inner) => inner.next.has_matching_unions(),
+            Self::ExploreRecursive(inner) => inner.sequence.has_matching_unions(),
+            Self::ExploreUnion(inner) => {
+                inner.iter().any(Self::may_match) || inner.iter().any(Self::has_matching_unions)
+            }
+            Self::ExploreConditional(inner) => inner.next.has_matching_unions(),
+            Self::ExploreInterpretAs(inner) => inner.next.has_matching_unions(),
+        }
+    }
+
+    /// Whether or not the selector may normalize into a [`Matcher`].
+    fn may_match(&self) -> bool {
+        match self {
+            Self::Matcher(_) => true,
+            Self::ExploreRecursive(inner) => inner.sequence.may_match(),
+            Self::ExploreUnion(inner) => inner.iter().any(Self::may_match),
+            Self::ExploreConditional(inner) => inner.next.may_match(),
+            _ => false,
+        }
+    }
+
+    /// The keys of the map entries explored by the selector, or `None` if it
+    /// may explore (or match) any of them.
+    pub(crate) fn explored_keys(&self) -> Option<Vec<&str>> {
+        match self {
+            Self::ExploreFields(inner) => Some(inner.fields.keys().map(|k| k.as_str()).collect()),
+            Self::ExploreIndex(_) | Self::ExploreRange(_) => Some(Vec::new()),
+            Self::ExploreUnion(inner) => {
+                inner
+                    .iter()
+                    .map(Self::explored_keys)
+                    .try_fold(Vec::new(), |mut keys, member| {
+                        keys.extend(member?);
+                        Some(keys)
+                    })
+            }
+            _ => None,
+        }
+    }
+
     impl_variant!(Matcher Matcher |
         {is_matcher, as_matcher, try_into_matcher});
     impl_variant!(@wrapped ExploreAll ExploreAll |
         {is_explore_all, as_explore_all, try_into_explore_all});
-    // impl_variant!(ExploreFields ExploreFields |
-    //     {is_explore_fields, as_explore_fields, try_into_explore_fields});
+    impl_variant!(ExploreFields ExploreFields |
+        {is_explore_fields, as_explore_fields, try_into_explore_fields});
     impl_variant!(@wrapped ExploreIndex ExploreIndex |
         {is_explore_index, as_explore_index, try_into_explore_index});
     impl_variant!(@wrapped ExploreRange ExploreRange |
         {is_explore_range, as_explore_range, try_into_explore_range});
-    // impl_variant!(ExploreRecursive ExploreRecursive |
-    //     {is_explore_recursive, as_explore_recursive, try_into_explore_recursive});
-    // impl_variant!(ExploreUnion ExploreUnion |
-    //     {is_explore_union, as_explore_union, try_into_explore_union});
-    // impl_variant!(ExploreConditional ExploreConditional |
-    //     {is_explore_conditional, as_explore_conditional, try_into_explore_conditional);
-    // impl_variant!(ExploreInterpretAs ExploreInterpretAs |
-    //     {is_explore_interpret_as, as_explore_interpret_as, try_into_explore_interpret_as});
-    // impl_variant!(ExploreRecursiveEdge ExploreRecursiveEdge |
-    //     {is_explore_recursive_edge, as_explore_recursive_edge, try_into_explore_recursive_edge});
+    impl_variant!(@wrapped ExploreRecursive ExploreRecursive |
+        {is_explore_recursive, as_explore_recursive, try_into_explore_recursive});
+    impl_variant!(ExploreUnion ExploreUnion |
+        {is_explore_union, as_explore_union, try_into_explore_union});
+    impl_variant!(@wrapped ExploreConditional ExploreConditional |
+        {is_explore_conditional, as_explore_conditional, try_into_explore_conditional});
+    impl_variant!(@wrapped ExploreInterpretAs ExploreInterpretAs |
+        {is_explore_interpret_as, as_explore_interpret_as, try_into_explore_interpret_as});
+    impl_variant!(ExploreRecursiveEdge ExploreRecursiveEdge |
+        {is_explore_recursive_edge, as_explore_recursive_edge, try_into_explore_recursive_edge});
+}
+
+/* ExploreRecursive */
+
+impl ExploreRecursive {
+    /// Expands the recursive selector into its sequence, replacing each
+    /// [`ExploreRecursiveEdge`] with this selector at a decremented depth.
+    ///
+    /// Once the depth limit is reached, edges are left in place, halting
+    /// further recursion.
+    pub(crate) fn expand(&self) -> Selector {
+        let edge = match &self.limit {
+            RecursionLimit::RecursionLimit_Depth(depth) if depth.0 <= 0 => {
+                return Selector::ExploreRecursiveEdge(ExploreRecursiveEdge {})
+            }
+            RecursionLimit::RecursionLimit_Depth(depth) if depth.0 == 1 => {
+                return self.sequence.clone()
+            }
+            RecursionLimit::RecursionLimit_Depth(depth) => Self {
+                limit: RecursionLimit_Depth(depth.0 - 1).into(),
+                ..self.clone()
+            },
+            RecursionLimit::RecursionLimit_None(_) => self.clone(),
+        };
+
+        Self::replace_edges(&self.sequence, &Selector::ExploreRecursive(Rc::new(edge)))
+    }
+
+    fn replace_edges(selector: &Selector, edge: &Selector) -> Selector {
+        match selector {
+            Selector::ExploreAll(inner) => Selector::ExploreAll(Rc::new(ExploreAll {
+                next: Self::replace_edges(&inner.next, edge),
+            })),
+            Selector::ExploreFields(inner) => Selector::ExploreFields(ExploreFields {
+                fields: inner
+                    .fields
+                    .iter()
+                    .map(|(field, next)| (field.clone(), Self::replace_edges(next, edge)))
+                    .collect(),
+            }),
+            Selector::ExploreIndex(inner) => Selector::ExploreIndex(Rc::new(ExploreIndex {
+                index: inner.index,
+                next: Self::replace_edges(&inner.next, edge),
+            })),
+            Selector::ExploreRange(inner) => Selector::ExploreRange(Rc::new(ExploreRange {
+                start: inner.start,
+                end: inner.end,
+                next: Self::replace_edges(&inner.next, edge),
+            })),
+            Selector::ExploreInterpretAs(inner) => {
+                Selector::ExploreInterpretAs(Rc::new(ExploreInterpretAs {
+                    r#as: inner.r#as.clone(),
+                    next: Self::replace_edges(&inner.next, edge),
+                }))
+            }
+            Selector::ExploreUnion(inner) => Selector::ExploreUnion(
+                inner
+                    .iter()
+                    .map(|next| Self::replace_edges(next, edge))
+                    .collect(),
+            ),
+            Selector::ExploreConditional(inner) => {
+                Selector::ExploreConditional(Rc::new(ExploreConditional {
+                    condition: inner.condition.clone(),
+                    next: Self::replace_edges(&inner.next, edge),
+                }))
+            }
+            Selector::ExploreRecursiveEdge(_) => edge.clone(),
+            // nested recursive selectors have edges of their own
+            _ => selector.clone(),
+        }
+    }
+}
+
+/* Condition */
+
+impl Condition {
+    /// Evaluates the condition against the data model view of a node.
+    pub fn matches(&self, node: &Any) -> bool {
+        match self {
+            Self::Condition_HasField(inner) => match node {
+                Any::Map(map) => match (map.get(inner.key.as_str()), &inner.condition) {
+                    (Some(value), Some(condition)) => condition.matches(value),
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                },
+                _ => false,
+            },
+            Self::Condition_HasValue(inner) => node == &inner.value,
+            Self::Condition_HasKind(inner) => Self::kind_of(node) == inner.kind.as_str(),
+            Self::Condition_IsLink(inner) => match (node, &inner.cid) {
+                (Any::Link(link), Some(cid)) => link.cid() == cid.cid(),
+                (Any::Link(_), None) => true,
+                _ => false,
+            },
+            Self::Condition_GreaterThan(inner) => {
+                Self::compare(node, &inner.value) == Some(Ordering::Greater)
+            }
+            Self::Condition_LessThan(inner) => {
+                Self::compare(node, &inner.value) == Some(Ordering::Less)
+            }
+            Self::Condition_And(inner) => inner.conditions.iter().all(|c| c.matches(node)),
+            Self::Condition_Or(inner) => inner.conditions.iter().any(|c| c.matches(node)),
+        }
+    }
+
+    fn kind_of(node: &Any) -> &'static str {
+        match node {
+            Any::Null(_) => "null",
+            Any::Bool(_) => "bool",
+            Any::Int(_) => "int",
+            Any::Float(_) => "float",
+            Any::String(_) => "string",
+            Any::Bytes(_) => "bytes",
+            Any::List(_) => "list",
+            Any::Map(_) => "map",
+            Any::Link(_) => "link",
+        }
+    }
+
+    /// Orders numbers by value, and strings and bytes lexicographically;
+    /// nodes of any other kind are unordered.
+    fn compare(node: &Any, value: &Any) -> Option<Ordering> {
+        match (node, value) {
+            (Any::Int(a), Any::Int(b)) => a.partial_cmp(b),
+            (Any::Float(a), Any::Float(b)) => a.partial_cmp(b),
+            (Any::Int(a), Any::Float(b)) => (*a as Float).partial_cmp(b),
+            (Any::Float(a), Any::Int(b)) => a.partial_cmp(&(*b as Float)),
+            (Any::String(a), Any::String(b)) => a.as_str().partial_cmp(b.as_str()),
+            (Any::Bytes(a), Any::Bytes(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
 }
 
 /* Selector */
@@ -545,6 +922,28 @@ impl FromStr for Selector {
 
 /* Slice */
 
+impl Slice {
+    /// Resolves the slice against a node of length `len` to a range of
+    /// bytes, or `None` if the slice matches nothing.
+    ///
+    /// Negative offsets count back from the end of the node, and both ends
+    /// are clamped to the node's bounds; a slice whose (resolved) start comes
+    /// after its end does not match.
+    pub fn range(&self, len: usize) -> Option<std::ops::Range<usize>> {
+        let resolve = |offset: Int| -> usize {
+            let offset = if offset < 0 {
+                (len as Int).saturating_add(offset).max(0)
+            } else {
+                offset
+            };
+            (offset as u64).min(len as u64) as usize
+        };
+
+        let (from, to) = (resolve(self.from), resolve(self.to));
+        (from <= to).then_some(from..to)
+    }
+}
+
 impl From<std::ops::Range<Int>> for Slice {
     fn from(range: std::ops::Range<Int>) -> Self {
         Self {
@@ -563,10 +962,41 @@ impl From<Slice> for std::ops::Range<Int> {
     }
 }
 
+/* ExploreUnion */
+
+impl ExploreUnion {
+    /// The number of member selectors in the union.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether or not the union has any member selectors.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the member selectors of the union.
+    pub fn iter(&self) -> std::slice::Iter<'_, Selector> {
+        self.0.iter()
+    }
+}
+
+impl From<List<Selector>> for ExploreUnion {
+    fn from(selectors: List<Selector>) -> Self {
+        Self(selectors)
+    }
+}
+
+impl FromIterator<Selector> for ExploreUnion {
+    fn from_iter<I: IntoIterator<Item = Selector>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
 /* ExploreFields */
 
 impl ExploreFields {
     pub fn contains_key(&self, key: &str) -> bool {
-        unimplemented!()
+        self.fields.contains_key(key)
     }
 }
@@ -2,83 +2,69 @@ use crate::dev::*;
 use macros::derive_more::{AsMut, AsRef, From};
 use std::{
     boxed::Box,
+    cell::RefCell,
     fmt,
     path::{Path, PathBuf},
+    rc::{Rc, Weak},
 };
 
-// ///
-// #[must_use = "Streams do nothing unless polled"]
-// // #[derive(Debug)]
-// // TODO: pin vs box?
-// pub struct SelectionStream<'a> {
-//     inner: Pin<Box<dyn Stream<Item = Result<SelectedNode, Error>> + 'a>>,
-// }
-//
-// // // impl Unpin for SelectionStream {}
-//
-// impl<'a> SelectionStream<'a> {
-//     // TODO: requires that the stream be wrapped in a pinbox - why?
-//     unsafe_pinned!(inner: dyn Stream<Item = Result<SelectedNode, Error>>);
-//
-//     ///
-//     #[inline]
-//     pub fn ok(selection: SelectedNode) -> Self {
-//         Self::from(async { Ok(selection) }.into_stream())
-//     }
-//
-//     ///
-//     #[inline]
-//     pub fn err(err: Error) -> Self {
-//         Self::from(async { Err(err) }.into_stream())
-//     }
-//
-//     ///
-//     #[inline]
-//     pub fn from<S>(inner: S) -> Self
-//     where
-//         S: Stream<Item = Result<SelectedNode, Error>> + 'a,
-//     {
-//         Self {
-//             inner: Box::pin(inner),
-//         }
-//     }
-// }
-//
-// impl<'a> Stream for SelectionStream<'a> {
-//     type Item = Result<SelectedNode, Error>;
-//
-//     #[inline]
-//     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Cx<'_>) -> Poll<Option<Self::Item>> {
-//         self.inner.as_mut().poll_next(cx)
-//     }
-//
-//     #[inline]
-//     fn size_hint(&self) -> (usize, Option<usize>) {
-//         self.inner.size_hint()
-//     }
-// }
-//
-// impl<'a> From<SelectedNode> for SelectionStream<'a> {
-//     #[inline]
-//     fn from(selection: SelectedNode) -> Self {
-//         Self::ok(selection)
-//     }
-// }
-//
-// impl<'a> From<Error> for SelectionStream<'a> {
-//     #[inline]
-//     fn from(err: Error) -> Self {
-//         Self::err(err)
-//     }
-// }
-//
-// impl<'a> From<SelectedNode> for Result<SelectionStream<'a>, Error> {
-//     #[inline]
-//     fn from(selection: SelectedNode) -> Self {
-//         Ok(SelectionStream::ok(selection))
-//     }
-// }
-//
+#[cfg(feature = "async")]
+pub use stream::SelectionStream;
+#[cfg(feature = "async")]
+mod stream {
+    use super::*;
+    use futures::Stream;
+    use std::{
+        pin::Pin,
+        task::{Context as Cx, Poll},
+    };
+
+    /// A [`Stream`] of [`NodeSelection`]s, selected against blocks loaded
+    /// from an [`AsyncContext`].
+    ///
+    /// As selectors and selection state are shared with [`Rc`]s, the stream
+    /// is `!Send`, and must be polled on the thread that created it, e.g.
+    /// within a `tokio::task::LocalSet` or with
+    /// [`futures::executor::block_on`].
+    #[must_use = "streams do nothing unless polled"]
+    pub struct SelectionStream<'a> {
+        inner: Pin<Box<dyn Stream<Item = Result<NodeSelection, Error>> + 'a>>,
+    }
+
+    impl<'a> SelectionStream<'a> {
+        /// Boxes a stream of [`NodeSelection`]s.
+        #[inline]
+        pub fn from<S>(inner: S) -> Self
+        where
+            S: Stream<Item = Result<NodeSelection, Error>> + 'a,
+        {
+            Self {
+                inner: Box::pin(inner),
+            }
+        }
+    }
+
+    impl<'a> Stream for SelectionStream<'a> {
+        type Item = Result<NodeSelection, Error>;
+
+        #[inline]
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Cx<'_>) -> Poll<Option<Self::Item>> {
+            self.inner.as_mut().poll_next(cx)
+        }
+
+        #[inline]
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.inner.size_hint()
+        }
+    }
+
+    impl<'a> fmt::Debug for SelectionStream<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SelectionStream").finish_non_exhaustive()
+        }
+    }
+}
+
 // /// A thin wrapper around a `Selector` and its selection state.
 // #[derive(AsRef, AsMut, Clone, Debug)]
 // pub struct SelectorState {
@@ -201,43 +187,93 @@ mod callbacks {
             (**self).clone_box()
         }
     }
-}
 
-/*
-///
-/// https://stackoverflow.com/questions/65203307/how-do-i-create-a-trait-object-that-implements-fn-and-can-be-cloned-to-distinct
-pub trait PatchOp<C, U>: Fn(&mut U, &mut C) -> Result<(), Error> {
+    /// An operation applied in-place to matched dags, which are provided
+    /// type-erased and are downcast by the op to the type it expects.
     ///
-    fn clone_box<'a>(&self) -> Box<dyn PatchOp<C, U> + 'a>
-    where
-        Self: 'a;
+    /// A single op is borrowed for the entire traversal, so any state it
+    /// captures is shared between all of the patched dags.
+    pub trait PatchOp<C>: FnMut(&mut dyn std::any::Any, &mut C) -> Result<(), Error> {}
+
+    impl<C, F> PatchOp<C> for F where F: FnMut(&mut dyn std::any::Any, &mut C) -> Result<(), Error> {}
 }
 
-impl<C, U, F> PatchOp<C, U> for F
-where
-    F: Fn(&mut U, &mut C) -> Result<(), Error> + Clone,
-{
-    fn clone_box<'a>(&self) -> Box<dyn PatchOp<C, U> + 'a>
-    where
-        Self: 'a,
-    {
-        Box::new(self.clone())
+// type SelectFn<U, C> = fn(U, &mut C) -> Result<(), Error>;
+// type PatchFn<U, C> = fn(U, &mut C) -> Result<(), Error>;
+
+/// A queue of selections made by lazy selection iterators, to which links are
+/// deferred until the iterator reaches them.
+#[doc(hidden)]
+pub type DeferredQueue<C> = Weak<RefCell<Vec<Deferred<C>>>>;
+
+/// A selection made by a lazy selection iterator.
+#[doc(hidden)]
+pub enum Deferred<C> {
+    Node(NodeSelection),
+    Dag(DagSelection),
+    Link(DeferredLink<C>),
+}
+
+/// A link encountered during selection, to be selected against once a lazy
+/// selection iterator reaches it.
+#[doc(hidden)]
+pub struct DeferredLink<C> {
+    pub(crate) cid: Cid,
+    pub(crate) selector: Selector,
+    pub(crate) state: State,
+    /// Selects against the linked block as the link's expected type.
+    pub(crate) select: DeferredSelectFn<C>,
+}
+
+/// Selects against a linked block, given the deferred link's [`Cid`],
+/// [`Selector`], [`State`] and a [`Callback`] of selections.
+pub(crate) type DeferredSelectFn<C> =
+    fn(Cid, &Selector, &mut State, Callback<'_, C, Any>, &mut C) -> Result<(), Error>;
+
+impl<C> DeferredLink<C> {
+    /// Selects against the linked block, invoking the callback with the
+    /// selections (and any further deferred links).
+    pub(crate) fn select(self, callback: Callback<'_, C, Any>, ctx: &mut C) -> Result<(), Error> {
+        let Self {
+            cid,
+            selector,
+            mut state,
+            select,
+        } = self;
+        select(cid, &selector, &mut state, callback, ctx)
     }
 }
 
-impl<'a, C, U> Clone for Box<dyn PatchOp<C, U> + 'a>
-where
-    C: 'a,
-    U: 'a,
-{
+impl<C> Clone for DeferredLink<C> {
     fn clone(&self) -> Self {
-        (**self).clone_box()
+        Self {
+            cid: self.cid,
+            selector: self.selector.clone(),
+            state: self.state.clone(),
+            select: self.select,
+        }
     }
 }
- */
 
-// type SelectFn<U, C> = fn(U, &mut C) -> Result<(), Error>;
-// type PatchFn<U, C> = fn(U, &mut C) -> Result<(), Error>;
+impl<C> fmt::Debug for Deferred<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Node(node) => f.debug_tuple("Node").field(node).finish(),
+            Self::Dag(dag) => f.debug_tuple("Dag").field(&dag.path).finish(),
+            Self::Link(link) => f.debug_tuple("Link").field(link).finish(),
+        }
+    }
+}
+
+impl<C> fmt::Debug for DeferredLink<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeferredLink")
+            .field("cid", &self.cid)
+            .field("selector", &self.selector)
+            .field("path", &self.state.path)
+            .finish()
+    }
+}
 
 /// The selection mode of the selector, which determines what gets visited,
 /// matched, sent and returned.
@@ -251,9 +287,9 @@ pub enum SelectionMode {
     SelectDag,
     // ///
     // MatchDag,
-    // /// Selection updates matching dags with the output of a callback.
-    // /// Optionally flushes changes after each callback.
-    // Patch,
+    /// Selection updates matching dags in-place with the provided callback,
+    /// or flushes previously patched dags.
+    Patch,
 }
 
 ///
@@ -262,22 +298,28 @@ pub enum Callback<'a, C, T> {
     SelectNode {
         cb: Box<dyn SelectNodeOp<C> + 'a>,
         only_matched: bool,
+        /// If present, links are deferred to this queue instead of being
+        /// selected against immediately.
+        deferred: Option<DeferredQueue<C>>,
     },
     SelectDag {
         // TODO: does this need to be cloneable? it is either called on U, or wrapped
         cb: Box<dyn SelectDagOp<C> + 'a>,
+        /// If present, links are deferred to this queue instead of being
+        /// selected against immediately.
+        deferred: Option<DeferredQueue<C>>,
     },
     MatchDag {
         cb: Box<dyn MatchDagOp<T, C> + 'a>,
-    }, // Patch {
-       //     /// current dag we're selecting against
-       //     /// if none, then load and store while patching
-       //     current: &'a mut T,
-       //     flush: bool,
-       //     // op to perform on matching dags, allowing update-inplace
-       //     // op: Box<dyn PatchOp<C, U> + 'a>,
-       //     // op: PatchFn<C, U>,
-       // }
+    },
+    Patch {
+        /// The op to perform in-place on matched dags.
+        op: Box<dyn PatchOp<C> + 'a>,
+    },
+    Flush {
+        /// The [`Cid`]s of the blocks written while flushing.
+        written: Rc<RefCell<Vec<Cid>>>,
+    },
 }
 
 impl<'a, C, T> fmt::Debug for Callback<'a, C, T>
@@ -300,11 +342,15 @@ where
                 .debug_struct("SelectionParams::MatchDag")
                 .field("source", &T::NAME)
                 .finish(),
-            // Self::Patch { current, flush, .. } => f
-            //     .debug_struct("SelectionParams::Patch")
-            //     .field("current", &current)
-            //     .field("flush", &flush)
-            //     .finish(),
+            Self::Patch { .. } => f
+                .debug_struct("SelectionParams::Patch")
+                .field("source", &T::NAME)
+                .finish(),
+            Self::Flush { written } => f
+                .debug_struct("SelectionParams::Flush")
+                .field("source", &T::NAME)
+                .field("written", &written.borrow())
+                .finish(),
         }
     }
 }
@@ -319,6 +365,7 @@ where
         Self::SelectNode {
             cb: Box::new(|_, _| Ok(())),
             only_matched: true,
+            deferred: None,
         }
     }
 }
@@ -383,7 +430,7 @@ where
         match self {
             Self::SelectNode { .. } => SelectionMode::SelectNode,
             Self::SelectDag { .. } | Self::MatchDag { .. } => SelectionMode::SelectDag,
-            // Self::Patch { .. } => SelectionMode::Patch,
+            Self::Patch { .. } | Self::Flush { .. } => SelectionMode::Patch,
         }
     }
 
@@ -403,17 +450,20 @@ where
         }
     }
 
+    /// Whether or not the callback flushes previously patched dags.
+    pub const fn is_flush(&self) -> bool {
+        matches!(self, Self::Flush { .. })
+    }
+
     pub(super) fn select_node(
         &mut self,
         selected_node: NodeSelection,
         ctx: &mut C,
     ) -> Result<(), Error> {
         match self {
-            Self::SelectNode { cb, only_matched }
-                if !*only_matched || selected_node.matched && *only_matched =>
-            {
-                cb(selected_node, ctx)
-            }
+            Self::SelectNode {
+                cb, only_matched, ..
+            } if !*only_matched || selected_node.matched && *only_matched => cb(selected_node, ctx),
             Self::SelectNode { .. } => Ok(()),
             _ => unreachable!(),
         }
@@ -421,11 +471,51 @@ where
 
     pub(super) fn select_dag(&mut self, dag: DagSelection, ctx: &mut C) -> Result<(), Error> {
         match self {
-            Self::SelectDag { cb } => cb(dag, ctx),
+            Self::SelectDag { cb, .. } => cb(dag, ctx),
+            _ => unreachable!(),
+        }
+    }
+
+    pub(super) fn patch(&mut self, dag: &mut dyn std::any::Any, ctx: &mut C) -> Result<(), Error> {
+        match self {
+            Self::Patch { op } => op(dag, ctx),
             _ => unreachable!(),
         }
     }
 
+    /// Returns the queue to which links are deferred, if any.
+    pub(crate) fn deferred(&self) -> Option<&DeferredQueue<C>> {
+        match self {
+            Self::SelectNode { deferred, .. } | Self::SelectDag { deferred, .. } => {
+                deferred.as_ref()
+            }
+            Self::MatchDag { .. } | Self::Patch { .. } | Self::Flush { .. } => None,
+        }
+    }
+
+    /// Casts the callback of selections against a `T` into one of selections
+    /// against a `U`.
+    ///
+    /// Only node and dag selection, patching and flushing callbacks can be
+    /// cast, as they are independent of the type being selected against.
+    pub(crate) fn cast<U>(self) -> Callback<'a, C, U> {
+        match self {
+            Self::SelectNode {
+                cb,
+                only_matched,
+                deferred,
+            } => Callback::SelectNode {
+                cb,
+                only_matched,
+                deferred,
+            },
+            Self::SelectDag { cb, deferred } => Callback::SelectDag { cb, deferred },
+            Self::Patch { op } => Callback::Patch { op },
+            Self::Flush { written } => Callback::Flush { written },
+            Self::MatchDag { .. } => unreachable!("cannot cast a dag matching callback"),
+        }
+    }
+
     // // TODO: is this valid?
     // pub(super) fn cast<'b, S>(self) -> SelectionCallback<'b, C, S>
     // where
@@ -459,7 +549,7 @@ where
 
 ///
 /// TODO: just rename this to state
-#[derive(AsRef, AsMut, Debug, Default)]
+#[derive(AsRef, AsMut, Clone, Debug, Default)]
 pub(crate) struct State {
     // selector: Selector,
     // mode: SelectionMode,
@@ -471,12 +561,18 @@ pub(crate) struct State {
     pub(crate) link_depth: usize,
     pub(crate) max_path_depth: Option<usize>,
     pub(crate) max_link_depth: Option<usize>,
+    pub(crate) max_recursion_depth: Option<usize>,
+    /// The path depths at which each unbounded recursion along the current
+    /// path was expanded.
+    pub(crate) recursions: Vec<usize>,
     // sender: Option<SelectionSender>,
     // params: SelectionParams<'a, C, T, U>,
 }
 
 // impl<'a> SelectorState<'a> {
 impl State {
+    const DEFAULT_MAX_RECURSION_DEPTH: usize = 1024;
+
     #[inline]
     pub fn path(&self) -> &Path {
         &self.path
@@ -498,6 +594,27 @@ impl State {
         }
     }
 
+    #[inline]
+    pub(crate) fn max_recursion_depth(&self) -> usize {
+        self.max_recursion_depth
+            .unwrap_or(Self::DEFAULT_MAX_RECURSION_DEPTH)
+    }
+
+    /// Records the expansion of an unbounded recursion at the current path,
+    /// failing if it would exceed the max recursion depth.
+    #[inline]
+    pub(crate) fn recurse(&mut self) -> Result<(), Error> {
+        if self.recursions.len() >= self.max_recursion_depth() {
+            return Err(Error::SelectorDepth(
+                "recursing would exceed max recursion depth",
+                self.max_recursion_depth(),
+            ));
+        }
+
+        self.recursions.push(self.path_depth);
+        Ok(())
+    }
+
     // ///
     // #[inline]
     // pub(crate) const fn with_max_path_depth(mut self, max_path_depth: usize) -> Self {
@@ -550,6 +667,9 @@ impl State {
             .path_depth
             .checked_sub(1)
             .ok_or_else(|| Error::SelectorDepth("exceeds root path depth", self.path_depth))?;
+        while matches!(self.recursions.last(), Some(depth) if *depth > self.path_depth) {
+            self.recursions.pop();
+        }
 
         if T::IS_LINK {
             self.link_depth = self
@@ -45,42 +45,6 @@ static FIXTURE_SKIPLIST: &[(&str, &str)] = &[
     ("int-18446744073709551615", "fails for i64s"),
     // skipped by libipld
     ("int--11959030306112471732", "integer out of int64 range"),
-    (
-        "dagpb_11unnamedlinks+data",
-        "DAG-PB isn't fully compatible yet",
-    ),
-    ("dagpb_1link", "DAG-PB isn't fully compatible yet"),
-    ("dagpb_2link+data", "DAG-PB isn't fully compatible yet"),
-    (
-        "dagpb_4namedlinks+data",
-        "DAG-PB isn't fully compatible yet",
-    ),
-    (
-        "dagpb_7unnamedlinks+data",
-        "DAG-PB isn't fully compatible yet",
-    ),
-    ("dagpb_Data_zero", "DAG-PB isn't fully compatible yet"),
-    ("dagpb_empty", "DAG-PB isn't fully compatible yet"),
-    ("dagpb_Links_Hash_some", "DAG-PB isn't fully compatible yet"),
-    (
-        "dagpb_Links_Hash_some_Name_some",
-        "DAG-PB isn't fully compatible yet",
-    ),
-    (
-        "dagpb_Links_Hash_some_Name_zero",
-        "DAG-PB isn't fully compatible yet",
-    ),
-    (
-        "dagpb_Links_Hash_some_Tsize_some",
-        "DAG-PB isn't fully compatible yet",
-    ),
-    (
-        "dagpb_Links_Hash_some_Tsize_zero",
-        "DAG-PB isn't fully compatible yet",
-    ),
-    ("dagpb_simple_forms_2", "DAG-PB isn't fully compatible yet"),
-    ("dagpb_simple_forms_3", "DAG-PB isn't fully compatible yet"),
-    ("dagpb_simple_forms_4", "DAG-PB isn't fully compatible yet"),
 ];
 
 /// Returns the paths to all directories that contain fixtures.
@@ -175,7 +139,7 @@ impl Fixture {
                 let codec = match codec_str {
                     "dag-json" => Multicodec::DagJson(DagJson::new()),
                     "dag-cbor" => Multicodec::DagCbor(DagCbor::new()),
-                    // "dag-pb" => Codec::DagPb,
+                    "dag-pb" => Multicodec::DagPb(DagPb::new()),
                     _ => return None,
                 };
 
@@ -238,10 +202,10 @@ impl Fixture {
             // FixtureType::Float => self.run_for::<Float>(),
             // FixtureType::Bytes => self.run_for::<Bytes>(), // none of the fixtures have the right multibase...
             FixtureType::String => self.run_for::<IpldString>(),
+            FixtureType::DagPb => self.run_for::<DagPbNode>(),
             // FixtureType::Array => self.run_for::<List<Any>>(),
             // FixtureType::Map => self.run_for::<Map<IpldString, Any>>(),
             // FixtureType::Cid => self.run_for::<Link<Any>>(),
-            // FixtureType::DagPb => {}
             // FixtureType::Garbage => {}
             _ => false,
         };
@@ -302,6 +266,7 @@ impl Fixture {
                 .expect(&self.format_err::<T>("should not fail selection"))
                 .next()
                 .expect("should produce at least one dag")
+                .expect(&self.format_err::<T>("should not fail selection"))
                 .dag
                 .downcast()
                 .expect("should not fail to downcast to dag:");